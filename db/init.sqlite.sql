@@ -1,10 +1,126 @@
-CREATE TABLE IF NOT EXISTS tasks (
+-- Accounts of the REST API, owning the rows of every other table
+CREATE TABLE IF NOT EXISTS users (
     id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    -- SHA-256 of the API token, hex encoded
+    token_hash TEXT NOT NULL UNIQUE
+);
+
+-- Task ids are unique per owner (single-user stores use owner 0, shared projects the
+-- largest unsigned 32-bit id)
+CREATE TABLE IF NOT EXISTS tasks (
+    owner_id INTEGER NOT NULL DEFAULT 0,
+    id INTEGER NOT NULL,
     desc TEXT NOT NULL,
     importance FLOAT NOT NULL,
     urgency FLOAT NOT NULL,
     status INTEGER NOT NULL,
     parent_task_id INTEGER NULL,
-    FOREIGN KEY (parent_task_id)
-        REFERENCES tasks(id)
+    notes TEXT NOT NULL DEFAULT '',
+    estimate INTEGER NULL,
+    due_date TEXT NULL,
+    deferred_until TEXT NULL,
+    placement TEXT NULL,
+    assignee_id INTEGER NULL,
+    follow_up TEXT NULL,
+    -- Only updated by writes that read the current one, see `Task::version`
+    version INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (owner_id, id),
+    FOREIGN KEY (owner_id, parent_task_id)
+        REFERENCES tasks(owner_id, id)
+);
+
+CREATE TABLE IF NOT EXISTS task_tags (
+    owner_id INTEGER NOT NULL DEFAULT 0,
+    task_id INTEGER NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (owner_id, task_id, tag),
+    FOREIGN KEY (owner_id, task_id)
+        REFERENCES tasks(owner_id, id)
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS tasks_fts USING fts5(desc, owner_id UNINDEXED, task_id UNINDEXED);
+
+CREATE TABLE IF NOT EXISTS task_links (
+    owner_id INTEGER NOT NULL DEFAULT 0,
+    task_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    PRIMARY KEY (owner_id, task_id, position),
+    FOREIGN KEY (owner_id, task_id)
+        REFERENCES tasks(owner_id, id)
+);
+
+CREATE TABLE IF NOT EXISTS task_checklist (
+    owner_id INTEGER NOT NULL DEFAULT 0,
+    task_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    done BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (owner_id, task_id, position),
+    FOREIGN KEY (owner_id, task_id)
+        REFERENCES tasks(owner_id, id)
+);
+
+-- No foreign key, so the tracked time outlives deleted tasks
+CREATE TABLE IF NOT EXISTS time_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    owner_id INTEGER NOT NULL DEFAULT 0,
+    task_id INTEGER NOT NULL,
+    start INTEGER NOT NULL,
+    stop INTEGER NULL,
+    note TEXT NOT NULL DEFAULT ''
+);
+
+-- One row per planned task. No foreign key either, deleted tasks are skipped when rendering
+CREATE TABLE IF NOT EXISTS daily_plans (
+    owner_id INTEGER NOT NULL DEFAULT 0,
+    date TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    task_id INTEGER NOT NULL,
+    PRIMARY KEY (owner_id, date, position)
+);
+
+-- Task subtrees stored as JSON, since they're only ever read whole
+CREATE TABLE IF NOT EXISTS templates (
+    owner_id INTEGER NOT NULL DEFAULT 0,
+    name TEXT NOT NULL,
+    task TEXT NOT NULL,
+    PRIMARY KEY (owner_id, name)
+);
+
+-- People tasks get delegated to, see `tasks.assignee_id`
+CREATE TABLE IF NOT EXISTS people (
+    owner_id INTEGER NOT NULL DEFAULT 0,
+    id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (owner_id, id)
+);
+
+-- Roles (viewer, editor or owner) in the shared projects, which are top-level tasks
+CREATE TABLE IF NOT EXISTS project_members (
+    owner_id INTEGER NOT NULL DEFAULT 0,
+    project_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    role TEXT NOT NULL,
+    PRIMARY KEY (owner_id, project_id, user_id),
+    FOREIGN KEY (owner_id, project_id)
+        REFERENCES tasks(owner_id, id),
+    FOREIGN KEY (user_id)
+        REFERENCES users(id)
+);
+
+-- The server's tasks as of the last sync, as JSON (along with their parent). No foreign key,
+-- as they may be long gone locally
+CREATE TABLE IF NOT EXISTS sync_base (
+    owner_id INTEGER NOT NULL DEFAULT 0,
+    task_id INTEGER NOT NULL,
+    task TEXT NOT NULL,
+    PRIMARY KEY (owner_id, task_id)
+);
+
+-- How the days get planned (columns, capacities), as JSON
+CREATE TABLE IF NOT EXISTS planning_settings (
+    owner_id INTEGER PRIMARY KEY,
+    settings TEXT NOT NULL
 );
@@ -1,18 +1,29 @@
 #[cfg(feature = "sqlite")]
 pub mod sqlite {
+    use crate::core::accounts::User;
     use crate::core::tasks::{
+        Oswald,
         Task,
         TaskStatus,
+        ChecklistItem,
         BoxTaskVec,
-
+        VersionConflict,
     };
-    use crate::ports::DataStore;
+    use crate::core::delegation::Person;
+    use crate::core::planning::{DailyPlan, PlanningSettings};
+    use crate::core::sharing::Membership;
+    use crate::core::sync::SyncedTask;
+    use crate::core::templates::Template;
+    use crate::core::tracking::TimeEntry;
+    use crate::ports::{AccountStore, DataStore};
     use async_trait::async_trait;
     use sqlx::{
         query,
         query_as,
+        query_scalar,
         sqlite::{
             Sqlite,
+            SqliteConnection,
             SqlitePoolOptions,
             SqlitePool,
             SqliteRow,
@@ -27,29 +38,68 @@ pub mod sqlite {
         Encode,
         FromRow,
         Row,
+        Connection,
         error::BoxDynError,
         Error
     };
     use async_recursion::async_recursion;
+    use chrono::DateTime;
+    use std::collections::{HashMap, HashSet};
+    use std::io::Write;
+    use std::sync::{Arc, Mutex, PoisonError};
+    use tokio::sync::{Mutex as AsyncMutex, OnceCell};
 
 #[derive(Debug)]
     pub struct SQLiteStore {
         conn: String,
+        // Every row belongs to an owner, so one database can hold several users' forests
+        owner_id: u32,
+        // Task versions as last read or written here, which the rows must still be at to be updated
+        known_versions: Arc<Mutex<HashMap<u32, u32>>>,
+        // Set once the database was brought up to `SCHEMA_VERSION`
+        migrated: Arc<OnceCell<()>>,
     }
 
     impl SQLiteStore {
         pub fn new(conn: String) -> Self {
-            SQLiteStore { conn }
+            SQLiteStore::with_owner(conn, 0)
+        }
+        pub fn with_owner(conn: String, owner_id: u32) -> Self {
+            SQLiteStore {
+                conn,
+                owner_id,
+                known_versions: Arc::new(Mutex::new(HashMap::new())),
+                migrated: Arc::new(OnceCell::new())
+            }
+        }
+        async fn connect(&self) -> anyhow::Result<SqlitePool> {
+            let pool = SqlitePoolOptions::new()
+                .max_connections(MAX_CONNECTIONS)
+                .connect(&self.conn)
+                .await?;
+            self.migrated.get_or_try_init(|| migrate(&pool)).await?;
+            Ok(pool)
+        }
+        fn remember_versions(&self, tasks: &[&Task]) {
+            let mut known_versions = self.known_versions.lock().unwrap_or_else(PoisonError::into_inner);
+            for task in tasks {
+                known_versions.insert(task.id, task.version);
+                for subtask in task.get_all_subtasks() {
+                    known_versions.insert(subtask.id, subtask.version);
+                }
+            }
         }
         #[async_recursion]
         async fn fill_subtasks<'a>(&'a self, task: &'a Task, pool: &'a SqlitePool) -> BoxTaskVec {
             let mut results: BoxTaskVec = vec![];
-            let raw_subtasks_query = query_as("SELECT * FROM tasks WHERE parent_task_id = ?;")
+            let raw_subtasks_query = query_as("SELECT * FROM tasks WHERE owner_id = ? AND parent_task_id = ?;")
+                .bind(self.owner_id)
                 .bind(task.id)
                 .fetch_all(pool);
             match raw_subtasks_query.await {
                 Ok(raw_subtasks) => {
                     for mut raw_subtask in raw_subtasks.into_iter() {
+                        self.fill_details(&mut raw_subtask, pool).await;
                         let microtasks = Box::pin(self.fill_subtasks(&raw_subtask, pool)).await;
                         raw_subtask.add_subtasks_vec(microtasks);
                         results.push(Box::new(raw_subtask));
@@ -59,8 +109,125 @@ pub mod sqlite {
             };
             results
         }
+        // Tags, links and checklist items
+        async fn fill_details(&self, task: &mut Task, pool: &SqlitePool) {
+            let raw_tags_query = query_scalar::<_, String>("SELECT tag FROM task_tags WHERE owner_id = ? AND task_id = ?;")
+                .bind(self.owner_id)
+                .bind(task.id)
+                .fetch_all(pool);
+            match raw_tags_query.await {
+                Ok(tags) => { task.tags = tags.into_iter().collect(); },
+                Err(_) => { println!("Couldn't retrieve tags for task #{}", task.id) }
+            };
+
+            let raw_links_query = query_scalar::<_, String>("SELECT url FROM task_links WHERE owner_id = ? AND task_id = ? ORDER BY position;")
+                .bind(self.owner_id)
+                .bind(task.id)
+                .fetch_all(pool);
+            match raw_links_query.await {
+                Ok(links) => { task.links = links; },
+                Err(_) => { println!("Couldn't retrieve links for task #{}", task.id) }
+            };
+
+            let raw_checklist_query = query_as::<_, (String, bool)>("SELECT text, done FROM task_checklist WHERE owner_id = ? AND task_id = ? ORDER BY position;")
+                .bind(self.owner_id)
+                .bind(task.id)
+                .fetch_all(pool);
+            match raw_checklist_query.await {
+                Ok(items) => {
+                    task.checklist = items.into_iter()
+                        .map(|(text, done)| ChecklistItem { text, done })
+                        .collect();
+                },
+                Err(_) => { println!("Couldn't retrieve checklist for task #{}", task.id) }
+            };
+        }
+        async fn write_details(&self, conn: &mut SqliteConnection, task: &Task) -> anyhow::Result<()> {
+            query("DELETE FROM task_tags WHERE owner_id = ? AND task_id = ?;")
+                .bind(self.owner_id)
+                .bind(task.id)
+                .execute(&mut *conn).await?;
+            for tag in task.tags.iter() {
+                query("INSERT INTO task_tags (owner_id, task_id, tag) VALUES (?,?,?);")
+                    .bind(self.owner_id)
+                    .bind(task.id)
+                    .bind(tag)
+                    .execute(&mut *conn).await?;
+            }
+
+            query("DELETE FROM task_links WHERE owner_id = ? AND task_id = ?;")
+                .bind(self.owner_id)
+                .bind(task.id)
+                .execute(&mut *conn).await?;
+            for (position, url) in task.links.iter().enumerate() {
+                query("INSERT INTO task_links (owner_id, task_id, position, url) VALUES (?,?,?,?);")
+                    .bind(self.owner_id)
+                    .bind(task.id)
+                    .bind(position as u32)
+                    .bind(url)
+                    .execute(&mut *conn).await?;
+            }
+
+            query("DELETE FROM task_checklist WHERE owner_id = ? AND task_id = ?;")
+                .bind(self.owner_id)
+                .bind(task.id)
+                .execute(&mut *conn).await?;
+            for (position, item) in task.checklist.iter().enumerate() {
+                query("INSERT INTO task_checklist (owner_id, task_id, position, text, done) VALUES (?,?,?,?,?);")
+                    .bind(self.owner_id)
+                    .bind(task.id)
+                    .bind(position as u32)
+                    .bind(&item.text)
+                    .bind(item.done)
+                    .execute(&mut *conn).await?;
+            }
+            Ok(())
+        }
+        async fn write_search_text(&self, conn: &mut SqliteConnection, task: &Task) -> anyhow::Result<()> {
+            query("DELETE FROM tasks_fts WHERE owner_id = ? AND task_id = ?;")
+                .bind(self.owner_id)
+                .bind(task.id)
+                .execute(&mut *conn).await?;
+            query("INSERT INTO tasks_fts (owner_id, task_id, desc) VALUES (?,?,?);")
+                .bind(self.owner_id)
+                .bind(task.id)
+                .bind(&task.desc)
+                .execute(&mut *conn).await?;
+            Ok(())
+        }
+        // Ranked task ids whose description contains words starting with each of the searched words
+        pub async fn search(&self, text: &str) -> anyhow::Result<Vec<u32>> {
+            let pool = self.connect().await?;
+            let match_expr: Vec<String> = text
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .map(|word| format!("\"{word}\"*"))
+                .collect();
+            if match_expr.is_empty() {
+                return Ok(vec![]);
+            }
+            let ids = query_scalar("SELECT task_id FROM tasks_fts WHERE tasks_fts MATCH ? AND owner_id = ? ORDER BY rank;")
+                .bind(match_expr.join(" "))
+                .bind(self.owner_id)
+                .fetch_all(&pool)
+                .await?;
+            Ok(ids)
+        }
+        // One row per time entry: task_id,start,stop,seconds,note (times in RFC 3339)
+        pub async fn export_time_entries_csv(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+            let entries = self.read_time_entries().await?;
+            writeln!(writer, "task_id,start,stop,seconds,note")?;
+            for entry in entries {
+                let stop = entry.stop.map(|stop| stop.to_rfc3339()).unwrap_or_default();
+                let seconds = entry.stop.map(|stop| (stop - entry.start).num_seconds().to_string()).unwrap_or_default();
+                let note = format!("\"{}\"", entry.note.replace('"', "\"\""));
+                writeln!(writer, "{},{},{},{},{}", entry.task_id, entry.start.to_rfc3339(), stop, seconds, note)?;
+            }
+            Ok(())
+        }
         async fn read_orphans(&self, pool: &SqlitePool) -> anyhow::Result<BoxTaskVec> {
-            let orphans: Vec<Box<Task>> = query_as("SELECT * FROM tasks WHERE parent_task_id ISNULL;")
+            let orphans: Vec<Box<Task>> = query_as("SELECT * FROM tasks WHERE owner_id = ? AND parent_task_id ISNULL;")
+                .bind(self.owner_id)
                 .fetch_all(pool)
                 .await?
                 .into_iter()
@@ -68,32 +235,138 @@ pub mod sqlite {
                 .collect();
             Ok(orphans)
         }
+        // Rows are only updated while they're at the version last seen here, so the changes made
+        // elsewhere in the meantime aren't overwritten
         #[async_recursion]
-        async fn write_tasks_helper(&self, pool: &SqlitePool, tasks: Vec<&Task>, parent_id: Option<u32>) -> anyhow::Result<()> {
+        async fn write_tasks_helper(
+            &self,
+            conn: &mut SqliteConnection,
+            known_versions: &HashMap<u32, u32>,
+            tasks: Vec<&'async_recursion Task>,
+            parent_id: Option<u32>
+        ) -> anyhow::Result<()> {
             for task in tasks {
-                dbg!(task);
-                let _ = query("REPLACE INTO tasks VALUES (?,?,?,?,?);")
-                    .bind(task.id)
+                let known_version = known_versions.get(&task.id).copied();
+                let raw_query = match known_version {
+                    Some(_) => "UPDATE tasks SET desc = ?, importance = ?, urgency = ?, status = ?, parent_task_id = ?, notes = ?, estimate = ?, due_date = ?, deferred_until = ?, placement = ?, assignee_id = ?, follow_up = ?, version = ?
+                        WHERE owner_id = ? AND id = ? AND version = ?;",
+                    None => "INSERT INTO tasks (desc, importance, urgency, status, parent_task_id, notes, estimate, due_date, deferred_until, placement, assignee_id, follow_up, version, owner_id, id)
+                        VALUES (?,?,?,?,?,?,?,?,?,?,?,?,?,?,?);"
+                };
+                let mut task_query = query(raw_query)
+                    .bind(&task.desc)
                     .bind(task.importance)
                     .bind(task.urgency)
                     .bind(task.status as u8)
                     .bind(parent_id)
-                    .execute(pool).await;
-                let _ = self.write_tasks_helper(pool, task.get_subtasks(), Some(task.id)).await;
+                    .bind(&task.notes)
+                    .bind(task.estimate)
+                    .bind(task.due_date.map(|date| date.to_string()))
+                    .bind(task.deferred_until.map(|date| date.to_string()))
+                    .bind(task.placement.map(|placement| placement.to_string()))
+                    .bind(task.assignee)
+                    .bind(task.follow_up.map(|date| date.to_string()))
+                    .bind(task.version)
+                    .bind(self.owner_id)
+                    .bind(task.id);
+                if let Some(version) = known_version {
+                    task_query = task_query.bind(version);
+                }
+                match task_query.execute(&mut *conn).await {
+                    Ok(result) if result.rows_affected() == 0 => {
+                        return Err(VersionConflict(format!("Task #{} was changed elsewhere since it was read", task.id)).into());
+                    },
+                    Err(err) if err.as_database_error().is_some_and(|err| err.is_unique_violation()) => {
+                        return Err(VersionConflict(format!("Task #{} was added elsewhere since the tasks were read", task.id)).into());
+                    },
+                    result => { result?; }
+                }
+                self.write_details(conn, task).await?;
+                self.write_search_text(conn, task).await?;
+                self.write_tasks_helper(conn, known_versions, task.get_subtasks(), Some(task.id)).await?;
             }
             Ok(())
         }
+        // Drops the rows of the tasks read or written here that aren't around anymore, unless
+        // they changed in the meantime. Returns their ids.
+        async fn delete_missing_tasks(
+            &self,
+            conn: &mut SqliteConnection,
+            known_versions: &HashMap<u32, u32>,
+            tasks: &[&Task]
+        ) -> anyhow::Result<Vec<u32>> {
+            let mut ids: HashSet<u32> = HashSet::new();
+            for task in tasks {
+                ids.insert(task.id);
+                ids.extend(task.get_all_subtasks().into_iter().map(|subtask| subtask.id));
+            }
+            let deleted_ids: Vec<u32> = known_versions.keys().filter(|id| !ids.contains(id)).copied().collect();
+            if deleted_ids.is_empty() {
+                return Ok(deleted_ids);
+            }
+            for id in deleted_ids.iter() {
+                let version: Option<u32> = query_scalar("SELECT version FROM tasks WHERE owner_id = ? AND id = ?;")
+                    .bind(self.owner_id)
+                    .bind(id)
+                    .fetch_optional(&mut *conn).await?;
+                if version.is_some_and(|version| Some(&version) != known_versions.get(id)) {
+                    return Err(VersionConflict(format!("Task #{id} was changed elsewhere since it was read")).into());
+                }
+            }
+            // All at once, so the foreign keys only get checked once the whole subtrees are gone
+            let raw_ids = serde_json::to_string(&deleted_ids)?;
+            for raw_query in [
+                "DELETE FROM task_tags WHERE owner_id = ? AND task_id IN (SELECT value FROM json_each(?));",
+                "DELETE FROM task_links WHERE owner_id = ? AND task_id IN (SELECT value FROM json_each(?));",
+                "DELETE FROM task_checklist WHERE owner_id = ? AND task_id IN (SELECT value FROM json_each(?));",
+                "DELETE FROM tasks_fts WHERE owner_id = ? AND task_id IN (SELECT value FROM json_each(?));",
+                "DELETE FROM project_members WHERE owner_id = ? AND project_id IN (SELECT value FROM json_each(?));",
+                "DELETE FROM tasks WHERE owner_id = ? AND id IN (SELECT value FROM json_each(?));",
+            ] {
+                query(raw_query)
+                    .bind(self.owner_id)
+                    .bind(&raw_ids)
+                    .execute(&mut *conn).await?;
+            }
+            Ok(deleted_ids)
+        }
 
     }
     impl<'r> FromRow<'r, SqliteRow> for Task {
         fn from_row(row: &'r SqliteRow) -> Result<Self, Error> {
-            let task = Task::new(
+            let mut task = Task::new(
                 row.try_get("id")?,
                 row.try_get("desc")?,
                 row.try_get("importance")?,
                 row.try_get("urgency")?,
                 row.try_get("status")?,
             );
+            task.notes = row.try_get("notes")?;
+            task.estimate = row.try_get("estimate")?;
+            // Dates are stored as ISO 8601, e.g. `2024-08-01`
+            let due_date: Option<String> = row.try_get("due_date")?;
+            task.due_date = due_date
+                .map(|raw_date| raw_date.parse())
+                .transpose()
+                .map_err(|err| Error::Decode(Box::new(err)))?;
+            let deferred_until: Option<String> = row.try_get("deferred_until")?;
+            task.deferred_until = deferred_until
+                .map(|raw_date| raw_date.parse())
+                .transpose()
+                .map_err(|err| Error::Decode(Box::new(err)))?;
+            // Either `backlog` or the pinned day
+            let placement: Option<String> = row.try_get("placement")?;
+            task.placement = placement
+                .map(|raw_placement| raw_placement.parse())
+                .transpose()
+                .map_err(|err: anyhow::Error| Error::Decode(err.into()))?;
+            task.assignee = row.try_get("assignee_id")?;
+            task.version = row.try_get("version")?;
+            let follow_up: Option<String> = row.try_get("follow_up")?;
+            task.follow_up = follow_up
+                .map(|raw_date| raw_date.parse())
+                .transpose()
+                .map_err(|err| Error::Decode(Box::new(err)))?;
             Ok(task)
         }
     }
@@ -120,7 +393,7 @@ pub mod sqlite {
         }
     }
 
-    impl<'r> Type<Sqlite> for TaskStatus {
+    impl Type<Sqlite> for TaskStatus {
         fn type_info() -> SqliteTypeInfo {
             <i32 as Type<Sqlite>>::type_info()
         }
@@ -130,34 +403,960 @@ pub mod sqlite {
     }
 
     const MAX_CONNECTIONS: u32 = 5;
+    const SCHEMA: &str = include_str!("../db/init.sqlite.sql");
+    // Kept in `PRAGMA user_version`, bumped along with every new step of `migrate`
+    const SCHEMA_VERSION: u32 = 2;
+    // So the stores sharing a database don't migrate it at the same time
+    static MIGRATION_LOCK: AsyncMutex<()> = AsyncMutex::const_new(());
+
+    // Brings the database up to `SCHEMA_VERSION`, creating it on the way if it's empty
+    async fn migrate(pool: &SqlitePool) -> anyhow::Result<()> {
+        let _lock = MIGRATION_LOCK.lock().await;
+        let mut conn = pool.acquire().await?;
+        let version: u32 = query_scalar("PRAGMA user_version;").fetch_one(&mut *conn).await?;
+        if version >= SCHEMA_VERSION {
+            return Ok(());
+        }
+        // The tables get rebuilt under the rows referencing them. Can't be toggled within a
+        // transaction, hence around it.
+        query("PRAGMA foreign_keys = OFF;").execute(&mut *conn).await?;
+        let result = migrate_from(&mut conn, version).await;
+        query("PRAGMA foreign_keys = ON;").execute(&mut *conn).await?;
+        result
+    }
+    async fn migrate_from(conn: &mut SqliteConnection, version: u32) -> anyhow::Result<()> {
+        let mut transaction = conn.begin().await?;
+        // 1: Owners on every row, tasks keyed by `(owner_id, id)` and the columns and tables
+        // added since, on top of whatever the unversioned databases were created with
+        if version < 1 {
+            query("DROP TABLE IF EXISTS tasks_fts;").execute(&mut *transaction).await?;
+            for statement in SCHEMA.split(';').filter(|statement| !statement.trim().is_empty()) {
+                rebuild_table(&mut transaction, statement).await?;
+            }
+        }
+        // 2: The search index back, filled with the tasks that were written without it
+        if version < 2 {
+            query("DROP TABLE IF EXISTS tasks_fts;").execute(&mut *transaction).await?;
+            for statement in SCHEMA.split(';').filter(|statement| statement.contains("tasks_fts")) {
+                query(statement).execute(&mut *transaction).await?;
+            }
+            query("INSERT INTO tasks_fts (owner_id, task_id, desc) SELECT owner_id, id, desc FROM tasks;")
+                .execute(&mut *transaction).await?;
+        }
+        query(&format!("PRAGMA user_version = {SCHEMA_VERSION};")).execute(&mut *transaction).await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+    // Creates the table of the statement anew, copying over the columns both versions have
+    async fn rebuild_table(conn: &mut SqliteConnection, statement: &str) -> anyhow::Result<()> {
+        let name = statement.split("CREATE TABLE IF NOT EXISTS ")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next());
+        let Some(name) = name else {
+            query(statement).execute(&mut *conn).await?;
+            return Ok(());
+        };
+        let old_columns = get_columns(conn, name).await?;
+        if old_columns.is_empty() {
+            query(statement).execute(&mut *conn).await?;
+            return Ok(());
+        }
+        let new_name = format!("{name}_new");
+        let new_statement = statement.replacen(&format!("IF NOT EXISTS {name} "), &format!("{new_name} "), 1);
+        query(&new_statement).execute(&mut *conn).await?;
+        let columns = get_columns(conn, &new_name).await?
+            .into_iter()
+            .filter(|column| old_columns.contains(column))
+            .map(|column| format!("\"{column}\""))
+            .collect::<Vec<String>>()
+            .join(", ");
+        query(&format!("INSERT INTO {new_name} ({columns}) SELECT {columns} FROM {name};")).execute(&mut *conn).await?;
+        query(&format!("DROP TABLE {name};")).execute(&mut *conn).await?;
+        query(&format!("ALTER TABLE {new_name} RENAME TO {name};")).execute(&mut *conn).await?;
+        Ok(())
+    }
+    async fn get_columns(conn: &mut SqliteConnection, table: &str) -> anyhow::Result<Vec<String>> {
+        let columns = query_scalar("SELECT name FROM pragma_table_info(?);")
+            .bind(table)
+            .fetch_all(&mut *conn)
+            .await?;
+        Ok(columns)
+    }
+    // Owner of the projects shared between users, out of the way of the user ids
+    const SHARED_OWNER_ID: u32 = u32::MAX;
 
 #[async_trait]
     impl DataStore for SQLiteStore {
         async fn read(&self) -> anyhow::Result<BoxTaskVec> {
-            let pool = SqlitePoolOptions::new()
-                .max_connections(MAX_CONNECTIONS)
-                .connect(&self.conn)
-                .await?;
+            let pool = self.connect().await?;
 
             let mut loaded_orphans: BoxTaskVec = vec![];
 
             let orphans = self.read_orphans(&pool).await?;
             for mut orphan in orphans.into_iter() {
+                self.fill_details(&mut orphan, &pool).await;
                 let subtasks = Box::pin(self.fill_subtasks(&orphan, &pool)).await;
                 orphan.add_subtasks_vec(subtasks);
                 loaded_orphans.push(orphan);
             }
+            self.known_versions.lock().unwrap_or_else(PoisonError::into_inner).clear();
+            self.remember_versions(&loaded_orphans.iter().map(|orphan| orphan.as_ref()).collect::<Vec<&Task>>());
             Ok(loaded_orphans)
         }
 
         async fn write(&self, tasks: Vec<&Task>) -> anyhow::Result<()> {
-            let pool = SqlitePoolOptions::new()
-                .max_connections(MAX_CONNECTIONS)
-                .connect(&self.conn)
+            let pool = self.connect().await?;
+            // All or nothing, so a conflict leaves the stored tasks as they were
+            let known_versions = self.known_versions.lock().unwrap_or_else(PoisonError::into_inner).clone();
+            let mut transaction = pool.begin().await?;
+            self.write_tasks_helper(&mut transaction, &known_versions, tasks.clone(), None).await?;
+            let deleted_ids = self.delete_missing_tasks(&mut transaction, &known_versions, &tasks).await?;
+            transaction.commit().await?;
+            let mut remembered_versions = self.known_versions.lock().unwrap_or_else(PoisonError::into_inner);
+            deleted_ids.iter().for_each(|id| { remembered_versions.remove(id); });
+            drop(remembered_versions);
+            self.remember_versions(&tasks);
+            Ok(())
+        }
+
+        async fn read_time_entries(&self) -> anyhow::Result<Vec<TimeEntry>> {
+            let pool = self.connect().await?;
+            let raw_entries: Vec<(u32, i64, Option<i64>, String)> = query_as("SELECT task_id, start, stop, note FROM time_entries WHERE owner_id = ? ORDER BY start;")
+                .bind(self.owner_id)
+                .fetch_all(&pool)
+                .await?;
+            let entries = raw_entries.into_iter()
+                .filter_map(|(task_id, start, stop, note)| {
+                    Some(TimeEntry {
+                        task_id,
+                        start: DateTime::from_timestamp(start, 0)?,
+                        stop: stop.and_then(|stop| DateTime::from_timestamp(stop, 0)),
+                        note
+                    })
+                })
+                .collect();
+            Ok(entries)
+        }
+
+        async fn write_time_entries(&self, entries: &[TimeEntry]) -> anyhow::Result<()> {
+            let pool = self.connect().await?;
+            let mut transaction = pool.begin().await?;
+            query("DELETE FROM time_entries WHERE owner_id = ?;")
+                .bind(self.owner_id)
+                .execute(&mut *transaction).await?;
+            for entry in entries {
+                query("INSERT INTO time_entries (owner_id, task_id, start, stop, note) VALUES (?,?,?,?,?);")
+                    .bind(self.owner_id)
+                    .bind(entry.task_id)
+                    .bind(entry.start.timestamp())
+                    .bind(entry.stop.map(|stop| stop.timestamp()))
+                    .bind(&entry.note)
+                    .execute(&mut *transaction).await?;
+            }
+            transaction.commit().await?;
+            Ok(())
+        }
+
+        async fn read_daily_plan(&self) -> anyhow::Result<Option<DailyPlan>> {
+            let pool = self.connect().await?;
+            let Some(raw_date): Option<String> = query_scalar("SELECT MAX(date) FROM daily_plans WHERE owner_id = ?;")
+                .bind(self.owner_id)
+                .fetch_one(&pool)
+                .await? else {
+                return Ok(None);
+            };
+            let task_ids: Vec<u32> = query_scalar("SELECT task_id FROM daily_plans WHERE owner_id = ? AND date = ? ORDER BY position;")
+                .bind(self.owner_id)
+                .bind(&raw_date)
+                .fetch_all(&pool)
+                .await?;
+            Ok(Some(DailyPlan::new(raw_date.parse()?, task_ids)))
+        }
+
+        async fn write_daily_plan(&self, plan: &DailyPlan) -> anyhow::Result<()> {
+            let pool = self.connect().await?;
+            let raw_date = plan.date.to_string();
+            let mut transaction = pool.begin().await?;
+            query("DELETE FROM daily_plans WHERE owner_id = ? AND date = ?;")
+                .bind(self.owner_id)
+                .bind(&raw_date)
+                .execute(&mut *transaction).await?;
+            for (position, task_id) in plan.task_ids.iter().enumerate() {
+                query("INSERT INTO daily_plans (owner_id, date, position, task_id) VALUES (?,?,?,?);")
+                    .bind(self.owner_id)
+                    .bind(&raw_date)
+                    .bind(position as u32)
+                    .bind(task_id)
+                    .execute(&mut *transaction).await?;
+            }
+            transaction.commit().await?;
+            Ok(())
+        }
+
+        // Templates aren't queried into, so each subtree is kept as JSON
+        async fn read_templates(&self) -> anyhow::Result<Vec<Template>> {
+            let pool = self.connect().await?;
+            let raw_templates: Vec<(String, String)> = query_as("SELECT name, task FROM templates WHERE owner_id = ? ORDER BY name;")
+                .bind(self.owner_id)
+                .fetch_all(&pool)
+                .await?;
+            raw_templates.into_iter()
+                .map(|(name, raw_task)| Ok(Template::new(&name, serde_json::from_str(&raw_task)?)))
+                .collect()
+        }
+
+        async fn write_templates(&self, templates: &[&Template]) -> anyhow::Result<()> {
+            let pool = self.connect().await?;
+            let mut transaction = pool.begin().await?;
+            query("DELETE FROM templates WHERE owner_id = ?;")
+                .bind(self.owner_id)
+                .execute(&mut *transaction).await?;
+            for template in templates {
+                query("INSERT INTO templates (owner_id, name, task) VALUES (?,?,?);")
+                    .bind(self.owner_id)
+                    .bind(&template.name)
+                    .bind(serde_json::to_string(&template.task)?)
+                    .execute(&mut *transaction).await?;
+            }
+            transaction.commit().await?;
+            Ok(())
+        }
+
+        // Kept as JSON too, it's only ever read whole
+        async fn read_sync_base(&self) -> anyhow::Result<Vec<SyncedTask>> {
+            let pool = self.connect().await?;
+            let raw_tasks: Vec<String> = query_scalar("SELECT task FROM sync_base WHERE owner_id = ? ORDER BY task_id;")
+                .bind(self.owner_id)
+                .fetch_all(&pool)
+                .await?;
+            raw_tasks.into_iter()
+                .map(|raw_task| Ok(serde_json::from_str(&raw_task)?))
+                .collect()
+        }
+
+        async fn write_sync_base(&self, tasks: &[&SyncedTask]) -> anyhow::Result<()> {
+            let pool = self.connect().await?;
+            let mut transaction = pool.begin().await?;
+            query("DELETE FROM sync_base WHERE owner_id = ?;")
+                .bind(self.owner_id)
+                .execute(&mut *transaction).await?;
+            for synced in tasks {
+                query("INSERT INTO sync_base (owner_id, task_id, task) VALUES (?,?,?);")
+                    .bind(self.owner_id)
+                    .bind(synced.task.id)
+                    .bind(serde_json::to_string(synced)?)
+                    .execute(&mut *transaction).await?;
+            }
+            transaction.commit().await?;
+            Ok(())
+        }
+
+        // One JSON document per owner
+        async fn read_planning_settings(&self) -> anyhow::Result<Option<PlanningSettings>> {
+            let pool = self.connect().await?;
+            let raw_settings: Option<String> = query_scalar("SELECT settings FROM planning_settings WHERE owner_id = ?;")
+                .bind(self.owner_id)
+                .fetch_optional(&pool)
+                .await?;
+            Ok(raw_settings.map(|raw_settings| serde_json::from_str(&raw_settings)).transpose()?)
+        }
+
+        async fn write_planning_settings(&self, settings: &PlanningSettings) -> anyhow::Result<()> {
+            let pool = self.connect().await?;
+            query("INSERT INTO planning_settings (owner_id, settings) VALUES (?,?)
+                ON CONFLICT(owner_id) DO UPDATE SET settings = excluded.settings;")
+                .bind(self.owner_id)
+                .bind(serde_json::to_string(settings)?)
+                .execute(&pool).await?;
+            Ok(())
+        }
+
+        async fn read_memberships(&self) -> anyhow::Result<Vec<Membership>> {
+            let pool = self.connect().await?;
+            let raw_memberships: Vec<(u32, u32, String)> = query_as("SELECT project_id, user_id, role FROM project_members WHERE owner_id = ?;")
+                .bind(self.owner_id)
+                .fetch_all(&pool)
+                .await?;
+            raw_memberships.into_iter()
+                .map(|(project_id, user_id, raw_role)| Ok(Membership { project_id, user_id, role: raw_role.parse()? }))
+                .collect()
+        }
+
+        async fn write_memberships(&self, memberships: &[Membership]) -> anyhow::Result<()> {
+            let pool = self.connect().await?;
+            let mut transaction = pool.begin().await?;
+            query("DELETE FROM project_members WHERE owner_id = ?;")
+                .bind(self.owner_id)
+                .execute(&mut *transaction).await?;
+            for membership in memberships {
+                query("INSERT INTO project_members (owner_id, project_id, user_id, role) VALUES (?,?,?,?);")
+                    .bind(self.owner_id)
+                    .bind(membership.project_id)
+                    .bind(membership.user_id)
+                    .bind(membership.role.to_string())
+                    .execute(&mut *transaction).await?;
+            }
+            transaction.commit().await?;
+            Ok(())
+        }
+
+        async fn read_people(&self) -> anyhow::Result<Vec<Person>> {
+            let pool = self.connect().await?;
+            let raw_people: Vec<(u32, String)> = query_as("SELECT id, name FROM people WHERE owner_id = ? ORDER BY id;")
+                .bind(self.owner_id)
+                .fetch_all(&pool)
+                .await?;
+            Ok(raw_people.into_iter().map(|(id, name)| Person::new(id, &name)).collect())
+        }
+
+        async fn write_people(&self, people: &[&Person]) -> anyhow::Result<()> {
+            let pool = self.connect().await?;
+            let mut transaction = pool.begin().await?;
+            query("DELETE FROM people WHERE owner_id = ?;")
+                .bind(self.owner_id)
+                .execute(&mut *transaction).await?;
+            for person in people {
+                query("INSERT INTO people (owner_id, id, name) VALUES (?,?,?);")
+                    .bind(self.owner_id)
+                    .bind(person.id)
+                    .bind(&person.name)
+                    .execute(&mut *transaction).await?;
+            }
+            transaction.commit().await?;
+            Ok(())
+        }
+    }
+
+#[async_trait]
+    impl AccountStore for SQLiteStore {
+        async fn read_users(&self) -> anyhow::Result<Vec<User>> {
+            let pool = self.connect().await?;
+            let raw_users: Vec<(u32, String, String)> = query_as("SELECT id, name, token_hash FROM users ORDER BY id;")
+                .fetch_all(&pool)
                 .await?;
-            let _ = self.write_tasks_helper(&pool, tasks, None).await;
+            Ok(raw_users.into_iter().map(|(id, name, token_hash)| User { id, name, token_hash }).collect())
+        }
+
+        async fn write_users(&self, users: &[&User]) -> anyhow::Result<()> {
+            let pool = self.connect().await?;
+            let mut transaction = pool.begin().await?;
+            for user in users {
+                query("INSERT INTO users (id, name, token_hash) VALUES (?,?,?)
+                    ON CONFLICT(id) DO UPDATE SET
+                        name = excluded.name,
+                        token_hash = excluded.token_hash;")
+                    .bind(user.id)
+                    .bind(&user.name)
+                    .bind(&user.token_hash)
+                    .execute(&mut *transaction).await?;
+            }
+            transaction.commit().await?;
+            Ok(())
+        }
+
+        fn get_oswald(&self, owner_id: u32) -> Oswald {
+            Oswald::new(SQLiteStore::with_owner(self.conn.clone(), owner_id))
+        }
+
+        fn get_shared_oswald(&self) -> Oswald {
+            Oswald::new(SQLiteStore::with_owner(self.conn.clone(), SHARED_OWNER_ID))
+        }
+    }
+
+    #[cfg(test)]
+    mod sqlite_tests {
+        use super::*;
+        use chrono::NaiveDate;
+        use std::collections::BTreeSet;
+
+        // A fresh database file, as every call opens its own pool
+        fn get_conn(name: &str) -> (String, std::path::PathBuf) {
+            let path = std::env::temp_dir().join(format!("tako-{name}-{}.sqlite", std::process::id()));
+            let _ = std::fs::remove_file(&path);
+            (format!("sqlite://{}?mode=rwc", path.display()), path)
+        }
+
+        #[tokio::test]
+        async fn test_migrate_unversioned_database() {
+            let (conn, path) = get_conn("migrate");
+            // As created by the first schema, before there were owners
+            let pool = SqlitePoolOptions::new().connect(&conn).await.unwrap();
+            query("CREATE TABLE tasks (
+                    id INTEGER PRIMARY KEY,
+                    desc TEXT NOT NULL,
+                    importance FLOAT NOT NULL,
+                    urgency FLOAT NOT NULL,
+                    status INTEGER NOT NULL,
+                    parent_task_id INTEGER NULL,
+                    FOREIGN KEY (parent_task_id) REFERENCES tasks(id)
+                );
+                CREATE TABLE task_tags (task_id INTEGER NOT NULL, tag TEXT NOT NULL);
+                INSERT INTO tasks VALUES (1, 'Parent', 1.0, 2.0, 1, NULL), (2, 'Child', 3.0, 4.0, 1, 1);
+                INSERT INTO task_tags VALUES (2, 'work');")
+                .execute(&pool).await.unwrap();
+            pool.close().await;
+
+            let store = SQLiteStore::new(conn.clone());
+            let tasks = store.read().await.unwrap();
+            assert_eq!(tasks.len(), 1);
+            assert_eq!(tasks[0].desc, "Parent");
+            let child = tasks[0].get_subtasks()[0];
+            assert_eq!(child.desc, "Child");
+            assert!(child.tags.contains("work"));
+
+            // The rows ended up with the default owner, and the new tables are there
+            let mut oswald = Oswald::new(SQLiteStore::new(conn.clone()));
+            oswald.load().await.unwrap();
+            oswald.add_task(Box::new(Task::new(3, "New".to_string(), 1.0, 1.0, TaskStatus::Open)));
+            oswald.save().await.unwrap();
+            let pool = SqlitePoolOptions::new().connect(&conn).await.unwrap();
+            let version: u32 = query_scalar("PRAGMA user_version;").fetch_one(&pool).await.unwrap();
+            assert_eq!(version, SCHEMA_VERSION);
+            let owners: Vec<u32> = query_scalar("SELECT DISTINCT owner_id FROM tasks;").fetch_all(&pool).await.unwrap();
+            assert_eq!(owners, vec![0]);
+            pool.close().await;
+
+            // Only ever once, with the search index filled on the way
+            assert_eq!(SQLiteStore::new(conn.clone()).read().await.unwrap().len(), 2);
+            assert_eq!(SQLiteStore::new(conn).search("chi").await.unwrap(), vec![2]);
+            let _ = std::fs::remove_file(&path);
+        }
+
+        fn new_task(id: u32, desc: &str) -> Box<Task> {
+            Box::new(Task::new(id, desc.to_owned(), 1.0, 1.0, TaskStatus::Open))
+        }
+
+        // What a store on the same database loads
+        async fn reload(conn: &str) -> Oswald {
+            let mut oswald = Oswald::new(SQLiteStore::new(conn.to_owned()));
+            oswald.load().await.unwrap();
+            oswald
+        }
+
+        #[tokio::test]
+        async fn test_tags_round_trip() {
+            let (conn, path) = get_conn("tags");
+            let mut oswald = Oswald::new(SQLiteStore::new(conn.clone()));
+            let mut parent = new_task(1, "Home");
+            parent.add_tag("diy");
+            let mut subtask = new_task(2, "Paint");
+            subtask.add_tag("@weekend");
+            subtask.add_tag("#Errands");
+            parent.add_subtask(subtask);
+            oswald.add_task(parent);
+            oswald.save().await.unwrap();
+
+            let loaded = reload(&conn).await;
+            assert_eq!(loaded.get_task(1).unwrap().tags, BTreeSet::from(["diy".to_owned()]));
+            assert_eq!(loaded.get_task(2).unwrap().tags, BTreeSet::from(["@weekend".to_owned(), "errands".to_owned()]));
+
+            // Removed tags don't come back
+            let mut subtask = loaded.get_task(2).unwrap().clone();
+            subtask.remove_tag("errands");
+            oswald.add_subtask(1, Box::new(subtask)).unwrap();
+            oswald.save().await.unwrap();
+            assert_eq!(reload(&conn).await.get_task(2).unwrap().tags, BTreeSet::from(["@weekend".to_owned()]));
+            let _ = std::fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_details_round_trip() {
+            let (conn, path) = get_conn("details");
+            let mut oswald = Oswald::new(SQLiteStore::new(conn.clone()));
+            let mut task = new_task(1, "Report");
+            task.notes = "Ask for the numbers,\nthen \"draft\"".to_owned();
+            task.links = vec!["https://example.com/b".to_owned(), "https://example.com/a".to_owned()];
+            task.add_checklist_item("Outline");
+            task.add_checklist_item("Draft");
+            task.checklist[1].done = true;
+            oswald.add_task(task.clone());
+            oswald.save().await.unwrap();
+
+            let loaded = reload(&conn).await;
+            let loaded_task = loaded.get_task(1).unwrap();
+            assert_eq!(loaded_task.notes, task.notes);
+            // In the order they were added
+            assert_eq!(loaded_task.links, task.links);
+            assert_eq!(loaded_task.checklist, task.checklist);
+
+            // Shorter lists replace the longer ones
+            task.links.pop();
+            task.checklist.remove(0);
+            oswald.add_task(task.clone());
+            oswald.save().await.unwrap();
+            let loaded = reload(&conn).await;
+            assert_eq!(loaded.get_task(1).unwrap().links, vec!["https://example.com/b".to_owned()]);
+            assert_eq!(loaded.get_task(1).unwrap().checklist, vec![ChecklistItem { text: "Draft".to_owned(), done: true }]);
+            let _ = std::fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_estimates_round_trip() {
+            let (conn, path) = get_conn("estimates");
+            let mut oswald = Oswald::new(SQLiteStore::new(conn.clone()));
+            let mut parent = new_task(1, "Report");
+            parent.estimate = Some(3);
+            let mut subtask = new_task(2, "Draft");
+            subtask.estimate = Some(5);
+            parent.add_subtask(subtask);
+            parent.add_subtask(new_task(3, "Slides"));
+            oswald.add_task(parent);
+            oswald.save().await.unwrap();
+
+            let loaded = reload(&conn).await;
+            let estimates = [1, 2, 3].map(|id| loaded.get_task(id).unwrap().estimate);
+            assert_eq!(estimates, [Some(3), Some(5), None]);
+            assert_eq!(loaded.get_task(1).unwrap().get_effort(1), 9);
+
+            // Cleared estimates stay cleared
+            let mut subtask = loaded.get_task(2).unwrap().clone();
+            subtask.estimate = None;
+            oswald.add_subtask(1, Box::new(subtask)).unwrap();
+            oswald.save().await.unwrap();
+            assert_eq!(reload(&conn).await.get_task(2).unwrap().estimate, None);
+            let _ = std::fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_time_entries_round_trip() {
+            use chrono::{TimeZone, Utc};
+
+            let (conn, path) = get_conn("time-entries");
+            let mut oswald = Oswald::new(SQLiteStore::new(conn.clone()));
+            oswald.add_task(new_task(1, "Report"));
+            oswald.add_task(new_task(2, "Slides"));
+            let start = Utc.with_ymd_and_hms(2024, 8, 1, 9, 0, 0).unwrap();
+            oswald.start_timer(1, "Numbers, \"final\" ones", start).unwrap();
+            oswald.stop_timer(1, start + chrono::Duration::minutes(90)).unwrap();
+            oswald.start_timer(2, "", start + chrono::Duration::hours(2)).unwrap();
+            oswald.save().await.unwrap();
+
+            let loaded = reload(&conn).await;
+            assert_eq!(loaded.get_time_entries(), oswald.get_time_entries());
+            assert!(loaded.get_time_entries()[1].is_running());
+
+            let mut csv = vec![];
+            SQLiteStore::new(conn).export_time_entries_csv(&mut csv).await.unwrap();
+            assert_eq!(String::from_utf8(csv).unwrap(), "task_id,start,stop,seconds,note\n\
+                1,2024-08-01T09:00:00+00:00,2024-08-01T10:30:00+00:00,5400,\"Numbers, \"\"final\"\" ones\"\n\
+                2,2024-08-01T11:00:00+00:00,,,\"\"\n");
+            let _ = std::fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_snoozes_round_trip() {
+            let (conn, path) = get_conn("snoozes");
+            let mut oswald = Oswald::new(SQLiteStore::new(conn.clone()));
+            oswald.add_task(new_task(1, "Report"));
+            let today = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+            let until = NaiveDate::from_ymd_opt(2024, 8, 12).unwrap();
+            oswald.snooze_task(1, until, today).unwrap();
+            oswald.save().await.unwrap();
+            assert_eq!(reload(&conn).await.get_task(1).unwrap().deferred_until, Some(until));
+
+            oswald.resurface_tasks(until);
+            oswald.save().await.unwrap();
+            assert_eq!(reload(&conn).await.get_task(1).unwrap().deferred_until, None);
+            let _ = std::fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_daily_plan_round_trip() {
+            let (conn, path) = get_conn("daily-plan");
+            let mut oswald = Oswald::new(SQLiteStore::new(conn.clone()));
+            [1, 2, 3].into_iter().for_each(|id| oswald.add_task(new_task(id, "Task")));
+            let today = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+            oswald.set_daily_plan(DailyPlan::new(today, vec![3, 1, 2]));
+            oswald.save().await.unwrap();
+            assert_eq!(reload(&conn).await.get_daily_plan(), Some(&DailyPlan::new(today, vec![3, 1, 2])));
+
+            // Replanning the day replaces its tasks, and the latest day's plan is the one loaded
+            oswald.set_daily_plan(DailyPlan::new(today, vec![2]));
+            oswald.save().await.unwrap();
+            assert_eq!(reload(&conn).await.get_daily_plan(), Some(&DailyPlan::new(today, vec![2])));
+            let tomorrow = today.succ_opt().unwrap();
+            oswald.set_daily_plan(DailyPlan::new(tomorrow, vec![1, 3]));
+            oswald.save().await.unwrap();
+            assert_eq!(reload(&conn).await.get_daily_plan(), Some(&DailyPlan::new(tomorrow, vec![1, 3])));
+            let _ = std::fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_placements_round_trip() {
+            use crate::core::tasks::Placement;
+
+            let (conn, path) = get_conn("placements");
+            let mut oswald = Oswald::new(SQLiteStore::new(conn.clone()));
+            [1, 2, 3].into_iter().for_each(|id| oswald.add_task(new_task(id, "Task")));
+            let day = NaiveDate::from_ymd_opt(2024, 8, 5).unwrap();
+            oswald.place_task(1, Some(Placement::Day(day))).unwrap();
+            oswald.place_task(2, Some(Placement::Backlog)).unwrap();
+            oswald.save().await.unwrap();
+
+            let loaded = reload(&conn).await;
+            let placements = [1, 2, 3].map(|id| loaded.get_task(id).unwrap().placement);
+            assert_eq!(placements, [Some(Placement::Day(day)), Some(Placement::Backlog), None]);
+
+            // Handed back to the distribution
+            oswald.place_task(1, None).unwrap();
+            oswald.save().await.unwrap();
+            assert_eq!(reload(&conn).await.get_task(1).unwrap().placement, None);
+            let _ = std::fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_templates_round_trip() {
+            let (conn, path) = get_conn("templates");
+            let mut oswald = Oswald::new(SQLiteStore::new(conn.clone()));
+            let mut task = new_task(1, "Release {version}");
+            task.notes = "Check the {channel} notes".to_owned();
+            let mut subtask = new_task(2, "Tag {version}");
+            subtask.add_tag("git");
+            subtask.estimate = Some(2);
+            task.add_subtask(subtask);
+            oswald.add_template(Template::new("Release", *task)).unwrap();
+            oswald.add_template(Template::new("Review", *new_task(1, "Review {pr}"))).unwrap();
+            oswald.save().await.unwrap();
+
+            let loaded = reload(&conn).await;
+            let names: Vec<&str> = loaded.get_templates().into_iter().map(|template| template.name.as_str()).collect();
+            assert_eq!(names, vec!["Release", "Review"]);
+            let template = loaded.get_template("Release").unwrap();
+            assert_eq!(template.task.notes, "Check the {channel} notes");
+            let subtask = template.task.get_subtask(2).unwrap();
+            assert_eq!((subtask.desc.as_str(), subtask.estimate), ("Tag {version}", Some(2)));
+            assert!(subtask.tags.contains("git"));
+
+            oswald.delete_template("Review").unwrap();
+            oswald.save().await.unwrap();
+            assert!(reload(&conn).await.get_template("Review").is_none());
+            let _ = std::fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_delegation_round_trip() {
+            let (conn, path) = get_conn("delegation");
+            let mut oswald = Oswald::new(SQLiteStore::new(conn.clone()));
+            oswald.add_person(Person::new(1, "Ada")).unwrap();
+            oswald.add_person(Person::new(2, "Grace")).unwrap();
+            oswald.add_task(new_task(1, "Report"));
+            let follow_up = NaiveDate::from_ymd_opt(2024, 8, 5).unwrap();
+            oswald.assign_task(1, Some(2), Some(follow_up)).unwrap();
+            oswald.save().await.unwrap();
+
+            let loaded = reload(&conn).await;
+            assert_eq!(loaded.get_people(), vec![&Person::new(1, "Ada"), &Person::new(2, "Grace")]);
+            let task = loaded.get_task(1).unwrap();
+            assert_eq!((task.assignee, task.follow_up), (Some(2), Some(follow_up)));
+
+            // Deleting the person takes their tasks back
+            oswald.delete_person(2).unwrap();
+            oswald.save().await.unwrap();
+            let loaded = reload(&conn).await;
+            assert_eq!(loaded.get_people(), vec![&Person::new(1, "Ada")]);
+            let task = loaded.get_task(1).unwrap();
+            assert_eq!((task.assignee, task.follow_up), (None, None));
+            let _ = std::fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_stale_writes_are_rejected() {
+            let (conn, path) = get_conn("stale");
+            let mut first = Oswald::new(SQLiteStore::new(conn.clone()));
+            first.add_task(new_task(1, "Report"));
+            first.save().await.unwrap();
+            let mut second = Oswald::new(SQLiteStore::new(conn.clone()));
+            second.load().await.unwrap();
+
+            first.add_task(new_task(1, "Draft"));
+            first.save().await.unwrap();
+            second.add_task(new_task(1, "Stale"));
+            second.add_task(new_task(2, "Slides"));
+            let err = second.save().await.unwrap_err();
+            assert!(err.is::<VersionConflict>());
+
+            // Nothing of the rejected write made it
+            let tasks = SQLiteStore::new(conn.clone()).read().await.unwrap();
+            assert_eq!(tasks.len(), 1);
+            assert_eq!((tasks[0].desc.as_str(), tasks[0].version), ("Draft", 2));
 
+            // Neither do tasks added elsewhere under the same id
+            second.reload().await.unwrap();
+            first.add_task(new_task(2, "Slides"));
+            first.save().await.unwrap();
+            second.add_task(new_task(2, "Deck"));
+            assert!(second.save().await.unwrap_err().is::<VersionConflict>());
+            let _ = std::fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_delete_missing_tasks() {
+            let (conn, path) = get_conn("delete");
+            let mut first = Oswald::new(SQLiteStore::new(conn.clone()));
+            let mut parent = new_task(1, "Home");
+            let mut subtask = new_task(2, "Paint");
+            subtask.add_tag("diy");
+            subtask.links.push("https://example.com".to_owned());
+            parent.add_subtask(subtask);
+            first.add_task(parent);
+            first.add_task(new_task(3, "Work"));
+            first.save().await.unwrap();
+            let mut second = Oswald::new(SQLiteStore::new(conn.clone()));
+            second.load().await.unwrap();
+
+            // Subtrees go along with their details
+            first.delete_task(1);
+            first.save().await.unwrap();
+            let tasks = SQLiteStore::new(conn.clone()).read().await.unwrap();
+            assert_eq!(tasks.iter().map(|task| task.id).collect::<Vec<u32>>(), vec![3]);
+            let pool = SqlitePoolOptions::new().connect(&conn).await.unwrap();
+            let details: u32 = query_scalar("SELECT (SELECT COUNT(*) FROM task_tags) + (SELECT COUNT(*) FROM task_links);")
+                .fetch_one(&pool).await.unwrap();
+            assert_eq!(details, 0);
+            pool.close().await;
+
+            // Tasks changed elsewhere in the meantime stay
+            second.reload().await.unwrap();
+            first.add_task(new_task(3, "Office"));
+            first.save().await.unwrap();
+            second.delete_task(3);
+            assert!(second.save().await.unwrap_err().is::<VersionConflict>());
+            assert_eq!(SQLiteStore::new(conn).read().await.unwrap()[0].desc, "Office");
+            let _ = std::fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_search() {
+            let (conn, path) = get_conn("search");
+            let mut oswald = Oswald::new(SQLiteStore::new(conn.clone()));
+            let mut parent = new_task(1, "Quarterly report");
+            parent.add_subtask(new_task(2, "Report the numbers"));
+            oswald.add_task(parent);
+            oswald.add_task(new_task(3, "Slides"));
+            oswald.save().await.unwrap();
+
+            let store = SQLiteStore::new(conn.clone());
+            let mut ids = store.search("report").await.unwrap();
+            ids.sort();
+            assert_eq!(ids, vec![1, 2]);
+            assert_eq!(store.search("quart rep").await.unwrap(), vec![1]);
+            assert!(store.search("  ").await.unwrap().is_empty());
+
+            // Renamed and deleted tasks are out of the index
+            oswald.add_task(new_task(3, "Deck"));
+            oswald.delete_task(1);
+            oswald.save().await.unwrap();
+            assert!(store.search("report").await.unwrap().is_empty());
+            assert_eq!(store.search("deck").await.unwrap(), vec![3]);
+            let _ = std::fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_sync_base_round_trip() {
+            use crate::core::sync::SyncBase;
+
+            let (conn, path) = get_conn("sync-base");
+            let mut oswald = Oswald::new(SQLiteStore::new(conn.clone()));
+            let mut parent = new_task(1, "Home");
+            parent.add_subtask(new_task(2, "Paint"));
+            let mut base_tasks = vec![*parent, *new_task(3, "Work")];
+            oswald.set_sync_base(SyncBase::new(&base_tasks.iter().collect::<Vec<&Task>>()));
+            oswald.save().await.unwrap();
+
+            // Saving again replaces the base rather than adding to it
+            base_tasks.pop();
+            base_tasks[0].version = 4;
+            oswald.set_sync_base(SyncBase::new(&base_tasks.iter().collect::<Vec<&Task>>()));
+            oswald.save().await.unwrap();
+
+            let mut loaded = Oswald::new(SQLiteStore::new(conn));
+            loaded.load().await.unwrap();
+            let synced: Vec<(u32, Option<u32>, u32, &str)> = loaded.get_sync_base().get_synced_tasks()
+                .into_iter()
+                .map(|synced| (synced.task.id, synced.parent, synced.task.version, synced.task.desc.as_str()))
+                .collect();
+            assert_eq!(synced, vec![(1, None, 4, "Home"), (2, Some(1), 0, "Paint")]);
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+}
+
+pub mod json {
+    use crate::core::delegation::Person;
+    use crate::core::planning::{DailyPlan, PlanningSettings};
+    use crate::core::sharing::Membership;
+    use crate::core::sync::SyncedTask;
+    use crate::core::tasks::{BoxTaskVec, Task};
+    use crate::core::templates::Template;
+    use crate::core::tracking::TimeEntry;
+    use crate::ports::DataStore;
+    use async_trait::async_trait;
+    use serde::{Serialize, Deserialize};
+    use std::path::PathBuf;
+    use tokio::sync::Mutex;
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    struct Document {
+        #[serde(default)]
+        tasks: Vec<Task>,
+        #[serde(default)]
+        time_entries: Vec<TimeEntry>,
+        #[serde(default)]
+        daily_plan: Option<DailyPlan>,
+        #[serde(default)]
+        templates: Vec<Template>,
+        #[serde(default)]
+        people: Vec<Person>,
+        #[serde(default)]
+        memberships: Vec<Membership>,
+        #[serde(default)]
+        sync_base: Vec<SyncedTask>,
+        #[serde(default)]
+        planning_settings: Option<PlanningSettings>,
+    }
+
+    // Everything in a single file, for local forests that don't need SQLite
+    #[derive(Debug)]
+    pub struct JSONStore {
+        path: PathBuf,
+        // Writes replace one part of the file at a time, so they take turns
+        lock: Mutex<()>,
+    }
+
+    impl JSONStore {
+        pub fn new(path: impl Into<PathBuf>) -> Self {
+            JSONStore { path: path.into(), lock: Mutex::new(()) }
+        }
+        // A missing file is an empty forest
+        async fn read_document(&self) -> anyhow::Result<Document> {
+            match tokio::fs::read(&self.path).await {
+                Ok(raw_document) => Ok(serde_json::from_slice(&raw_document)?),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Document::default()),
+                Err(err) => Err(err.into())
+            }
+        }
+        // Written next to the file first, so a crash never leaves half of it behind
+        async fn update_document(&self, update: impl FnOnce(&mut Document) + Send) -> anyhow::Result<()> {
+            let _guard = self.lock.lock().await;
+            let mut document = self.read_document().await?;
+            update(&mut document);
+            let staged_path = self.path.with_extension("json.tmp");
+            tokio::fs::write(&staged_path, serde_json::to_vec(&document)?).await?;
+            tokio::fs::rename(&staged_path, &self.path).await?;
             Ok(())
         }
     }
+
+    #[async_trait]
+    impl DataStore for JSONStore {
+        async fn read(&self) -> anyhow::Result<BoxTaskVec> {
+            Ok(self.read_document().await?.tasks.into_iter().map(Box::new).collect())
+        }
+        async fn write(&self, tasks: Vec<&Task>) -> anyhow::Result<()> {
+            let tasks: Vec<Task> = tasks.into_iter().cloned().collect();
+            self.update_document(|document| document.tasks = tasks).await
+        }
+
+        async fn read_time_entries(&self) -> anyhow::Result<Vec<TimeEntry>> {
+            Ok(self.read_document().await?.time_entries)
+        }
+        async fn write_time_entries(&self, entries: &[TimeEntry]) -> anyhow::Result<()> {
+            let entries = entries.to_vec();
+            self.update_document(|document| document.time_entries = entries).await
+        }
+
+        async fn read_daily_plan(&self) -> anyhow::Result<Option<DailyPlan>> {
+            Ok(self.read_document().await?.daily_plan)
+        }
+        async fn write_daily_plan(&self, plan: &DailyPlan) -> anyhow::Result<()> {
+            let plan = plan.clone();
+            self.update_document(|document| document.daily_plan = Some(plan)).await
+        }
+
+        async fn read_templates(&self) -> anyhow::Result<Vec<Template>> {
+            Ok(self.read_document().await?.templates)
+        }
+        async fn write_templates(&self, templates: &[&Template]) -> anyhow::Result<()> {
+            let templates: Vec<Template> = templates.iter().map(|template| (*template).clone()).collect();
+            self.update_document(|document| document.templates = templates).await
+        }
+
+        async fn read_people(&self) -> anyhow::Result<Vec<Person>> {
+            Ok(self.read_document().await?.people)
+        }
+        async fn write_people(&self, people: &[&Person]) -> anyhow::Result<()> {
+            let people: Vec<Person> = people.iter().map(|person| (*person).clone()).collect();
+            self.update_document(|document| document.people = people).await
+        }
+
+        async fn read_memberships(&self) -> anyhow::Result<Vec<Membership>> {
+            Ok(self.read_document().await?.memberships)
+        }
+        async fn write_memberships(&self, memberships: &[Membership]) -> anyhow::Result<()> {
+            let memberships = memberships.to_vec();
+            self.update_document(|document| document.memberships = memberships).await
+        }
+
+        async fn read_sync_base(&self) -> anyhow::Result<Vec<SyncedTask>> {
+            Ok(self.read_document().await?.sync_base)
+        }
+        async fn write_sync_base(&self, tasks: &[&SyncedTask]) -> anyhow::Result<()> {
+            let tasks: Vec<SyncedTask> = tasks.iter().map(|synced| (*synced).clone()).collect();
+            self.update_document(|document| document.sync_base = tasks).await
+        }
+
+        async fn read_planning_settings(&self) -> anyhow::Result<Option<PlanningSettings>> {
+            Ok(self.read_document().await?.planning_settings)
+        }
+        async fn write_planning_settings(&self, settings: &PlanningSettings) -> anyhow::Result<()> {
+            let settings = settings.clone();
+            self.update_document(|document| document.planning_settings = Some(settings)).await
+        }
+    }
+
+    #[cfg(test)]
+    mod json_tests {
+        use super::*;
+        use chrono::{NaiveDate, Utc};
+        use crate::core::sync::SyncBase;
+        use crate::core::tasks::{Oswald, TaskStatus};
+
+        fn new_task(id: u32, desc: &str) -> Box<Task> {
+            Box::new(Task::new(id, desc.to_owned(), 1.0, 1.0, TaskStatus::Open))
+        }
+
+        #[tokio::test]
+        async fn test_load_and_save() {
+            let path = std::env::temp_dir().join(format!("tako-store-{}.json", std::process::id()));
+            let _ = std::fs::remove_file(&path);
+            // A missing file is an empty forest
+            let mut oswald = Oswald::new(JSONStore::new(&path));
+            oswald.load().await.unwrap();
+            assert!(oswald.get_tasks().is_empty());
+
+            let mut parent = new_task(1, "Home");
+            parent.add_subtask(new_task(2, "Paint"));
+            oswald.add_task(parent);
+            let today = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+            oswald.add_time_entry(TimeEntry::new(2, Utc::now(), "Walls"));
+            oswald.set_daily_plan(DailyPlan::new(today, vec![2]));
+            oswald.set_sync_base(SyncBase::new(&oswald.get_tasks()));
+            let settings = PlanningSettings { daily_capacity: 3, ..PlanningSettings::default() };
+            oswald.set_planning_settings(settings.clone());
+            oswald.save().await.unwrap();
+
+            let mut loaded = Oswald::new(JSONStore::new(&path));
+            loaded.load().await.unwrap();
+            assert_eq!(loaded.get_task_path(2), Some(vec![1]));
+            assert_eq!(loaded.get_task(2).unwrap().desc, "Paint");
+            assert_eq!(loaded.get_time_entries()[0].task_id, 2);
+            assert_eq!(loaded.get_time_entries()[0].note, "Walls");
+            assert_eq!(loaded.get_daily_plan().unwrap().task_ids, vec![2]);
+            assert_eq!(loaded.get_sync_base().get_synced_tasks().len(), 2);
+            assert_eq!(loaded.get_planning_settings(), &settings);
+            // Nothing staged is left behind
+            assert!(!path.with_extension("json.tmp").exists());
+            let _ = std::fs::remove_file(&path);
+        }
+    }
 }
@@ -1,38 +1,1220 @@
-use crate::core::tasks::{Oswald, Task};
+use crate::core::accounts::{Accounts, User};
+use crate::core::delegation::Person;
+use crate::core::planning::PlanningSettings;
+use crate::core::query::Query as TaskQuery;
+use crate::core::quick_add::QuickAdd;
+use crate::core::sharing::Role;
+use crate::core::tasks::{BatchOperation, Oswald, Quadrant, Task, TaskEvent, VersionConflict, parse_tags};
+use crate::core::templates::Template;
+use crate::ports::AccountStore;
 use axum::{
+    Extension,
     Router,
-    extract::State,
-    routing::get,
-    http::StatusCode,
+    extract::{Path, Query, Request, State},
+    http::{HeaderMap, HeaderName, StatusCode, header::{AUTHORIZATION, ETAG, IF_MATCH}},
+    middleware::{self, Next},
+    response::{IntoResponse, Response, sse::{Event, KeepAlive, Sse}},
+    routing::{delete, get, post, put},
     Json
 };
+use chrono::{Local, NaiveDate, NaiveTime};
+use futures::stream::{self, Stream, StreamExt};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, broadcast};
+use serde::Deserialize;
 use serde_json::{Value, json};
 
+// Task events a subscriber can fall behind on before it's sent everything again
+const FEED_CAPACITY: usize = 256;
 
-pub async fn start(mut oswald: Oswald) {
-    let _ = oswald.load().await;
+// Which forest a feed's event belongs to, see `/events`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Forest {
+    Own,
+    Shared
+}
+type Feed = broadcast::Sender<(Forest, TaskEvent)>;
 
-    let oswald = Arc::new(Mutex::new(oswald));
-    let app = Router::new()
-        .route("/tasks/", get(get_tasks).post(add_task))
-        .with_state(oswald);
+// Each user gets a forest of their own, loaded on their first request
+#[derive(Clone)]
+struct AppState {
+    store: Arc<dyn AccountStore + Send + Sync>,
+    accounts: Arc<Mutex<Accounts>>,
+    forests: Arc<Mutex<HashMap<u32, Arc<Mutex<Oswald>>>>>,
+    // Changes to each user's forest, see `/events`
+    feeds: Arc<Mutex<HashMap<u32, Feed>>>,
+    // Projects shared between users, see `/projects`
+    shared: Arc<Mutex<Oswald>>,
+}
+impl AppState {
+    async fn get_oswald(&self, user_id: u32) -> anyhow::Result<Arc<Mutex<Oswald>>> {
+        let mut forests = self.forests.lock().await;
+        if let Some(oswald) = forests.get(&user_id) {
+            return Ok(oswald.clone());
+        }
+        let mut oswald = self.store.get_oswald(user_id);
+        oswald.load().await?;
+        if !oswald.resurface_tasks(Local::now().date_naive()).is_empty() {
+            oswald.save().await?;
+        }
+        oswald.record_events();
+        let oswald = Arc::new(Mutex::new(oswald));
+        forests.insert(user_id, oswald.clone());
+        Ok(oswald)
+    }
+
+    async fn get_feed(&self, user_id: u32) -> Feed {
+        let mut feeds = self.feeds.lock().await;
+        feeds.entry(user_id).or_insert_with(|| broadcast::channel(FEED_CAPACITY).0).clone()
+    }
+
+    // Called with the forest still locked, so the events go out in the order they happened
+    async fn publish_events(&self, user_id: u32, oswald: &mut Oswald) {
+        let events = oswald.take_events();
+        if events.is_empty() {
+            return;
+        }
+        let feed = self.get_feed(user_id).await;
+        for event in events {
+            // Nobody may be listening
+            let _ = feed.send((Forest::Own, event));
+        }
+    }
+
+    // Each shared event goes to the members of its project, along with the users given (e.g.
+    // the members before the change, who'd miss the deletions otherwise)
+    async fn publish_shared_events(&self, shared: &mut Oswald, former_member_ids: &[u32]) {
+        for event in shared.take_events() {
+            let project_id = match &event {
+                TaskEvent::Upserted { task, .. } => shared.get_project_id(task.id),
+                TaskEvent::Deleted { id } => shared.get_project_id(*id),
+                // Every member starts over with their own projects
+                TaskEvent::Reset { .. } => {
+                    let user_ids: HashSet<u32> = shared.get_tasks()
+                        .into_iter()
+                        .flat_map(|project| get_member_ids(shared, project.id))
+                        .chain(former_member_ids.iter().copied())
+                        .collect();
+                    for user_id in user_ids {
+                        let _ = self.get_feed(user_id).await.send((Forest::Shared, get_shared_reset_event(shared, user_id)));
+                    }
+                    continue;
+                }
+            };
+            let mut user_ids: HashSet<u32> = former_member_ids.iter().copied().collect();
+            user_ids.extend(project_id.map_or(vec![], |project_id| get_member_ids(shared, project_id)));
+            for user_id in user_ids {
+                let _ = self.get_feed(user_id).await.send((Forest::Shared, event.clone()));
+            }
+        }
+    }
+
+    // Publishing whatever happened, even when saving failed (and the projects were reloaded)
+    async fn save_shared(&self, shared: &mut Oswald, former_member_ids: &[u32]) -> Result<(), (StatusCode, String)> {
+        let result = save(shared).await;
+        self.publish_shared_events(shared, former_member_ids).await;
+        result
+    }
+
+    async fn get_current_user(&self, headers: &HeaderMap) -> Option<User> {
+        let token = get_bearer_token(headers)?;
+        let accounts = self.accounts.lock().await;
+        accounts.find_by_token_hash(&hash_token(token)).cloned()
+    }
+}
+
+// e.g. `Authorization: Bearer 3f9a...`
+fn get_bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers.get(AUTHORIZATION)?
+        .to_str().ok()?
+        .strip_prefix("Bearer ")
+}
+
+// e.g. `If-Match: "3"`, with `None` for `*`. Updates have to say which version they're based
+// on, a missing header is a 428.
+fn get_if_match(headers: &HeaderMap) -> Result<Option<u32>, (StatusCode, String)> {
+    let Some(raw_version) = headers.get(IF_MATCH) else {
+        return Err((StatusCode::PRECONDITION_REQUIRED, "Updates need an If-Match header".to_owned()));
+    };
+    let raw_version = raw_version.to_str().unwrap_or_default().trim();
+    if raw_version == "*" {
+        return Ok(None);
+    }
+    raw_version.trim_start_matches("W/")
+        .trim_matches('"')
+        .parse()
+        .map(Some)
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid If-Match `{raw_version}`")))
+}
+
+// Stale writes have the client fetch the task again: a 412 for `If-Match`, a conflict for
+// the version in the body
+fn check_if_match(oswald: &Oswald, id: u32, headers: &HeaderMap) -> Result<(), (StatusCode, String)> {
+    match get_if_match(headers)? {
+        Some(version) => oswald.check_version(id, version).map_err(|err| (StatusCode::PRECONDITION_FAILED, err.to_string())),
+        None => Ok(())
+    }
+}
+
+fn check_version(oswald: &Oswald, id: u32, version: u32) -> Result<(), (StatusCode, String)> {
+    oswald.check_version(id, version).map_err(|err| (StatusCode::CONFLICT, err.to_string()))
+}
+
+// Handlers pass the store's errors on. A conflict means the stored tasks changed behind this
+// server's back (e.g. another server on the same database), so the forest starts over from
+// them and the client has to fetch the tasks again.
+async fn save(oswald: &mut Oswald) -> Result<(), (StatusCode, String)> {
+    let Err(err) = oswald.save().await else {
+        return Ok(());
+    };
+    if !err.is::<VersionConflict>() {
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string()));
+    }
+    oswald.reload().await.map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    Err((StatusCode::CONFLICT, err.to_string()))
+}
+
+fn get_member_ids(shared: &Oswald, project_id: u32) -> Vec<u32> {
+    shared.get_members(project_id).into_iter().map(|membership| membership.user_id).collect()
+}
 
+fn get_etag(oswald: &Oswald, id: u32) -> [(HeaderName, String); 1] {
+    let version = oswald.get_task(id).map_or(0, |task| task.version);
+    [(ETAG, format!("\"{version}\""))]
+}
+
+fn generate_token() -> String {
+    rand::random::<[u8; 32]>().iter().map(|byte| format!("{byte:02x}")).collect()
+}
 
+fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes()).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+// Every route, with the daily resurfacing already running, for `start` to serve (or tests to
+// run in-process)
+pub async fn app(store: impl AccountStore + Send + Sync + 'static) -> Router {
+    let users = store.read_users().await.unwrap_or_default();
+    let mut shared = store.get_shared_oswald();
+    let _ = shared.load().await;
+    shared.record_events();
+    let state = AppState {
+        store: Arc::new(store),
+        accounts: Arc::new(Mutex::new(Accounts::new(users))),
+        forests: Arc::new(Mutex::new(HashMap::new())),
+        feeds: Arc::new(Mutex::new(HashMap::new())),
+        shared: Arc::new(Mutex::new(shared)),
+    };
+    tokio::spawn(resurface_tasks_daily(state.clone()));
+
+    let tasks = Router::new()
+        .route("/tasks/", get(get_tasks).post(add_task))
+        .route("/tasks/batch", post(apply_batch))
+        .route("/tasks/quick", post(add_quick_task))
+        .route("/tasks/:id", get(get_task).put(update_task))
+        .route("/tasks/:id/snooze", post(snooze_task).delete(unsnooze_task))
+        .route("/tasks/:id/move", post(move_task))
+        .route("/tasks/:id/assign", post(assign_task))
+        .route("/events", get(get_events))
+        .route("/people", get(get_people).post(add_person))
+        .route("/people/:id", delete(delete_person))
+        .route("/people/:id/waiting", get(get_waiting_for))
+        .route("/waiting", get(get_all_waiting_for))
+        .route("/follow-ups", get(get_follow_ups))
+        .route("/search", get(search_tasks))
+        .route("/plan", get(get_plan))
+        .route("/settings/planning", get(get_planning_settings).put(set_planning_settings))
+        .route("/quadrants", get(get_quadrants))
+        .route("/templates", get(get_templates).post(add_template))
+        .route("/templates/:name", get(get_template).delete(delete_template))
+        .route("/templates/:name/instantiate", post(instantiate_template))
+        .route("/users", get(get_users))
+        .route("/users/me", get(get_me))
+        .route("/users/me/token", post(rotate_token))
+        .route("/projects", get(get_projects).post(add_project))
+        .route("/projects/:id", get(get_project).delete(delete_project))
+        .route("/projects/:id/members", get(get_members))
+        .route("/projects/:id/members/:user_id", put(share_project).delete(unshare_project))
+        .route("/projects/:id/tasks", post(add_project_task))
+        .route("/projects/:id/tasks/:task_id", put(update_project_task).delete(delete_project_task))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authenticate));
+    Router::new()
+        .route("/users", post(add_user))
+        .merge(tasks)
+        .with_state(state)
+}
+
+pub async fn start(store: impl AccountStore + Send + Sync + 'static) {
+    let app = app(store).await;
     let listener = tokio::net::TcpListener::bind("0.0.0.0:1337").await.unwrap();
     axum::serve(listener, app).await.unwrap()
 }
 
-async fn get_tasks(State(oswald): State<Arc<Mutex<Oswald>>>) -> Json<Value>{
+// Hands the handlers the current user and their forest, then pushes whatever they changed to
+// the user's feed
+async fn authenticate(State(state): State<AppState>, mut request: Request, next: Next) -> Result<Response, StatusCode> {
+    let user = state.get_current_user(request.headers()).await.ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_id = user.id;
+    let oswald = state.get_oswald(user_id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    request.extensions_mut().insert(user);
+    request.extensions_mut().insert(oswald.clone());
+    let response = next.run(request).await;
+    state.publish_events(user_id, &mut *oswald.lock().await).await;
+    Ok(response)
+}
+
+// Brings back the snoozed tasks every midnight (each forest does on load too)
+async fn resurface_tasks_daily(state: AppState) {
+    loop {
+        let now = Local::now().naive_local();
+        let forests: Vec<(u32, Arc<Mutex<Oswald>>)> = state.forests.lock().await
+            .iter()
+            .map(|(user_id, oswald)| (*user_id, oswald.clone()))
+            .collect();
+        for (user_id, oswald) in forests {
+            let mut oswald = oswald.lock().await;
+            if !oswald.resurface_tasks(now.date()).is_empty() {
+                if let Err((_, err)) = save(&mut oswald).await {
+                    eprintln!("Couldn't save the resurfaced tasks of user #{user_id}: {err}");
+                }
+                state.publish_events(user_id, &mut oswald).await;
+            }
+        }
+        {
+            let mut shared = state.shared.lock().await;
+            if !shared.resurface_tasks(now.date()).is_empty() {
+                if let Err((_, err)) = state.save_shared(&mut shared, &[]).await {
+                    eprintln!("Couldn't save the resurfaced shared tasks: {err}");
+                }
+            }
+        }
+        let next_midnight = (now.date() + chrono::Duration::days(1)).and_time(NaiveTime::MIN);
+        let until_midnight = (next_midnight - now).to_std().unwrap_or_default();
+        tokio::time::sleep(until_midnight).await;
+    }
+}
+
+fn get_reset_event(oswald: &Oswald) -> TaskEvent {
+    TaskEvent::Reset { tasks: oswald.get_tasks().into_iter().cloned().collect() }
+}
+
+// Only the projects the user is a member of
+fn get_shared_reset_event(shared: &Oswald, user_id: u32) -> TaskEvent {
+    TaskEvent::Reset { tasks: shared.get_projects(user_id).into_iter().map(|(project, _)| project.clone()).collect() }
+}
+
+async fn get_reset_events(state: &AppState, user: &User, oswald: &Mutex<Oswald>) -> Vec<(Forest, TaskEvent)> {
+    vec![
+        (Forest::Own, get_reset_event(&*oswald.lock().await)),
+        (Forest::Shared, get_shared_reset_event(&*state.shared.lock().await, user.id))
+    ]
+}
+
+// Server-Sent Events with the changes to the user's tasks, as JSON `TaskEvent`s. It starts
+// with a reset holding every task, and resets again whenever the client falls behind. The
+// changes to the user's shared projects come as `shared` events, e.g.
+// `event: shared\ndata: { "event": "deleted", "id": 4 }`.
+async fn get_events(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Extension(oswald): Extension<Arc<Mutex<Oswald>>>
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    // Subscribing first, so nothing gets lost between the resets and the first change
+    let receiver = state.get_feed(user.id).await.subscribe();
+    let resets = get_reset_events(&state, &user, &oswald).await;
+    let changes = stream::unfold((receiver, state, user, oswald), |(mut receiver, state, user, oswald)| async move {
+        let events = match receiver.recv().await {
+            Ok(event) => vec![event],
+            Err(broadcast::error::RecvError::Lagged(_)) => get_reset_events(&state, &user, &oswald).await,
+            Err(broadcast::error::RecvError::Closed) => return None
+        };
+        Some((stream::iter(events), (receiver, state, user, oswald)))
+    });
+    let events = stream::iter(resets)
+        .chain(changes.flatten())
+        .map(|(forest, event)| match forest {
+            Forest::Own => Event::default().json_data(event),
+            Forest::Shared => Event::default().event("shared").json_data(event)
+        });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+#[derive(Debug, Deserialize)]
+struct UserBody {
+    // e.g. `{ "name": "ada" }`
+    name: String
+}
+
+// Anybody can create the first user, then it takes an existing user's token.
+// The token is only ever shown here (and when rotating it).
+async fn add_user(State(state): State<AppState>, headers: HeaderMap, Json(body): Json<UserBody>) -> Result<(StatusCode, Json<Value>), (StatusCode, String)> {
+    let mut accounts = state.accounts.lock().await;
+    let is_user = get_bearer_token(&headers).is_some_and(|token| accounts.find_by_token_hash(&hash_token(token)).is_some());
+    if !accounts.is_empty() && !is_user {
+        return Err((StatusCode::UNAUTHORIZED, "Only users can add other users".to_owned()));
+    }
+    let token = generate_token();
+    // Only kept once stored
+    let mut updated_accounts = accounts.clone();
+    let id = updated_accounts.add_user(&body.name, &hash_token(&token)).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    state.store.write_users(&updated_accounts.get_users()).await.map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    *accounts = updated_accounts;
+    Ok((StatusCode::CREATED, Json(json!({ "id": id, "token": token }))))
+}
+
+async fn get_users(State(state): State<AppState>) -> Json<Value> {
+    let accounts = state.accounts.lock().await;
+    Json(json!(accounts.get_users()))
+}
+
+async fn get_me(Extension(user): Extension<User>) -> Json<Value> {
+    Json(json!(user))
+}
+
+async fn rotate_token(State(state): State<AppState>, Extension(user): Extension<User>) -> Result<Json<Value>, (StatusCode, String)> {
+    let token = generate_token();
+    let mut accounts = state.accounts.lock().await;
+    let mut updated_accounts = accounts.clone();
+    updated_accounts.set_token_hash(user.id, &hash_token(&token)).map_err(|err| (StatusCode::NOT_FOUND, err.to_string()))?;
+    state.store.write_users(&updated_accounts.get_users()).await.map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    *accounts = updated_accounts;
+    Ok(Json(json!({ "token": token })))
+}
+
+// Missing tasks and tasks outside the user's projects are both 404, too low a role is 403
+fn check_role(oswald: &Oswald, user: &User, task_id: u32, role: Role) -> Result<(), (StatusCode, String)> {
+    oswald.check_role(user.id, task_id, role).map_err(|err| match oswald.get_role(user.id, task_id) {
+        Some(_) => (StatusCode::FORBIDDEN, err.to_string()),
+        None => (StatusCode::NOT_FOUND, err.to_string())
+    })
+}
+
+// Also making sure the task belongs to the project in the path
+fn check_project_role(oswald: &Oswald, user: &User, project_id: u32, task_id: u32, role: Role) -> Result<(), (StatusCode, String)> {
+    if oswald.get_project_id(task_id) != Some(project_id) {
+        return Err((StatusCode::NOT_FOUND, format!("Task #{task_id} isn't part of project #{project_id}")));
+    }
+    check_role(oswald, user, task_id, role)
+}
+
+async fn get_projects(State(state): State<AppState>, Extension(user): Extension<User>) -> Json<Value> {
+    let shared = state.shared.lock().await;
+    let projects: Vec<Value> = shared.get_projects(user.id)
+        .into_iter()
+        .map(|(project, role)| json!({ "role": role, "project": project }))
+        .collect();
+    Json(json!(projects))
+}
+
+#[derive(Debug, Deserialize)]
+struct ProjectBody {
+    // e.g. `{ "desc": "Launch" }`
+    desc: String
+}
+
+async fn add_project(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(body): Json<ProjectBody>
+) -> Result<(StatusCode, Json<Value>), (StatusCode, String)> {
+    let mut shared = state.shared.lock().await;
+    let id = shared.add_project(user.id, &body.desc).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    state.save_shared(&mut shared, &[]).await?;
+    Ok((StatusCode::CREATED, Json(json!({ "id": id }))))
+}
+
+async fn get_project(State(state): State<AppState>, Extension(user): Extension<User>, Path(id): Path<u32>) -> Result<Json<Value>, (StatusCode, String)> {
+    let shared = state.shared.lock().await;
+    check_project_role(&shared, &user, id, id, Role::Viewer)?;
+    Ok(Json(json!(shared.get_task(id))))
+}
+
+async fn delete_project(State(state): State<AppState>, Extension(user): Extension<User>, Path(id): Path<u32>) -> Result<StatusCode, (StatusCode, String)> {
+    let mut shared = state.shared.lock().await;
+    check_project_role(&shared, &user, id, id, Role::Owner)?;
+    let member_ids = get_member_ids(&shared, id);
+    shared.delete_task_as(user.id, id).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    state.save_shared(&mut shared, &member_ids).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_members(State(state): State<AppState>, Extension(user): Extension<User>, Path(id): Path<u32>) -> Result<Json<Value>, (StatusCode, String)> {
+    let shared = state.shared.lock().await;
+    check_project_role(&shared, &user, id, id, Role::Viewer)?;
+    Ok(Json(json!(shared.get_members(id))))
+}
+
+#[derive(Debug, Deserialize)]
+struct MemberBody {
+    // e.g. `{ "role": "editor" }`
+    role: String
+}
+
+async fn share_project(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((id, user_id)): Path<(u32, u32)>,
+    Json(body): Json<MemberBody>
+) -> Result<StatusCode, (StatusCode, String)> {
+    let role: Role = body.role.parse().map_err(|err: anyhow::Error| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    if state.accounts.lock().await.get_user(user_id).is_none() {
+        return Err((StatusCode::NOT_FOUND, format!("User #{user_id} doesn't exist")));
+    }
+    let mut shared = state.shared.lock().await;
+    check_project_role(&shared, &user, id, id, Role::Owner)?;
+    shared.share_project(user.id, id, user_id, Some(role)).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    state.save_shared(&mut shared, &[]).await?;
+    // The project shows up for the new member
+    if let Some(project) = shared.get_task(id) {
+        let event = TaskEvent::Upserted { parent: None, task: Box::new(project.clone()) };
+        let _ = state.get_feed(user_id).await.send((Forest::Shared, event));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn unshare_project(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((id, user_id)): Path<(u32, u32)>
+) -> Result<StatusCode, (StatusCode, String)> {
+    let mut shared = state.shared.lock().await;
+    check_project_role(&shared, &user, id, id, Role::Owner)?;
+    shared.share_project(user.id, id, user_id, None).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    state.save_shared(&mut shared, &[]).await?;
+    // And goes away for the former one
+    let _ = state.get_feed(user_id).await.send((Forest::Shared, TaskEvent::Deleted { id }));
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+struct ProjectTaskBody {
+    // Right under the project by default, e.g. `{ "parent": 4, "task": { ... } }`
+    #[serde(default)]
+    parent: Option<u32>,
+    task: Task
+}
+
+async fn add_project_task(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<u32>,
+    Json(body): Json<ProjectTaskBody>
+) -> Result<(StatusCode, Json<Value>), (StatusCode, String)> {
+    let mut shared = state.shared.lock().await;
+    let parent_id = body.parent.unwrap_or(id);
+    check_project_role(&shared, &user, id, parent_id, Role::Editor)?;
+    let task_id = shared.add_task_as(user.id, parent_id, body.task).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    state.save_shared(&mut shared, &[]).await?;
+    Ok((StatusCode::CREATED, Json(json!({ "id": task_id }))))
+}
+
+// The subtasks in the body are ignored, they're added one at a time. Needs `If-Match`, like
+// `PUT /tasks/:id`.
+async fn update_project_task(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((id, task_id)): Path<(u32, u32)>,
+    headers: HeaderMap,
+    Json(mut task): Json<Task>
+) -> Result<(StatusCode, [(HeaderName, String); 1]), (StatusCode, String)> {
+    let mut shared = state.shared.lock().await;
+    check_project_role(&shared, &user, id, task_id, Role::Editor)?;
+    check_if_match(&shared, task_id, &headers)?;
+    task.id = task_id;
+    shared.update_task_as(user.id, task).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    state.save_shared(&mut shared, &[]).await?;
+    Ok((StatusCode::NO_CONTENT, get_etag(&shared, task_id)))
+}
+
+async fn delete_project_task(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((id, task_id)): Path<(u32, u32)>
+) -> Result<StatusCode, (StatusCode, String)> {
+    let mut shared = state.shared.lock().await;
+    let role = if task_id == id { Role::Owner } else { Role::Editor };
+    check_project_role(&shared, &user, id, task_id, role)?;
+    let member_ids = get_member_ids(&shared, id);
+    shared.delete_task_as(user.id, task_id).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    state.save_shared(&mut shared, &member_ids).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TasksParams {
+    // Comma separated, e.g. `?tag=work,urgent`
+    tag: Option<String>,
+    // Query language over all the tasks, e.g. `?q=status:open complexity>3`
+    q: Option<String>,
+    // e.g. `?quadrant=eliminate`
+    quadrant: Option<String>
+}
+
+fn filter_tasks<'a>(oswald: &'a Oswald, params: &TasksParams) -> Result<Vec<&'a Task>, StatusCode> {
+    let tags = parse_tags(params.tag.as_deref().unwrap_or_default());
+    let quadrant: Option<Quadrant> = params.quadrant
+        .as_deref()
+        .map(|raw_quadrant| raw_quadrant.parse())
+        .transpose()
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    let tasks: Vec<&Task> = match (&params.q, quadrant) {
+        (Some(raw_query), _) => {
+            let query: TaskQuery = raw_query.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+            oswald.query(&query)
+                .into_iter()
+                .filter(|task| task.has_tags(&tags))
+                .filter(|task| quadrant.is_none_or(|quadrant| task.get_quadrant_with(&oswald.get_quadrant_thresholds()) == quadrant))
+                .collect()
+        },
+        (None, Some(quadrant)) => oswald.get_quadrant_tasks(quadrant)
+            .into_iter()
+            .filter(|task| task.has_tags(&tags))
+            .collect(),
+        (None, None) => oswald.get_tasks_with_tags(&tags)
+    };
+    Ok(tasks)
+}
+
+// The user's own tasks, along with the ones of the projects they're a member of, e.g.
+// `{ "tasks": [...], "shared": [...] }`. Both are filtered the same.
+async fn get_tasks(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Extension(oswald): Extension<Arc<Mutex<Oswald>>>,
+    Query(params): Query<TasksParams>
+) -> Result<Json<Value>, StatusCode> {
+    let oswald = oswald.lock().await;
+    let tasks = filter_tasks(&oswald, &params)?;
+    let shared = state.shared.lock().await;
+    let shared_tasks: Vec<&Task> = filter_tasks(&shared, &params)?
+        .into_iter()
+        .filter(|task| shared.get_role(user.id, task.id).is_some())
+        .collect();
+    Ok(Json(json!({ "tasks": tasks, "shared": shared_tasks })))
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchParams {
+    q: String
+}
+
+async fn search_tasks(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Query(params): Query<SearchParams>) -> Json<Value> {
     let oswald = oswald.lock().await;
-    Json(json!(oswald.get_tasks()))
+    Json(json!(oswald.search(&params.q)))
 }
 
+// Today's plan and the upcoming columns, opening the day on the first request
+async fn get_plan(Extension(oswald): Extension<Arc<Mutex<Oswald>>>) -> Result<Json<Value>, (StatusCode, String)> {
+    let mut oswald = oswald.lock().await;
+    let settings = oswald.get_planning_settings().clone();
+    let today = Local::now().date_naive();
+    if oswald.open_day_with(&settings, today) {
+        save(&mut oswald).await?;
+    }
+    Ok(Json(json!(oswald.get_overview(&settings, &HashSet::new(), today))))
+}
+
+async fn get_planning_settings(Extension(oswald): Extension<Arc<Mutex<Oswald>>>) -> Json<Value> {
+    let oswald = oswald.lock().await;
+    Json(json!(oswald.get_planning_settings()))
+}
+
+// Shared by every client, so the plan looks the same everywhere
+async fn set_planning_settings(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Json(settings): Json<PlanningSettings>) -> Result<StatusCode, (StatusCode, String)> {
+    let mut oswald = oswald.lock().await;
+    oswald.set_planning_settings(settings);
+    save(&mut oswald).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// How many pending tasks each quadrant holds, and what to do about them
+async fn get_quadrants(Extension(oswald): Extension<Arc<Mutex<Oswald>>>) -> Json<Value> {
+    let oswald = oswald.lock().await;
+    Json(json!({
+        "thresholds": oswald.get_quadrant_thresholds(),
+        "counts": oswald.get_quadrant_counts(),
+        "suggestions": oswald.get_suggestions()
+    }))
+}
+
+async fn get_task(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Path(id): Path<u32>) -> Result<impl IntoResponse, StatusCode> {
+    let oswald = oswald.lock().await;
+    let task = oswald.get_task(id).ok_or(StatusCode::NOT_FOUND)?;
+    Ok((get_etag(&oswald, id), Json(json!(task))))
+}
+
+// Writes to existing tasks are checked against `If-Match`, or else the version in the body,
+// answering with the new ETag
 #[axum::debug_handler]
-async fn add_task(State(oswald): State<Arc<Mutex<Oswald>>>, Json(task): Json<Box<Task>>) -> Result<StatusCode, StatusCode> {
+async fn add_task(
+    Extension(oswald): Extension<Arc<Mutex<Oswald>>>,
+    headers: HeaderMap,
+    Json(task): Json<Box<Task>>
+) -> Result<(StatusCode, [(HeaderName, String); 1]), (StatusCode, String)> {
+    let mut oswald = oswald.lock().await;
+    let id = task.id;
+    if oswald.get_task(id).is_some() {
+        match headers.contains_key(IF_MATCH) {
+            true => check_if_match(&oswald, id, &headers)?,
+            false => check_version(&oswald, id, task.version)?
+        }
+    }
+    oswald.add_task(task);
+    save(&mut oswald).await?;
+    Ok((StatusCode::CREATED, get_etag(&oswald, id)))
+}
+
+// Like `POST /tasks/`, for a task that has to exist already and with `If-Match` required
+async fn update_task(
+    Extension(oswald): Extension<Arc<Mutex<Oswald>>>,
+    Path(id): Path<u32>,
+    headers: HeaderMap,
+    Json(mut task): Json<Box<Task>>
+) -> Result<[(HeaderName, String); 1], (StatusCode, String)> {
     let mut oswald = oswald.lock().await;
+    if oswald.get_task(id).is_none() {
+        return Err((StatusCode::NOT_FOUND, format!("Task #{id} doesn't exist")));
+    }
+    check_if_match(&oswald, id, &headers)?;
+    task.id = id;
     oswald.add_task(task);
-    let _ = oswald.save().await;
+    save(&mut oswald).await?;
+    Ok(get_etag(&oswald, id))
+}
+
+#[derive(Debug, Deserialize)]
+struct QuickAddBody {
+    // e.g. `{ "text": "Write report !high ~urgent #work due friday under:42" }`
+    text: String
+}
+
+async fn add_quick_task(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Json(body): Json<QuickAddBody>) -> Result<(StatusCode, Json<Value>), (StatusCode, String)> {
+    let mut oswald = oswald.lock().await;
+    let quick_add = QuickAdd::parse(&body.text, Local::now().date_naive())
+        .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    let id = oswald.add_quick_task(quick_add).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    save(&mut oswald).await?;
+    Ok((StatusCode::CREATED, Json(json!({ "id": id }))))
+}
+
+// All or nothing, so a bad operation leaves the tasks as they were. A stale
+// `CheckVersion` is a 409, so clients know to read the tasks again and retry.
+async fn apply_batch(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Json(operations): Json<Vec<BatchOperation>>) -> Result<StatusCode, (StatusCode, String)> {
+    let mut oswald = oswald.lock().await;
+    oswald.apply_batch(&operations).map_err(|err| match err.is::<VersionConflict>() {
+        true => (StatusCode::CONFLICT, err.to_string()),
+        false => (StatusCode::BAD_REQUEST, err.to_string())
+    })?;
+    save(&mut oswald).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+struct SnoozeBody {
+    // e.g. `{ "until": "2024-08-01" }`
+    until: NaiveDate
+}
+
+async fn snooze_task(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Path(id): Path<u32>, Json(body): Json<SnoozeBody>) -> Result<StatusCode, (StatusCode, String)> {
+    let mut oswald = oswald.lock().await;
+    if oswald.get_task(id).is_none() {
+        return Err((StatusCode::NOT_FOUND, format!("Task #{id} doesn't exist")));
+    }
+    oswald.snooze_task(id, body.until, Local::now().date_naive()).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    save(&mut oswald).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn unsnooze_task(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Path(id): Path<u32>) -> Result<StatusCode, (StatusCode, String)> {
+    let mut oswald = oswald.lock().await;
+    oswald.unsnooze_task(id).map_err(|err| (StatusCode::NOT_FOUND, err.to_string()))?;
+    save(&mut oswald).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+struct MoveBody {
+    // e.g. `{ "parent": 42 }`, or `{ "parent": null }` for the top level
+    parent: Option<u32>
+}
+
+async fn move_task(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Path(id): Path<u32>, Json(body): Json<MoveBody>) -> Result<StatusCode, (StatusCode, String)> {
+    let mut oswald = oswald.lock().await;
+    if oswald.get_task(id).is_none() {
+        return Err((StatusCode::NOT_FOUND, format!("Task #{id} doesn't exist")));
+    }
+    oswald.move_task(id, body.parent).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    save(&mut oswald).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+struct AssignBody {
+    // e.g. `{ "assignee": 2, "follow_up": "2024-08-09" }`, or `{ "assignee": null }` to take it back
+    assignee: Option<u32>,
+    #[serde(default)]
+    follow_up: Option<NaiveDate>
+}
+
+async fn assign_task(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Path(id): Path<u32>, Json(body): Json<AssignBody>) -> Result<StatusCode, (StatusCode, String)> {
+    let mut oswald = oswald.lock().await;
+    if oswald.get_task(id).is_none() {
+        return Err((StatusCode::NOT_FOUND, format!("Task #{id} doesn't exist")));
+    }
+    oswald.assign_task(id, body.assignee, body.follow_up).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    save(&mut oswald).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_people(Extension(oswald): Extension<Arc<Mutex<Oswald>>>) -> Json<Value> {
+    let oswald = oswald.lock().await;
+    Json(json!(oswald.get_people()))
+}
+
+#[derive(Debug, Deserialize)]
+struct PersonBody {
+    // e.g. `{ "name": "Ada" }`, with an `id` to rename someone
+    #[serde(default)]
+    id: Option<u32>,
+    name: String
+}
+
+async fn add_person(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Json(body): Json<PersonBody>) -> Result<(StatusCode, Json<Value>), (StatusCode, String)> {
+    let mut oswald = oswald.lock().await;
+    let id = match body.id {
+        Some(id) => id,
+        None => oswald.get_next_person_id().map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?
+    };
+    oswald.add_person(Person::new(id, &body.name)).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    save(&mut oswald).await?;
+    Ok((StatusCode::CREATED, Json(json!({ "id": id }))))
+}
+
+// Their tasks are handed back
+async fn delete_person(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Path(id): Path<u32>) -> Result<StatusCode, (StatusCode, String)> {
+    let mut oswald = oswald.lock().await;
+    oswald.delete_person(id).map_err(|err| (StatusCode::NOT_FOUND, err.to_string()))?;
+    save(&mut oswald).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_waiting_for(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Path(id): Path<u32>) -> Result<Json<Value>, StatusCode> {
+    let oswald = oswald.lock().await;
+    if oswald.get_person(id).is_none() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(Json(json!(oswald.get_waiting_for(id))))
+}
+
+async fn get_all_waiting_for(Extension(oswald): Extension<Arc<Mutex<Oswald>>>) -> Json<Value> {
+    let oswald = oswald.lock().await;
+    Json(json!(oswald.get_all_waiting_for()))
+}
+
+// Delegated tasks to chase today
+async fn get_follow_ups(Extension(oswald): Extension<Arc<Mutex<Oswald>>>) -> Json<Value> {
+    let oswald = oswald.lock().await;
+    Json(json!(oswald.get_follow_ups(Local::now().date_naive())))
+}
+
+async fn get_templates(Extension(oswald): Extension<Arc<Mutex<Oswald>>>) -> Json<Value> {
+    let oswald = oswald.lock().await;
+    Json(json!(oswald.get_templates()))
+}
+
+async fn get_template(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Path(name): Path<String>) -> Result<Json<Value>, StatusCode> {
+    let oswald = oswald.lock().await;
+    let template = oswald.get_template(&name).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(json!(template)))
+}
+
+// Replaces the template with the same name, if any
+async fn add_template(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Json(template): Json<Template>) -> Result<StatusCode, (StatusCode, String)> {
+    let mut oswald = oswald.lock().await;
+    oswald.add_template(template).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    save(&mut oswald).await?;
     Ok(StatusCode::CREATED)
 }
+
+async fn delete_template(Extension(oswald): Extension<Arc<Mutex<Oswald>>>, Path(name): Path<String>) -> Result<StatusCode, (StatusCode, String)> {
+    let mut oswald = oswald.lock().await;
+    oswald.delete_template(&name).map_err(|err| (StatusCode::NOT_FOUND, err.to_string()))?;
+    save(&mut oswald).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct InstantiateBody {
+    // e.g. `{ "parent": 42, "values": { "version": "v2.0" } }`
+    #[serde(default)]
+    parent: Option<u32>,
+    #[serde(default)]
+    values: HashMap<String, String>
+}
+
+async fn instantiate_template(
+    Extension(oswald): Extension<Arc<Mutex<Oswald>>>,
+    Path(name): Path<String>,
+    Json(body): Json<InstantiateBody>
+) -> Result<(StatusCode, Json<Value>), (StatusCode, String)> {
+    let mut oswald = oswald.lock().await;
+    if oswald.get_template(&name).is_none() {
+        return Err((StatusCode::NOT_FOUND, format!("Template \"{name}\" doesn't exist")));
+    }
+    let id = oswald.instantiate_template(&name, &body.values, body.parent)
+        .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    save(&mut oswald).await?;
+    Ok((StatusCode::CREATED, Json(json!({ "id": id }))))
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod api_tests {
+    use super::*;
+    use crate::core::tasks::TaskStatus;
+    use crate::ports::DummyStore;
+    use axum::body::Body;
+    use axum::http::header::CONTENT_TYPE;
+    use tower::ServiceExt;
+
+    fn build_request(method: &str, uri: &str, token: Option<&str>, body: Option<Value>) -> Request {
+        let mut builder = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            builder = builder.header(AUTHORIZATION, format!("Bearer {token}"));
+        }
+        match body {
+            Some(body) => builder.header(CONTENT_TYPE, "application/json").body(Body::from(body.to_string())),
+            None => builder.body(Body::empty())
+        }.unwrap()
+    }
+
+    // Status, headers and JSON body (null if there's none)
+    async fn send(app: &Router, request: Request) -> (StatusCode, HeaderMap, Value) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let raw_body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, headers, serde_json::from_slice(&raw_body).unwrap_or(Value::Null))
+    }
+
+    // The new user's id and token
+    async fn add_test_user(app: &Router, token: Option<&str>, name: &str) -> (u32, String) {
+        let (status, _, body) = send(app, build_request("POST", "/users", token, Some(json!({ "name": name })))).await;
+        assert_eq!(status, StatusCode::CREATED);
+        (body["id"].as_u64().unwrap() as u32, body["token"].as_str().unwrap().to_owned())
+    }
+
+    fn get_test_task(id: u32, desc: &str) -> Value {
+        json!(Task::new(id, desc.to_owned(), 1.0, 1.0, TaskStatus::Open))
+    }
+
+    #[tokio::test]
+    async fn test_requests_need_a_token() {
+        let app = app(DummyStore).await;
+        let (_, token) = add_test_user(&app, None, "Ada").await;
+
+        let (status, _, _) = send(&app, build_request("GET", "/tasks/", None, None)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _, _) = send(&app, build_request("GET", "/tasks/", Some("not-a-token"), None)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _, _) = send(&app, build_request("GET", "/tasks/", Some(&token), None)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Only the first user can be added without one
+        let (status, _, _) = send(&app, build_request("POST", "/users", None, Some(json!({ "name": "Bob" })))).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        add_test_user(&app, Some(&token), "Bob").await;
+
+        // Rotating the token logs the old one out
+        let (_, _, body) = send(&app, build_request("POST", "/users/me/token", Some(&token), None)).await;
+        let (status, _, _) = send(&app, build_request("GET", "/users/me", Some(&token), None)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _, me) = send(&app, build_request("GET", "/users/me", body["token"].as_str(), None)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(me["name"], "Ada");
+    }
+
+    #[tokio::test]
+    async fn test_users_only_see_their_own_tasks() {
+        let app = app(DummyStore).await;
+        let (_, ada) = add_test_user(&app, None, "Ada").await;
+        let (_, bob) = add_test_user(&app, Some(&ada), "Bob").await;
+
+        let (status, _, _) = send(&app, build_request("POST", "/tasks/", Some(&ada), Some(get_test_task(1, "Ada's")))).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (_, _, tasks) = send(&app, build_request("GET", "/tasks/", Some(&bob), None)).await;
+        assert_eq!(tasks["tasks"], json!([]));
+        let (status, _, _) = send(&app, build_request("GET", "/tasks/1", Some(&bob), None)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // The same id in another forest is another task
+        let (status, _, _) = send(&app, build_request("POST", "/tasks/", Some(&bob), Some(get_test_task(1, "Bob's")))).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let (_, _, task) = send(&app, build_request("GET", "/tasks/1", Some(&ada), None)).await;
+        assert_eq!(task["desc"], "Ada's");
+        let (_, _, task) = send(&app, build_request("GET", "/tasks/1", Some(&bob), None)).await;
+        assert_eq!(task["desc"], "Bob's");
+    }
+
+    fn build_update(uri: &str, token: &str, version: Option<&str>, body: Value) -> Request {
+        let mut request = build_request("PUT", uri, Some(token), Some(body));
+        if let Some(version) = version {
+            request.headers_mut().insert(IF_MATCH, version.parse().unwrap());
+        }
+        request
+    }
+
+    #[tokio::test]
+    async fn test_task_versions() {
+        let app = app(DummyStore).await;
+        let (_, ada) = add_test_user(&app, None, "Ada").await;
+
+        let (status, headers, _) = send(&app, build_request("POST", "/tasks/", Some(&ada), Some(get_test_task(1, "Report")))).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(headers[ETAG], "\"1\"");
+        let (_, headers, task) = send(&app, build_request("GET", "/tasks/1", Some(&ada), None)).await;
+        assert_eq!(headers[ETAG], "\"1\"");
+        assert_eq!(task["version"], 1);
+
+        // Updates say which version they're based on, and bump it
+        let (status, _, _) = send(&app, build_update("/tasks/1", &ada, None, get_test_task(1, "Draft"))).await;
+        assert_eq!(status, StatusCode::PRECONDITION_REQUIRED);
+        let (status, headers, _) = send(&app, build_update("/tasks/1", &ada, Some("\"1\""), get_test_task(1, "Draft"))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[ETAG], "\"2\"");
+        let (status, _, _) = send(&app, build_update("/tasks/1", &ada, Some("\"1\""), get_test_task(1, "Stale"))).await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+        let (status, _, _) = send(&app, build_update("/tasks/1", &ada, Some("soon"), get_test_task(1, "Stale"))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, headers, _) = send(&app, build_update("/tasks/1", &ada, Some("*"), get_test_task(1, "Final"))).await;
+        assert_eq!((status, &headers[ETAG]), (StatusCode::OK, &"\"3\"".parse().unwrap()));
+
+        // Upserts without `If-Match` go by the version in the body, even the default one
+        let (status, _, _) = send(&app, build_request("POST", "/tasks/", Some(&ada), Some(get_test_task(1, "Stale")))).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let mut task = Task::new(1, "Report".to_owned(), 1.0, 1.0, TaskStatus::Open);
+        task.version = 3;
+        let (status, headers, _) = send(&app, build_request("POST", "/tasks/", Some(&ada), Some(json!(task)))).await;
+        assert_eq!((status, &headers[ETAG]), (StatusCode::CREATED, &"\"4\"".parse().unwrap()));
+        let (_, _, task) = send(&app, build_request("GET", "/tasks/1", Some(&ada), None)).await;
+        assert_eq!(task["desc"], "Report");
+
+        // Same for the shared projects' tasks
+        let (_, _, project) = send(&app, build_request("POST", "/projects", Some(&ada), Some(json!({ "desc": "Launch" })))).await;
+        let uri = format!("/projects/{0}/tasks/{0}", project["id"]);
+        let (status, _, _) = send(&app, build_update(&uri, &ada, None, get_test_task(0, "Launch v2"))).await;
+        assert_eq!(status, StatusCode::PRECONDITION_REQUIRED);
+        let (status, _, _) = send(&app, build_update(&uri, &ada, Some("\"0\""), get_test_task(0, "Launch v2"))).await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+        let (status, headers, _) = send(&app, build_update(&uri, &ada, Some("\"1\""), get_test_task(0, "Launch v2"))).await;
+        assert_eq!((status, &headers[ETAG]), (StatusCode::NO_CONTENT, &"\"2\"".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_subtask_versions() {
+        let app = app(DummyStore).await;
+        let (_, ada) = add_test_user(&app, None, "Ada").await;
+        let new_tree = |desc: &str, version: u32| {
+            let mut task = Task::new(1, "Report".to_owned(), 1.0, 1.0, TaskStatus::Open);
+            let mut subtask = Task::new(2, desc.to_owned(), 1.0, 1.0, TaskStatus::Open);
+            subtask.version = version;
+            task.add_subtask(Box::new(subtask));
+            json!(task)
+        };
+        send(&app, build_request("POST", "/tasks/", Some(&ada), Some(new_tree("Draft", 7)))).await;
+        let (_, headers, _) = send(&app, build_request("GET", "/tasks/2", Some(&ada), None)).await;
+        assert_eq!(headers[ETAG], "\"1\"");
+
+        // Edited through its parent, even with an older version in the body
+        let (status, _, _) = send(&app, build_update("/tasks/1", &ada, Some("\"1\""), new_tree("Outline", 0))).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, headers, _) = send(&app, build_request("GET", "/tasks/2", Some(&ada), None)).await;
+        assert_eq!(headers[ETAG], "\"2\"");
+        let (status, _, _) = send(&app, build_update("/tasks/2", &ada, Some("\"1\""), get_test_task(2, "Stale"))).await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+
+        // Moved tasks get bumped too
+        let (status, _, _) = send(&app, build_request("POST", "/tasks/batch", Some(&ada), Some(json!([
+            BatchOperation::Move { ids: vec![2], parent: None }
+        ])))).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _, _) = send(&app, build_update("/tasks/2", &ada, Some("\"2\""), get_test_task(2, "Stale"))).await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+        let (_, headers, task) = send(&app, build_request("GET", "/tasks/2", Some(&ada), None)).await;
+        assert_eq!((&headers[ETAG], task["desc"].as_str()), (&"\"3\"".parse().unwrap(), Some("Outline")));
+    }
+
+    // Two servers on the same database, each with a forest of its own in memory
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_store_conflicts() {
+        use crate::adapters::sqlite::SQLiteStore;
+
+        let path = std::env::temp_dir().join(format!("tako-api-{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let conn = format!("sqlite://{}?mode=rwc", path.display());
+        let first_app = app(SQLiteStore::new(conn.clone())).await;
+        let (_, ada) = add_test_user(&first_app, None, "Ada").await;
+        let second_app = app(SQLiteStore::new(conn)).await;
+
+        send(&first_app, build_request("POST", "/tasks/", Some(&ada), Some(get_test_task(1, "Report")))).await;
+        let (_, headers, _) = send(&second_app, build_request("GET", "/tasks/1", Some(&ada), None)).await;
+        assert_eq!(headers[ETAG], "\"1\"");
+        let (status, _, _) = send(&first_app, build_update("/tasks/1", &ada, Some("\"1\""), get_test_task(1, "Draft"))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // The second server only finds out once writing, and starts over from the database
+        let (status, _, _) = send(&second_app, build_update("/tasks/1", &ada, Some("\"1\""), get_test_task(1, "Stale"))).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (_, headers, task) = send(&second_app, build_request("GET", "/tasks/1", Some(&ada), None)).await;
+        assert_eq!((&headers[ETAG], &task["desc"]), (&"\"2\"".parse().unwrap(), &json!("Draft")));
+        let (status, _, _) = send(&second_app, build_update("/tasks/1", &ada, Some("\"2\""), get_test_task(1, "Final"))).await;
+        assert_eq!(status, StatusCode::OK);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    // Keeps the users, but can't write any tasks
+    #[derive(Debug)]
+    struct ReadOnlyStore;
+    #[async_trait::async_trait]
+    impl crate::ports::DataStore for ReadOnlyStore {
+        async fn write(&self, _tasks: Vec<&Task>) -> anyhow::Result<()> {
+            anyhow::bail!("The disk is full")
+        }
+        async fn read(&self) -> anyhow::Result<crate::core::tasks::BoxTaskVec> {
+            Ok(vec![])
+        }
+    }
+    #[async_trait::async_trait]
+    impl AccountStore for ReadOnlyStore {
+        async fn write_users(&self, _users: &[&User]) -> anyhow::Result<()> {
+            Ok(())
+        }
+        async fn read_users(&self) -> anyhow::Result<Vec<User>> {
+            Ok(vec![])
+        }
+        fn get_oswald(&self, _owner_id: u32) -> Oswald {
+            Oswald::new(ReadOnlyStore)
+        }
+        fn get_shared_oswald(&self) -> Oswald {
+            Oswald::new(ReadOnlyStore)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_errors() {
+        let app = app(ReadOnlyStore).await;
+        let (_, ada) = add_test_user(&app, None, "Ada").await;
+        let (status, _, _) = send(&app, build_request("POST", "/tasks/", Some(&ada), Some(get_test_task(1, "Report")))).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        let (status, _, _) = send(&app, build_request("POST", "/projects", Some(&ada), Some(json!({ "desc": "Launch" })))).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_shared_project_roles() {
+        let app = app(DummyStore).await;
+        let (_, ada) = add_test_user(&app, None, "Ada").await;
+        let (bob_id, bob) = add_test_user(&app, Some(&ada), "Bob").await;
+        let (_, cy) = add_test_user(&app, Some(&ada), "Cy").await;
+
+        let (_, _, project) = send(&app, build_request("POST", "/projects", Some(&ada), Some(json!({ "desc": "Launch" })))).await;
+        let id = project["id"].as_u64().unwrap();
+        let uri = format!("/projects/{id}/members/{bob_id}");
+        let (status, _, _) = send(&app, build_request("PUT", &uri, Some(&ada), Some(json!({ "role": "viewer" })))).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let task = json!({ "task": get_test_task(0, "Write post") });
+
+        // Viewers can read but not write
+        let (status, _, _) = send(&app, build_request("GET", &format!("/projects/{id}"), Some(&bob), None)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _, _) = send(&app, build_request("POST", &format!("/projects/{id}/tasks"), Some(&bob), Some(task.clone()))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _, _) = send(&app, build_request("DELETE", &format!("/projects/{id}"), Some(&bob), None)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _, _) = send(&app, build_request("PUT", &uri, Some(&bob), Some(json!({ "role": "owner" })))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // Non-members can't tell the project exists
+        let (status, _, _) = send(&app, build_request("GET", &format!("/projects/{id}"), Some(&cy), None)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _, _) = send(&app, build_request("POST", &format!("/projects/{id}/tasks"), Some(&cy), Some(task.clone()))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _, _) = send(&app, build_request("POST", &format!("/projects/{id}/tasks"), Some(&ada), Some(task))).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        // Members get the project along with their own tasks
+        let (_, _, tasks) = send(&app, build_request("GET", "/tasks/", Some(&bob), None)).await;
+        assert_eq!(tasks["shared"][0]["desc"], "Launch");
+        assert_eq!(tasks["shared"][0]["subtasks_map"].as_object().unwrap().len(), 1);
+        let (_, _, tasks) = send(&app, build_request("GET", "/tasks/?q=post", Some(&bob), None)).await;
+        assert_eq!(tasks["shared"][0]["desc"], "Write post");
+        let (_, _, tasks) = send(&app, build_request("GET", "/tasks/", Some(&cy), None)).await;
+        assert_eq!(tasks["shared"], json!([]));
+    }
+
+    // Reads the feed until the text comes up, returning everything read
+    async fn read_feed_until(feed: &mut (impl Stream<Item = Result<axum::body::Bytes, axum::Error>> + Unpin), text: &str) -> String {
+        let mut read = String::new();
+        while !read.contains(text) {
+            let chunk = tokio::time::timeout(std::time::Duration::from_secs(5), feed.next()).await
+                .unwrap_or_else(|_| panic!("`{text}` never came up in {read}"))
+                .unwrap()
+                .unwrap();
+            read.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+        read
+    }
+
+    #[tokio::test]
+    async fn test_events() {
+        let app = app(DummyStore).await;
+        let (_, ada) = add_test_user(&app, None, "Ada").await;
+        send(&app, build_request("POST", "/tasks/", Some(&ada), Some(get_test_task(1, "Report")))).await;
+
+        let response = app.clone().oneshot(build_request("GET", "/events", Some(&ada), None)).await.unwrap();
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/event-stream");
+        let mut feed = response.into_body().into_data_stream();
+        // The user's tasks, then their shared projects
+        let read = read_feed_until(&mut feed, "event: shared").await;
+        assert!(read.starts_with(r#"data: {"event":"reset""#) && read.contains("Report"));
+
+        let operations = json!([{ "op": "delete", "ids": [1] }]);
+        send(&app, build_request("POST", "/tasks/quick", Some(&ada), Some(json!({ "text": "Slides" })))).await;
+        send(&app, build_request("POST", "/tasks/batch", Some(&ada), Some(operations))).await;
+        let read = read_feed_until(&mut feed, r#"{"event":"deleted","id":1}"#).await;
+        assert!(read.contains(r#""event":"upserted""#) && read.contains("Slides"));
+        assert!(!read.contains("event: shared"));
+    }
+
+    #[tokio::test]
+    async fn test_shared_events() {
+        let app = app(DummyStore).await;
+        let (_, ada) = add_test_user(&app, None, "Ada").await;
+        let (bob_id, bob) = add_test_user(&app, Some(&ada), "Bob").await;
+
+        let response = app.clone().oneshot(build_request("GET", "/events", Some(&bob), None)).await.unwrap();
+        let mut feed = response.into_body().into_data_stream();
+        let read = read_feed_until(&mut feed, "event: shared").await;
+        assert!(read.contains(r#"{"event":"reset","tasks":[]}"#));
+
+        let (_, _, project) = send(&app, build_request("POST", "/projects", Some(&ada), Some(json!({ "desc": "Launch" })))).await;
+        let id = project["id"].as_u64().unwrap();
+        send(&app, build_request("PUT", &format!("/projects/{id}/members/{bob_id}"), Some(&ada), Some(json!({ "role": "editor" })))).await;
+        let read = read_feed_until(&mut feed, "Launch").await;
+        assert!(read.contains("event: shared") && read.contains(r#""event":"upserted""#));
+
+        send(&app, build_request("POST", &format!("/projects/{id}/tasks"), Some(&ada), Some(json!({ "task": get_test_task(0, "Write post") })))).await;
+        read_feed_until(&mut feed, "Write post").await;
+
+        send(&app, build_request("DELETE", &format!("/projects/{id}/members/{bob_id}"), Some(&ada), None)).await;
+        let read = read_feed_until(&mut feed, r#""event":"deleted""#).await;
+        assert!(read.contains("event: shared"));
+    }
+}
+
+/* ========================================================================= */
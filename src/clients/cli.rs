@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::PathBuf;
+use chrono::Local;
+use crate::adapters::json::JSONStore;
+use crate::core::query::Query;
+use crate::core::quick_add::QuickAdd;
+use crate::core::tasks::{Oswald, Task};
+
+const USAGE: &str = "usage: tako [--db PATH] list [QUERY] | plan | add TEXT";
+// Where the tasks live without `--db`, falling back to `~/.tako.json`
+const DB_VAR: &str = "TAKO_DB";
+
+fn get_default_db() -> PathBuf {
+    if let Some(path) = std::env::var_os(DB_VAR) {
+        return PathBuf::from(path);
+    }
+    let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+    home.join(".tako.json")
+}
+
+fn print_task(out: &mut impl Write, task: &Task) -> anyhow::Result<()> {
+    let tags: Vec<String> = task.tags.iter().map(|tag| format!("#{tag}")).collect();
+    writeln!(out, "{:>5} {:<9} {} {}", format!("#{}", task.id), format!("{:?}", task.status), task.desc, tags.join(" "))?;
+    Ok(())
+}
+
+fn list(oswald: &Oswald, args: &[String], out: &mut impl Write) -> anyhow::Result<()> {
+    let query: Query = args.join(" ").parse()?;
+    for task in oswald.query(&query) {
+        print_task(out, task)?;
+    }
+    Ok(())
+}
+
+async fn add(oswald: &mut Oswald, args: &[String], out: &mut impl Write) -> anyhow::Result<()> {
+    let quick_add = QuickAdd::parse(&args.join(" "), Local::now().date_naive())?;
+    let id = oswald.add_quick_task(quick_add)?;
+    oswald.save().await?;
+    if let Some(task) = oswald.get_task(id) {
+        print_task(out, task)?;
+    }
+    Ok(())
+}
+
+async fn plan(oswald: &mut Oswald, out: &mut impl Write) -> anyhow::Result<()> {
+    let settings = oswald.get_planning_settings().clone();
+    let today = Local::now().date_naive();
+    if oswald.open_day_with(&settings, today) {
+        oswald.save().await?;
+    }
+
+    let overview = oswald.get_overview(&settings, &HashSet::new(), today);
+    for column in overview.columns {
+        writeln!(out, "{} ({})", column.label, column.first_day)?;
+        for task in column.tasks {
+            print_task(out, task)?;
+        }
+    }
+    writeln!(out, "Backlog")?;
+    for task in overview.backlog {
+        print_task(out, task)?;
+    }
+    Ok(())
+}
+
+// The tasks are kept in a JSON file, see `DB_VAR`
+pub async fn run(args: Vec<String>, out: &mut impl Write) -> anyhow::Result<()> {
+    let (path, args) = match args.split_first() {
+        Some((flag, rest)) if flag == "--db" => match rest.split_first() {
+            Some((path, args)) => (PathBuf::from(path), args),
+            None => anyhow::bail!(USAGE)
+        },
+        _ => (get_default_db(), args.as_slice())
+    };
+    let mut oswald = Oswald::new(JSONStore::new(path));
+    oswald.load().await?;
+    if !oswald.resurface_tasks(Local::now().date_naive()).is_empty() {
+        oswald.save().await?;
+    }
+
+    match args.split_first() {
+        Some((command, args)) if command == "list" => list(&oswald, args, out),
+        Some((command, _)) if command == "plan" => plan(&mut oswald, out).await,
+        Some((command, args)) if command == "add" => add(&mut oswald, args, out).await,
+        _ => anyhow::bail!(USAGE)
+    }
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod cli_tests {
+    use super::*;
+    use crate::core::calendar::CalendarColumn;
+    use crate::core::planning::PlanningSettings;
+
+    fn run_args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_add_then_list() {
+        let path = std::env::temp_dir().join(format!("tako-cli-{}.json", std::process::id()));
+        let db = path.to_str().unwrap();
+
+        let mut out = vec![];
+        run(run_args(&["--db", db, "add", "Write report #work"]), &mut out).await.unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("Write report #work"));
+
+        // A separate run reads back what the first one saved
+        let mut out = vec![];
+        run(run_args(&["--db", db, "list", "tag:work"]), &mut out).await.unwrap();
+        let listed = String::from_utf8(out).unwrap();
+        assert_eq!(listed.lines().count(), 1);
+        assert!(listed.contains("#1") && listed.contains("Write report"));
+
+        assert!(run(run_args(&["--db"]), &mut vec![]).await.is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_plan_with_saved_settings() {
+        let path = std::env::temp_dir().join(format!("tako-cli-plan-{}.json", std::process::id()));
+        let mut oswald = Oswald::new(JSONStore::new(&path));
+        let settings = PlanningSettings { columns: vec![CalendarColumn::new("Now", 1)], ..PlanningSettings::default() };
+        oswald.set_planning_settings(settings);
+        oswald.save().await.unwrap();
+
+        let mut out = vec![];
+        run(run_args(&["--db", path.to_str().unwrap(), "plan"]), &mut out).await.unwrap();
+        let plan = String::from_utf8(out).unwrap();
+        assert!(plan.starts_with("Now ("));
+        assert!(!plan.contains("Tomorrow"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
+
+/* ========================================================================= */
@@ -1,4 +1,8 @@
+#[cfg(feature = "cli")]
+pub mod cli;
 #[cfg(feature = "rest_api")]
 pub mod api;
 #[cfg(feature = "wasm_app")]
 pub mod wasm_app;
+#[cfg(feature = "sync")]
+pub mod sync;
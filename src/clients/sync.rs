@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use crate::core::sync::{SyncBase, plan_sync};
+use crate::core::tasks::{BatchOperation, Oswald, Task, TaskEvent};
+
+// Merging starts over when the server's tasks change before the push lands
+const MAX_SYNC_ATTEMPTS: usize = 3;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct SyncReport {
+    // Local changes the server took, without counting the version checks
+    pub pushed: usize,
+    // Local tasks whose id was taken on the server in the meantime, see `SyncPlan::renumbered`
+    pub renumbered: BTreeMap<u32, u32>,
+}
+
+// What `GET /tasks/` answers, leaving out the shared projects (which aren't synced)
+#[derive(Debug, Deserialize)]
+struct RemoteTasks {
+    tasks: Vec<Task>,
+}
+
+// Keeps a local forest (e.g. on SQLite or a JSON file) in step with a tako server's, so it can
+// be worked on offline and synced whenever the server is reachable
+#[derive(Debug, Clone)]
+pub struct SyncClient {
+    // e.g. `http://localhost:1337`
+    server: String,
+    token: String,
+    http: Client,
+}
+impl SyncClient {
+    pub fn new(server: &str, token: &str) -> Self {
+        SyncClient {
+            server: server.trim_end_matches('/').to_owned(),
+            token: token.to_owned(),
+            http: Client::new(),
+        }
+    }
+
+    async fn get_remote_tasks(&self) -> anyhow::Result<Vec<Task>> {
+        let response = self.http.get(format!("{}/tasks/", self.server))
+            .bearer_auth(&self.token)
+            .send().await?
+            .error_for_status()?;
+        let remote_tasks: RemoteTasks = response.json().await?;
+        Ok(remote_tasks.tasks)
+    }
+
+    // `false` when the server's tasks changed since they were read, anything else it turns
+    // down won't go through on a retry either
+    async fn push(&self, operations: &[BatchOperation]) -> anyhow::Result<bool> {
+        let response = self.http.post(format!("{}/tasks/batch", self.server))
+            .bearer_auth(&self.token)
+            .json(operations)
+            .send().await?;
+        match response.status() {
+            status if status.is_success() => Ok(true),
+            StatusCode::CONFLICT | StatusCode::PRECONDITION_FAILED => Ok(false),
+            status => {
+                let reason = response.text().await.unwrap_or_default();
+                anyhow::bail!("The server couldn't take the changes ({status}): {reason}")
+            }
+        }
+    }
+
+    // Merges the local tasks with the server's (see `plan_sync`), pushes the result and takes the
+    // server's tasks back, versions included. Offline, it fails without touching the local tasks.
+    pub async fn sync(&self, oswald: &mut Oswald) -> anyhow::Result<SyncReport> {
+        for _ in 0..MAX_SYNC_ATTEMPTS {
+            let remote_tasks = self.get_remote_tasks().await?;
+            let plan = plan_sync(
+                oswald.get_sync_base(),
+                &oswald.get_tasks(),
+                &remote_tasks.iter().collect::<Vec<&Task>>()
+            )?;
+            let remote_tasks = match plan.operations.is_empty() {
+                true => remote_tasks,
+                false if self.push(&plan.operations).await? => self.get_remote_tasks().await?,
+                false => continue
+            };
+
+            oswald.set_sync_base(SyncBase::new(&remote_tasks.iter().collect::<Vec<&Task>>()));
+            oswald.apply_event(TaskEvent::Reset { tasks: remote_tasks });
+            oswald.renumber_tasks(&plan.renumbered);
+            oswald.save().await?;
+            let pushed = plan.operations.iter()
+                .filter(|operation| !matches!(operation, BatchOperation::CheckVersion { .. }))
+                .count();
+            return Ok(SyncReport { pushed, renumbered: plan.renumbered });
+        }
+        anyhow::bail!("The server's tasks kept changing while syncing, try again later")
+    }
+}
+
+/* TESTS =================================================================== */
+#[cfg(all(test, feature = "rest_api"))]
+mod sync_tests {
+    use super::*;
+    use serde_json::{Value, json};
+    use crate::adapters::json::JSONStore;
+    use crate::clients::api;
+    use crate::core::tasks::TaskStatus;
+    use crate::ports::DummyStore;
+
+    // In-process server on a free port, along with a user's token
+    async fn start_server() -> (String, String) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server = format!("http://{}", listener.local_addr().unwrap());
+        let app = api::app(DummyStore).await;
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let user: Value = Client::new().post(format!("{server}/users"))
+            .json(&json!({ "name": "Ada" }))
+            .send().await.unwrap()
+            .json().await.unwrap();
+        (server, user["token"].as_str().unwrap().to_owned())
+    }
+
+    fn new_task(id: u32, desc: &str) -> Box<Task> {
+        Box::new(Task::new(id, desc.to_owned(), 0.0, 0.0, TaskStatus::Open))
+    }
+
+    fn get_descs(oswald: &Oswald) -> Vec<(u32, String)> {
+        let mut descs: Vec<(u32, String)> = oswald.get_all_tasks()
+            .into_iter()
+            .map(|task| (task.id, task.desc.clone()))
+            .collect();
+        descs.sort();
+        descs
+    }
+
+    #[tokio::test]
+    async fn test_sync_pushes_and_pulls() {
+        let (server, token) = start_server().await;
+        let client = SyncClient::new(&server, &token);
+        let path = std::env::temp_dir().join(format!("tako-sync-{}.json", std::process::id()));
+        let mut laptop = Oswald::new(JSONStore::new(&path));
+        let mut desktop = Oswald::new(DummyStore);
+
+        laptop.add_task(new_task(1, "Report"));
+        laptop.add_subtask(1, new_task(2, "Draft")).unwrap();
+        let report = client.sync(&mut laptop).await.unwrap();
+        assert_eq!(report.pushed, 3);
+        client.sync(&mut desktop).await.unwrap();
+        assert_eq!(get_descs(&desktop), vec![(1, "Report".to_owned()), (2, "Draft".to_owned())]);
+        assert_eq!(desktop.get_task_path(2), Some(vec![1]));
+
+        desktop.delete_task(2);
+        desktop.add_task(new_task(3, "Home"));
+        client.sync(&mut desktop).await.unwrap();
+        client.sync(&mut laptop).await.unwrap();
+        assert_eq!(get_descs(&laptop), get_descs(&desktop));
+
+        // What was synced survives a restart, so there's nothing left to push
+        let mut reloaded = Oswald::new(JSONStore::new(&path));
+        reloaded.load().await.unwrap();
+        assert_eq!(get_descs(&reloaded), get_descs(&desktop));
+        assert!(!reloaded.get_sync_base().is_empty());
+        assert_eq!(client.sync(&mut reloaded).await.unwrap(), SyncReport::default());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_sync_resolves_conflicts() {
+        let (server, token) = start_server().await;
+        let client = SyncClient::new(&server, &token);
+        let mut laptop = Oswald::new(DummyStore);
+        let mut desktop = Oswald::new(DummyStore);
+        laptop.add_task(new_task(1, "Report"));
+        laptop.add_subtask(1, new_task(2, "Draft")).unwrap();
+        laptop.add_task(new_task(3, "Home"));
+        laptop.add_task(new_task(4, "Taxes"));
+        client.sync(&mut laptop).await.unwrap();
+        client.sync(&mut desktop).await.unwrap();
+
+        // Both sides change the same tasks while offline
+        desktop.apply_batch(&[
+            BatchOperation::Nudge { ids: vec![1], importance: 30.0, urgency: 0.0 },
+            BatchOperation::SetStatus { ids: vec![2], status: TaskStatus::Blocked },
+            BatchOperation::Move { ids: vec![2], parent: Some(3) },
+        ]).unwrap();
+        client.sync(&mut desktop).await.unwrap();
+        laptop.apply_batch(&[
+            BatchOperation::Nudge { ids: vec![1], importance: 50.0, urgency: 0.0 },
+            BatchOperation::SetStatus { ids: vec![2], status: TaskStatus::Done },
+            BatchOperation::Move { ids: vec![2], parent: None },
+            BatchOperation::AddTag { ids: vec![2], tag: "writing".to_owned() },
+            BatchOperation::Delete { ids: vec![4] },
+        ]).unwrap();
+        client.sync(&mut laptop).await.unwrap();
+        client.sync(&mut desktop).await.unwrap();
+
+        for oswald in [&laptop, &desktop] {
+            // The highest priority and the furthest status win, as does the server's move
+            assert_eq!(oswald.get_task(1).unwrap().importance, 50.0);
+            let task = oswald.get_task(2).unwrap();
+            assert_eq!(task.status, TaskStatus::Done);
+            assert!(task.tags.contains("writing"));
+            assert_eq!(oswald.get_task_path(2), Some(vec![3]));
+            assert!(oswald.get_task(4).is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_keeps_edits_over_deletions() {
+        let (server, token) = start_server().await;
+        let client = SyncClient::new(&server, &token);
+        let mut laptop = Oswald::new(DummyStore);
+        let mut desktop = Oswald::new(DummyStore);
+        laptop.add_task(new_task(1, "Report"));
+        laptop.add_subtask(1, new_task(2, "Draft")).unwrap();
+        client.sync(&mut laptop).await.unwrap();
+        client.sync(&mut desktop).await.unwrap();
+
+        desktop.delete_task(1);
+        client.sync(&mut desktop).await.unwrap();
+        laptop.apply_batch(&[BatchOperation::SetStatus { ids: vec![2], status: TaskStatus::Done }]).unwrap();
+        client.sync(&mut laptop).await.unwrap();
+
+        // The edited subtask outlives its deleted parent, at the top level
+        assert_eq!(get_descs(&laptop), vec![(2, "Draft".to_owned())]);
+        assert_eq!(laptop.get_task_path(2), Some(vec![]));
+    }
+
+    #[tokio::test]
+    async fn test_sync_renumbers_taken_ids() {
+        let (server, token) = start_server().await;
+        let client = SyncClient::new(&server, &token);
+        let mut laptop = Oswald::new(DummyStore);
+        let mut desktop = Oswald::new(DummyStore);
+
+        laptop.add_task(new_task(1, "Groceries"));
+        client.sync(&mut laptop).await.unwrap();
+        desktop.add_task(new_task(1, "Report"));
+        desktop.add_subtask(1, new_task(2, "Draft")).unwrap();
+        let report = client.sync(&mut desktop).await.unwrap();
+
+        assert_eq!(report.renumbered, BTreeMap::from([(1, 3)]));
+        assert_eq!(get_descs(&desktop), vec![
+            (1, "Groceries".to_owned()),
+            (2, "Draft".to_owned()),
+            (3, "Report".to_owned())
+        ]);
+        assert_eq!(desktop.get_task_path(2), Some(vec![3]));
+    }
+
+    #[tokio::test]
+    async fn test_push_retries_stale_versions_only() {
+        let (server, token) = start_server().await;
+        let client = SyncClient::new(&server, &token);
+        assert!(client.push(&[BatchOperation::Upsert { task: new_task(1, "Report") }]).await.unwrap());
+
+        // Another version was pushed meanwhile, worth merging again
+        assert!(!client.push(&[BatchOperation::CheckVersion { id: 1, version: 7 }]).await.unwrap());
+        // Retrying wouldn't fix this one
+        assert!(client.push(&[BatchOperation::Move { ids: vec![1], parent: Some(9) }]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sync_offline() {
+        let mut laptop = Oswald::new(DummyStore);
+        laptop.add_task(new_task(1, "Report"));
+
+        // Nothing listens on port 1
+        let client = SyncClient::new("http://127.0.0.1:1", "token");
+        assert!(client.sync(&mut laptop).await.is_err());
+        assert_eq!(get_descs(&laptop), vec![(1, "Report".to_owned())]);
+        assert!(laptop.get_sync_base().is_empty());
+    }
+}
+
+/* ========================================================================= */
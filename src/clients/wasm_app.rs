@@ -1,9 +1,12 @@
-use std::cmp::{max, min};
-use std::collections::HashSet;
+use std::cmp::max;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{Receiver, Sender, channel};
 use std::time::Duration;
-use chrono::{NaiveDate, Local};
+use chrono::{NaiveDate, NaiveTime, Local, Utc};
 use egui::{
     Slider,
+    ComboBox,
+    DragValue,
     Layout,
     Direction,
     Window,
@@ -15,7 +18,9 @@ use egui::{
     Pos2,
     Ui,
     Frame,
+    RichText,
     SidePanel,
+    TopBottomPanel,
     CentralPanel,
     Align,
     Align2,
@@ -23,19 +28,39 @@ use egui::{
     Sense,
     FontId,
     ScrollArea,
+    TextEdit,
     FontFamily,
     Rect,
     Area,
     CursorIcon,
     Id,
-    text::LayoutJob
+    Event,
+    Grid,
+    Key,
+    KeyboardShortcut,
+    Modifiers,
+    RawInput,
+    Stroke,
+    text::LayoutJob,
+    text_edit::TextEditState
 };
 use eframe::{
     NativeOptions,
     Storage,
     run_native
 };
-use crate::core::tasks::{Oswald, Task, TaskStatus};
+use crate::core::calendar::CalendarColumn;
+use crate::core::delegation::Person;
+use crate::core::focus::{FocusPhase, FocusSession};
+use crate::core::planning::{DailyPlan, Overview, PlanningSettings, pick_today, plan_overview};
+use crate::core::query::Query;
+use crate::core::quick_add::QuickAdd;
+use crate::core::sync::merge_remote_event;
+use crate::core::templates::Template;
+use crate::core::tasks::{BatchOperation, ComplexityMode, Oswald, Placement, Quadrant, Task, TaskEvent, TaskStatus, parse_tags};
+use crate::core::tracking::TimeEntry;
+use reqwest::header::ACCEPT;
+use tokio::task::JoinHandle;
 
 const AUTO_SAVE_INTERVAL: Duration = Duration::new(10, 0);
 
@@ -43,6 +68,7 @@ const DEFAULT_MARGIN: f32 = 8.0;
 const MENU_WIDTH: f32 = 144.0;
 const MENU_BOTTOM_SECTION: f32 = 200.0;
 const MENU_PADDING: Vec2 = Vec2 { x: 0.0, y: 8.0 };
+const MENU_ERROR_FG: Color32 = Color32::from_rgb(235, 77, 75);
 
 const BUTTON_SELECTED_BG: Color32 = Color32::from_rgb(119, 140, 163);
 const BUTTON_HOVERED_BG: Color32 = Color32::from_rgb(165, 177, 194);
@@ -65,6 +91,11 @@ const TASK_PADDING: f32 = 16.0;
 const TASK_RADIUS: f32 = 8.0;
 const TASK_SIZE: Vec2 = Vec2 { x: 120.0, y: 80.0 };
 
+const TAG_CHIP_BG: Color32 = Color32::from_rgba_premultiplied(0, 0, 0, 64);
+const TAG_CHIP_PADDING: f32 = 2.0;
+const TAG_CHIP_SPACING: f32 = 4.0;
+const TAG_CHIP_RADIUS: f32 = 4.0;
+
 const DONE_TASK_BG: Color32 = Color32::from_rgb(106, 176, 76);
 const DONE_TASK_HOVERED_BG: Color32 = Color32::from_rgb(163, 203, 56);
 const DONE_TASK_FG: Color32 = Color32::WHITE;
@@ -73,6 +104,31 @@ const ARCHIVED_TASK_BG: Color32 = Color32::from_rgb(10, 61, 98);
 const ARCHIVED_TASK_HOVERED_BG: Color32 = Color32::from_rgb(60, 99, 130);
 const ARCHIVED_TASK_FG: Color32 = Color32::from_rgb(223, 249, 251);
 
+const DETAILS_WIDTH: f32 = 280.0;
+const DETAILS_HEADING_SIZE: f32 = 16.0;
+const DETAILS_SUBHEADING_SIZE: f32 = 14.0;
+
+const QUICK_OPEN_OFFSET: f32 = 64.0;
+const QUICK_OPEN_WIDTH: f32 = 360.0;
+const QUICK_OPEN_MAX_RESULTS: usize = 10;
+const QUICK_OPEN_PATH_SEPARATOR: &str = " › ";
+
+const TIMER_FG: Color32 = Color32::from_rgb(255, 121, 63);
+const TIMER_DOT_RADIUS: f32 = 3.0;
+const TIMER_REPAINT_INTERVAL: Duration = Duration::new(1, 0);
+
+const FOCUS_CLOCK_SIZE: f32 = 48.0;
+const FOCUS_TASK_SIZE: f32 = 20.0;
+
+const DROP_ZONE_STROKE: Stroke = Stroke { width: 2.0, color: BUTTON_SELECTED_BG };
+const KEYBOARD_FOCUS_STROKE: Stroke = Stroke { width: 2.0, color: BUTTON_FG };
+const KEYBOARD_NUDGE: f32 = 5.0;
+const SELECTION_FG: Color32 = Color32::from_rgb(254, 211, 48);
+const SELECTION_STROKE: Stroke = Stroke { width: 2.0, color: SELECTION_FG };
+const SELECTION_BG: Color32 = Color32::from_rgba_premultiplied(64, 53, 12, 64);
+
+const SNOOZE_OPTIONS: [(&str, i64); 3] = [("Tomorrow", 1), ("In 3 days", 3), ("Next week", 7)];
+
 const MIN_DRAG_DELTA: f32 = 1e-2;
 const MAX_ARRANGE_RECT: f32 = 100.0;
 const MIN_ARRANGE_RECT: f32 = -100.0;
@@ -80,6 +136,81 @@ const RANGE_ARRANGE_RECT: f32 = MAX_ARRANGE_RECT - MIN_ARRANGE_RECT;
 
 
 const MAX_TARGET_DAILY_TASKS: usize = 24;
+const MAX_COLUMN_DAYS: u32 = 31;
+const MAX_WEEKDAY_CAPACITY: u32 = 200;
+const WEEKDAY_LABELS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+// Just enough Markdown for notes: headings, bullet points and paragraphs
+fn show_markdown(ui: &mut Ui, text: &str) {
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if let Some(heading) = trimmed.strip_prefix("# ") {
+            ui.label(RichText::new(heading).strong().size(DETAILS_HEADING_SIZE));
+        } else if let Some(heading) = trimmed.strip_prefix("## ") {
+            ui.label(RichText::new(heading).strong().size(DETAILS_SUBHEADING_SIZE));
+        } else if let Some(item) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+            ui.label(format!("• {item}"));
+        } else if trimmed.is_empty() {
+            ui.add_space(DEFAULT_MARGIN);
+        } else {
+            ui.label(trimmed);
+        }
+    }
+}
+
+fn format_date_range((first_day, last_day): (NaiveDate, NaiveDate)) -> String {
+    match first_day == last_day {
+        true => first_day.format("%a %d %b").to_string(),
+        false => format!("{} – {}", first_day.format("%a %d"), last_day.format("%a %d %b"))
+    }
+}
+
+fn format_duration(duration: chrono::Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    match (seconds / 3600, seconds % 3600 / 60, seconds % 60) {
+        (0, minutes, seconds) => format!("{minutes}m {seconds:02}s"),
+        (hours, minutes, _) => format!("{hours}h {minutes:02}m")
+    }
+}
+
+// Paints a dot and the elapsed time in the top right corner of the task with the running timer
+fn paint_timer_indicator(ui: &Ui, task: &Task, rect: Rect, running_timer: Option<&TimeEntry>) {
+    let Some(entry) = running_timer.filter(|entry| entry.task_id == task.id) else {
+        return;
+    };
+    let elapsed_galley = ui.painter().layout_no_wrap(
+        format_duration(entry.get_duration(Utc::now())),
+        FontId { size: TASK_SMALL_FONT_SIZE, family: FontFamily::Monospace },
+        TIMER_FG
+    );
+    let elapsed_anchor = Pos2::new(
+        rect.max.x - TASK_RADIUS - elapsed_galley.rect.width(),
+        rect.min.y + TAG_CHIP_SPACING
+    );
+    let dot_center = Pos2::new(
+        elapsed_anchor.x - TAG_CHIP_SPACING - TIMER_DOT_RADIUS,
+        elapsed_anchor.y + elapsed_galley.rect.height() / 2.0
+    );
+    ui.painter().circle_filled(dot_center, TIMER_DOT_RADIUS, TIMER_FG);
+    ui.painter().galley(elapsed_anchor, elapsed_galley, TIMER_FG);
+}
+
+fn paint_keyboard_focus(ui: &Ui, task: &Task, rect: Rect, keyboard_focus: Option<u32>) {
+    if keyboard_focus == Some(task.id) {
+        ui.painter().rect_stroke(rect.expand(KEYBOARD_FOCUS_STROKE.width), TASK_RADIUS, KEYBOARD_FOCUS_STROKE);
+    }
+}
+
+fn paint_selection(ui: &Ui, task: &Task, rect: Rect, selected_tasks: &HashSet<u32>) {
+    if selected_tasks.contains(&task.id) {
+        ui.painter().rect_stroke(rect, TASK_RADIUS, SELECTION_STROKE);
+    }
+}
+
+// Whether a text field has the keyboard, so shortcuts don't get in the way of typing
+fn is_typing(ctx: &Context) -> bool {
+    ctx.memory(|mem| mem.focused()).is_some_and(|id| TextEditState::load(ctx, id).is_some())
+}
 
 fn norm_value(mut curr: f32, mut min_val: f32, mut max_val: f32) -> f32 {
     if max_val == min_val {
@@ -174,6 +305,17 @@ impl egui::Widget for &Task {
 
         ui.painter().rect_filled(rect, TASK_RADIUS, background_color);
 
+        let (checklist_done, checklist_total) = self.get_checklist_progress();
+        if checklist_total > 0 {
+            let checklist_galley = ui.painter().layout_no_wrap(
+                format!("{checklist_done}/{checklist_total}"),
+                FontId { size: TASK_SMALL_FONT_SIZE, family: FontFamily::Monospace },
+                font_color,
+            );
+            let checklist_anchor = Pos2::new(content_rect.min.x, rect.min.y + TAG_CHIP_SPACING);
+            ui.painter().galley(checklist_anchor, checklist_galley, font_color);
+        }
+
         let mut content_width = content_rect.width();
         if complexity > 1 {
             let complexity_galley = ui.painter().layout_no_wrap(
@@ -199,6 +341,27 @@ impl egui::Widget for &Task {
         let desc_pos = Pos2::new(content_rect.min.x, content_rect.min.y + y_desc_offset.max(0.0));
         ui.painter().galley(desc_pos, desc_galley, background_color);
 
+        // Tag chips are laid out along the bottom edge, dropping the ones that don't fit
+        let mut chip_x = content_rect.min.x;
+        for tag in self.tags.iter() {
+            let tag_galley = ui.painter().layout_no_wrap(
+                tag.clone(),
+                FontId { size: TASK_SMALL_FONT_SIZE, family: FontFamily::Monospace },
+                font_color
+            );
+            let chip_size = tag_galley.rect.size() + Vec2::splat(2.0 * TAG_CHIP_PADDING);
+            let chip_rect = Rect::from_min_size(
+                Pos2::new(chip_x, rect.max.y - TAG_CHIP_SPACING - chip_size.y),
+                chip_size
+            );
+            if chip_rect.max.x > content_rect.max.x {
+                break;
+            }
+            ui.painter().rect_filled(chip_rect, TAG_CHIP_RADIUS, TAG_CHIP_BG);
+            ui.painter().galley(chip_rect.min + Vec2::splat(TAG_CHIP_PADDING), tag_galley, font_color);
+            chip_x = chip_rect.max.x + TAG_CHIP_SPACING;
+        }
+
         response
     }
 }
@@ -207,30 +370,272 @@ impl egui::Widget for &Task {
 enum View {
     Arrange,
     ArrangeAll,
+    Focus,
     #[default]
     Overview
 }
 
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+enum EffortUnit {
+    #[default]
+    Minutes,
+    Points
+}
+impl EffortUnit {
+    fn suffix(&self) -> &'static str {
+        match self {
+            EffortUnit::Minutes => " min",
+            EffortUnit::Points => " pts"
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum KeyAction {
+    FocusNext,
+    FocusPrevious,
+    Edit,
+    NewTask,
+    MoreImportant,
+    LessImportant,
+    MoreUrgent,
+    LessUrgent,
+    GoDown,
+    GoUp,
+    ToggleDone,
+    ToggleArchived,
+    CheatSheet
+}
+impl KeyAction {
+    fn label(&self) -> &'static str {
+        match self {
+            KeyAction::FocusNext => "Focus the next task",
+            KeyAction::FocusPrevious => "Focus the previous task",
+            KeyAction::Edit => "Edit the task",
+            KeyAction::NewTask => "New task",
+            KeyAction::MoreImportant => "More important",
+            KeyAction::LessImportant => "Less important",
+            KeyAction::MoreUrgent => "More urgent",
+            KeyAction::LessUrgent => "Less urgent",
+            KeyAction::GoDown => "Go into the task",
+            KeyAction::GoUp => "Go up a level",
+            KeyAction::ToggleDone => "Mark as done/open",
+            KeyAction::ToggleArchived => "Archive/unarchive",
+            KeyAction::CheatSheet => "Show the shortcuts"
+        }
+    }
+}
+
+const DEFAULT_KEYMAP: [(KeyAction, KeyboardShortcut); 13] = [
+    (KeyAction::FocusNext, KeyboardShortcut::new(Modifiers::NONE, Key::Tab)),
+    (KeyAction::FocusPrevious, KeyboardShortcut::new(Modifiers::SHIFT, Key::Tab)),
+    (KeyAction::Edit, KeyboardShortcut::new(Modifiers::NONE, Key::Enter)),
+    (KeyAction::NewTask, KeyboardShortcut::new(Modifiers::NONE, Key::N)),
+    (KeyAction::MoreImportant, KeyboardShortcut::new(Modifiers::NONE, Key::ArrowUp)),
+    (KeyAction::LessImportant, KeyboardShortcut::new(Modifiers::NONE, Key::ArrowDown)),
+    (KeyAction::MoreUrgent, KeyboardShortcut::new(Modifiers::NONE, Key::ArrowRight)),
+    (KeyAction::LessUrgent, KeyboardShortcut::new(Modifiers::NONE, Key::ArrowLeft)),
+    (KeyAction::GoDown, KeyboardShortcut::new(Modifiers::NONE, Key::Space)),
+    (KeyAction::GoUp, KeyboardShortcut::new(Modifiers::NONE, Key::Backspace)),
+    (KeyAction::ToggleDone, KeyboardShortcut::new(Modifiers::NONE, Key::D)),
+    (KeyAction::ToggleArchived, KeyboardShortcut::new(Modifiers::NONE, Key::A)),
+    (KeyAction::CheatSheet, KeyboardShortcut::new(Modifiers::NONE, Key::Questionmark)),
+];
+
+// Exact matches win, so Shift+Tab isn't taken for Tab, while `?` still works with Shift held
+fn get_key_action(keymap: &[(KeyAction, KeyboardShortcut)], modifiers: Modifiers, key: Key) -> Option<KeyAction> {
+    let bindings = || keymap.iter().filter(|(_, shortcut)| shortcut.logical_key == key);
+    bindings().find(|(_, shortcut)| modifiers.matches_exact(shortcut.modifiers))
+        .or_else(|| bindings().find(|(_, shortcut)| modifiers.matches_logically(shortcut.modifiers)))
+        .map(|(action, _)| *action)
+}
+
 #[derive(Debug)]
 #[derive(Default)]
 struct Settings {
     arrange_hide_completed_tasks: bool,
     arrange_hide_parent_tasks: bool,
     backlog_column_label: String,
-    overview_columns: Vec<String>,
+    daily_budget: u32,
+    default_estimate: u32,
+    effort_complexity: bool,
+    effort_unit: EffortUnit,
+    fill_by_budget: bool,
+    focus_break_minutes: u32,
+    focus_work_minutes: u32,
+    keymap: Vec<(KeyAction, KeyboardShortcut)>,
+    // Left to right, so the last one comes right after today
+    overview_columns: Vec<CalendarColumn>,
+    search_query: String,
+    // tako server to take live updates from, e.g. `127.0.0.1:1337`
+    server_address: String,
+    server_token: String,
+    tag_filter: String,
     target_daily_tasks: usize,
     today_column_label: String,
+    // Percentage of the daily capacity, from Monday to Sunday
+    weekday_capacity: [u32; 7],
+}
+impl Settings {
+    // Today and the named columns, in chronological order
+    fn get_planning_settings(&self) -> PlanningSettings {
+        let mut columns = vec![CalendarColumn::new(&self.today_column_label, 1)];
+        columns.extend(self.overview_columns.iter().rev().cloned());
+        PlanningSettings {
+            columns,
+            daily_capacity: match self.fill_by_budget {
+                true => self.daily_budget,
+                false => self.target_daily_tasks as u32
+            },
+            weekday_capacity: self.weekday_capacity,
+            budget_default_estimate: self.fill_by_budget.then_some(self.default_estimate)
+        }
+    }
+}
+// What the user asked for while the overview was borrowed, applied once it's painted
+#[derive(Default)]
+struct OverviewActions {
+    update_task: Option<Task>,
+    deletion_id: Option<u32>,
+    details_id: Option<u32>,
+    timer_id: Option<u32>,
+    // Ctrl/Cmd-clicked, toggling it in the selection
+    select_id: Option<u32>,
+    // `None` brings the task back right away
+    snooze: Option<(u32, Option<NaiveDate>)>,
+    // `None` hands the task back to the automatic distribution
+    placement: Option<(u32, Option<Placement>)>,
+}
+
+// Drag-and-drop payload for moving tasks between the Overview columns and between parents
+struct DraggedTask(u32);
+
+// Subscription to a tako server's `/events`, which ends with an error
+struct LiveFeed {
+    events: Receiver<anyhow::Result<TaskEvent>>,
+    handle: JoinHandle<()>,
+}
+impl LiveFeed {
+    fn connect(ctx: &Context, address: &str, token: &str) -> Self {
+        let (sender, events) = channel();
+        let ctx = ctx.clone();
+        let address = address.trim().to_owned();
+        let token = token.trim().to_owned();
+        let handle = tokio::spawn(async move {
+            let err = match subscribe(&address, &token, &sender, &ctx).await {
+                Ok(()) => anyhow::anyhow!("The server closed the feed"),
+                Err(err) => err
+            };
+            let _ = sender.send(Err(err));
+            ctx.request_repaint();
+        });
+        LiveFeed { events, handle }
+    }
 }
+impl Drop for LiveFeed {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+// e.g. `localhost:1337` or `http://localhost:1337/`
+async fn subscribe(address: &str, token: &str, sender: &Sender<anyhow::Result<TaskEvent>>, ctx: &Context) -> anyhow::Result<()> {
+    let address = address.trim_end_matches('/');
+    let url = match address.contains("://") {
+        true => format!("{address}/events"),
+        false => format!("http://{address}/events")
+    };
+    let mut response = reqwest::Client::new().get(url)
+        .bearer_auth(token)
+        .header(ACCEPT, "text/event-stream")
+        .send().await?
+        .error_for_status()?;
+    let mut parser = EventParser::default();
+    while let Some(chunk) = response.chunk().await? {
+        for event in parser.push(&chunk)? {
+            sender.send(Ok(event))?;
+            ctx.request_repaint();
+        }
+    }
+    Ok(())
+}
+
+// Server-Sent Events, in whatever chunks they arrive. Each event is made of `data:` lines and
+// ends with a blank one, while the keep-alive comments are skipped. So are the shared projects'
+// (named) events, as they're about another forest.
+#[derive(Default)]
+struct EventParser {
+    // Up to the first incomplete line
+    buffer: Vec<u8>,
+    data: String,
+    is_named: bool,
+}
+impl EventParser {
+    fn push(&mut self, chunk: &[u8]) -> anyhow::Result<Vec<TaskEvent>> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = vec![];
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let raw_line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = std::str::from_utf8(&raw_line)?.trim_end_matches(['\r', '\n']);
+            if let Some(chunk) = line.strip_prefix("data:") {
+                self.data.push_str(chunk.strip_prefix(' ').unwrap_or(chunk));
+            } else if line.starts_with("event:") {
+                self.is_named = true;
+            } else if line.is_empty() {
+                if !self.data.is_empty() && !self.is_named {
+                    events.push(serde_json::from_str(&self.data)?);
+                }
+                self.data.clear();
+                self.is_named = false;
+            }
+        }
+        Ok(events)
+    }
+}
+
 struct Tako {
     oswald: Oswald,
-    arrange_nested_tasks: Vec<Task>,
+    // Ids from the top-level task down to the one being arranged
+    arrange_path: Vec<u32>,
     current_view: View,
+    detail_task: Option<u32>,
+    detail_checklist_item: String,
+    detail_template_name: String,
+    detail_follow_up: String,
+    focus_session: Option<FocusSession>,
+    focus_subtasks: String,
     form_task: Option<Task>,
+    form_due_date: String,
+    form_links: String,
+    form_tags: String,
+    // Task the keyboard acts on, out of the ones painted in the last frame
+    keyboard_focus: Option<u32>,
+    keyboard_ring: Vec<u32>,
+    last_resurface: Option<NaiveDate>,
+    live_feed: Option<LiveFeed>,
+    live_feed_error: Option<String>,
     next_task_id: u32,
+    open_cheat_sheet: bool,
     open_settings: bool,
+    open_templates: bool,
+    open_waiting_for: bool,
+    new_person_name: String,
+    pending_key_actions: Vec<KeyAction>,
     overview_completed_tasks: HashSet<u32>,
     overview_completed_tasks_last_flush: Option<NaiveDate>,
-    settings: Settings
+    quick_open: Option<String>,
+    quick_add_text: String,
+    quick_add_error: Option<String>,
+    recording_shortcut: Option<KeyAction>,
+    // Changes from the live feed, held back while something is dragged
+    remote_events: Vec<TaskEvent>,
+    selected_tasks: HashSet<u32>,
+    // Where the selection rectangle started, while it's being dragged
+    selection_origin: Option<Pos2>,
+    selection_tag: String,
+    settings: Settings,
+    // Template being instantiated, with the values typed in so far
+    template_values: Option<(String, HashMap<String, String>)>
 }
 impl Tako {
     fn tako_full_button(&self, ui: &mut Ui, text: &str, selected: bool) -> Response {
@@ -267,6 +672,27 @@ impl Tako {
         response
     }
 
+    fn open_task_form(&mut self, task: Task) {
+        self.form_tags = task.tags.iter().cloned().collect::<Vec<String>>().join(", ");
+        self.form_links = task.links.join("\n");
+        self.form_due_date = task.due_date.map(|date| date.to_string()).unwrap_or_default();
+        self.form_task = Some(task);
+    }
+
+    // Ids of the tasks matching the menu search, or `None` when there is nothing to search for
+    fn search_matches(&self) -> Option<HashSet<u32>> {
+        let query: Query = self.settings.search_query.parse().ok()?;
+        if query.is_empty() {
+            return None;
+        }
+        Some(self.oswald.query(&query).into_iter().map(|task| task.id).collect())
+    }
+
+    fn show_tag_filter(&mut self, ui: &mut Ui) {
+        ui.label("Tags:");
+        ui.text_edit_singleline(&mut self.settings.tag_filter);
+    }
+
     fn show_arrange_labels(&self, ui: &mut Ui, rect: &Rect) {
         let north_label = ui.painter().layout_no_wrap("(+) important".to_owned(), ARRANGE_LABEL_FONT, ARRANGE_FG);
         let south_label = ui.painter().layout_no_wrap("(-) important".to_owned(), ARRANGE_LABEL_FONT, ARRANGE_FG);
@@ -292,6 +718,16 @@ impl Tako {
         ui.painter().galley(west_anchor, west_label, ARRANGE_FG);
         ui.painter().galley(east_anchor, east_label, ARRANGE_FG);
 
+        // Pending tasks per quadrant, in their corners
+        for (quadrant, count) in self.oswald.get_quadrant_counts() {
+            let align = match quadrant {
+                Quadrant::Do => Align2::RIGHT_TOP,
+                Quadrant::Schedule => Align2::LEFT_TOP,
+                Quadrant::Delegate => Align2::RIGHT_BOTTOM,
+                Quadrant::Eliminate => Align2::LEFT_BOTTOM,
+            };
+            ui.painter().text(align.pos_in_rect(rect), align, format!("{quadrant:?} ({count})"), ARRANGE_LABEL_FONT, ARRANGE_FG);
+        }
     }
 
     fn show_menu(&mut self, ctx: &Context) {
@@ -304,8 +740,7 @@ impl Tako {
                     ui.add_space(MENU_PADDING.y);
                     if self.tako_full_button(ui, "Overview", matches!(self.current_view, View::Overview)).clicked() {
                         self.current_view = View::Overview;
-                        self.save_arrange();
-                        self.arrange_nested_tasks.clear();
+                        self.arrange_path.clear();
                     }
                     if self.tako_full_button(ui, "Arrange (All)", matches!(self.current_view, View::ArrangeAll)).clicked() {
                         self.current_view = View::ArrangeAll;
@@ -313,32 +748,244 @@ impl Tako {
                     if self.tako_full_button(ui, "Arrange (Tree)", matches!(self.current_view, View::Arrange)).clicked() {
                         self.current_view = View::Arrange;
                     }
+                    if self.tako_full_button(ui, "Focus", matches!(self.current_view, View::Focus)).clicked() {
+                        self.current_view = View::Focus;
+                    }
+                    if self.tako_full_button(ui, "Quick Open", self.quick_open.is_some()).clicked() {
+                        self.quick_open = Some(String::new());
+                    }
+                    if self.tako_full_button(ui, "Templates", self.open_templates).clicked() {
+                        self.open_templates = true;
+                    }
+                    if self.tako_full_button(ui, "Waiting For", self.open_waiting_for).clicked() {
+                        self.open_waiting_for = true;
+                    }
+                    ui.add_space(MENU_PADDING.y);
+                    let quick_add = ui.add(TextEdit::singleline(&mut self.quick_add_text).hint_text("Task !high #work due fri"));
+                    if quick_add.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter)) {
+                        self.add_quick_task();
+                        quick_add.request_focus();
+                    }
+                    if let Some(err) = &self.quick_add_error {
+                        ui.colored_label(MENU_ERROR_FG, err);
+                    }
+                    ui.add_space(MENU_PADDING.y);
+                    ui.add(TextEdit::singleline(&mut self.settings.search_query).hint_text("status:open tag:work"));
+                    if let Err(err) = self.settings.search_query.parse::<Query>() {
+                        ui.colored_label(MENU_ERROR_FG, err.to_string());
+                    }
                     ui.add_space(ui.available_size().y - MENU_BOTTOM_SECTION - MENU_PADDING.y);
                     ui.with_layout(Layout::bottom_up(Align::Center), |ui| {
                         ui.add_space(MENU_PADDING.y);
                         if self.tako_full_button(ui, "Settings", matches!(self.current_view, View::Arrange)).clicked() {
                             self.open_settings = true;
                         }
+                        if self.tako_full_button(ui, "Shortcuts", self.open_cheat_sheet).clicked() {
+                            self.open_cheat_sheet = true;
+                        }
                     });
                 });
             });
     }
 
+    // Tasks without an `under:` go under the arranged task
+    fn add_quick_task(&mut self) {
+        let result = QuickAdd::parse(&self.quick_add_text, Local::now().date_naive())
+            .and_then(|mut quick_add| {
+                if let (None, View::Arrange) = (quick_add.parent, &self.current_view) {
+                    quick_add.parent = self.arrange_path.last().copied();
+                }
+                self.oswald.add_quick_task(quick_add)
+            });
+        match result {
+            Ok(_) => {
+                self.next_task_id = max(self.next_task_id, self.oswald.get_next_id().unwrap_or(u32::MAX));
+                self.quick_add_text.clear();
+                self.quick_add_error = None;
+            },
+            Err(err) => self.quick_add_error = Some(err.to_string())
+        }
+    }
+
+    // Starts the task's timer, or stops it when it's the one running
+    fn toggle_timer(&mut self, task_id: u32) {
+        let now = Utc::now();
+        let is_running = self.oswald.get_running_timer().is_some_and(|entry| entry.task_id == task_id);
+        let result = match is_running {
+            true => self.oswald.stop_timer(task_id, now),
+            false => self.oswald.start_timer(task_id, "", now)
+        };
+        if let Err(err) = result {
+            println!("Couldn't toggle the timer: {err}");
+        }
+    }
+
+    fn snooze_task(&mut self, task_id: u32, until: Option<NaiveDate>) {
+        let result = match until {
+            Some(until) => self.oswald.snooze_task(task_id, until, Local::now().date_naive()),
+            None => self.oswald.unsnooze_task(task_id)
+        };
+        match result {
+            Ok(()) => {
+                // Keeping the arranged copies in sync, so saving the stack doesn't revert it
+                if let Some(task) = self.oswald.get_task(task_id).cloned() {
+                    self.oswald.add_task(Box::new(task));
+                }
+            },
+            Err(err) => println!("Couldn't snooze the task: {err}")
+        }
+    }
+
+    fn place_task(&mut self, task_id: u32, placement: Option<Placement>) {
+        match self.oswald.place_task(task_id, placement) {
+            Ok(()) => {
+                if let Some(task) = self.oswald.get_task(task_id).cloned() {
+                    self.oswald.add_task(Box::new(task));
+                }
+            },
+            Err(err) => println!("Couldn't move the task: {err}")
+        }
+    }
+
+    fn toggle_selection(&mut self, task_id: u32) {
+        if !self.selected_tasks.remove(&task_id) {
+            self.selected_tasks.insert(task_id);
+        }
+    }
+
+    // Dragging over the empty space selects the cards touched by the rectangle, adding them
+    // to the selection while Ctrl/Cmd is held. Clicking it clears the selection.
+    fn handle_selection_rect(&mut self, ui: &Ui, response: &Response, task_rects: &[(u32, Rect)]) {
+        if response.clicked() {
+            self.selected_tasks.clear();
+        }
+        if response.drag_started() {
+            self.selection_origin = response.interact_pointer_pos();
+        }
+        let (Some(origin), Some(pointer)) = (self.selection_origin, ui.input(|input| input.pointer.latest_pos())) else {
+            return;
+        };
+        let selection_rect = Rect::from_two_pos(origin, pointer);
+        if response.dragged() {
+            ui.painter().rect(selection_rect, 0.0, SELECTION_BG, SELECTION_STROKE);
+        }
+        if response.drag_stopped() {
+            if !ui.input(|input| input.modifiers.command) {
+                self.selected_tasks.clear();
+            }
+            self.selected_tasks.extend(task_rects.iter()
+                .filter(|(_, rect)| rect.intersects(selection_rect))
+                .map(|(task_id, _)| *task_id));
+            self.selection_origin = None;
+        }
+    }
+
+    fn apply_batch(&mut self, operations: Vec<BatchOperation>) {
+        if let Err(err) = self.oswald.apply_batch(&operations) {
+            println!("Couldn't update the tasks: {err}");
+            return;
+        }
+        for operation in operations {
+            if let BatchOperation::SetStatus { ids, status } = operation {
+                match status {
+                    TaskStatus::Done => self.overview_completed_tasks.extend(ids),
+                    _ => ids.iter().for_each(|id| { self.overview_completed_tasks.remove(id); })
+                }
+            }
+        }
+    }
+
+    fn show_selection_bar(&mut self, ctx: &Context) {
+        self.selected_tasks.retain(|id| self.oswald.get_task(*id).is_some());
+        if self.selected_tasks.is_empty() {
+            return;
+        }
+        let ids: Vec<u32> = self.selected_tasks.iter().copied().collect();
+        let mut operations: Vec<BatchOperation> = vec![];
+        TopBottomPanel::bottom("Selection").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} selected", ids.len()));
+                ui.separator();
+                for (label, status) in [("Done", TaskStatus::Done), ("Open", TaskStatus::Open), ("Archive", TaskStatus::Archived)] {
+                    if ui.button(label).clicked() {
+                        operations.push(BatchOperation::SetStatus { ids: ids.clone(), status });
+                    }
+                }
+                ui.separator();
+                ui.add(TextEdit::singleline(&mut self.selection_tag).hint_text("tag").desired_width(MENU_WIDTH / 2.0));
+                let tag = self.selection_tag.trim().to_owned();
+                if ui.add_enabled(!tag.is_empty(), Button::new("Tag")).clicked() {
+                    operations.push(BatchOperation::AddTag { ids: ids.clone(), tag: tag.clone() });
+                }
+                if ui.add_enabled(!tag.is_empty(), Button::new("Untag")).clicked() {
+                    operations.push(BatchOperation::RemoveTag { ids: ids.clone(), tag });
+                }
+                ui.separator();
+                let arranged_parent = match self.current_view {
+                    View::Arrange => self.arrange_path.last().copied(),
+                    _ => None
+                };
+                if arranged_parent.is_some() && ui.button("Move here").clicked() {
+                    operations.push(BatchOperation::Move { ids: ids.clone(), parent: arranged_parent });
+                }
+                if ui.button("Move to top").clicked() {
+                    operations.push(BatchOperation::Move { ids: ids.clone(), parent: None });
+                }
+                ui.separator();
+                let nudges = [
+                    ("⬆", "More important", KEYBOARD_NUDGE, 0.0),
+                    ("⬇", "Less important", -KEYBOARD_NUDGE, 0.0),
+                    ("⬅", "Less urgent", 0.0, -KEYBOARD_NUDGE),
+                    ("➡", "More urgent", 0.0, KEYBOARD_NUDGE),
+                ];
+                for (label, hover_text, importance, urgency) in nudges {
+                    if ui.button(label).on_hover_text(hover_text).clicked() {
+                        operations.push(BatchOperation::Nudge { ids: ids.clone(), importance, urgency });
+                    }
+                }
+                ui.separator();
+                if ui.button("Clear").clicked() {
+                    self.selected_tasks.clear();
+                }
+            });
+        });
+        if !operations.is_empty() {
+            self.apply_batch(operations);
+        }
+    }
+
+    fn move_task(&mut self, task_id: u32, new_parent: Option<u32>) {
+        if let Err(err) = self.oswald.move_task(task_id, new_parent) {
+            println!("Couldn't move the task: {err}");
+        }
+    }
+
     fn handle_overview_task_response(
         ctx: &Context,
         task: &Task,
         response: Response,
+        running_task_id: Option<u32>,
         completed_tasks: &mut HashSet<u32>,
-        pending_update: &mut Option<Task>,
-        pending_deletion: &mut Option<u32>
+        actions: &mut OverviewActions
     ) {
         if response.hovered() {
             ctx.set_cursor_icon(CursorIcon::PointingHand)
         }
+        if response.dragged() {
+            ctx.set_cursor_icon(CursorIcon::Grabbing);
+        }
+        response.dnd_set_drag_payload(DraggedTask(task.id));
+
+        if response.clicked() && ctx.input(|input| input.modifiers.command) {
+            actions.select_id = Some(task.id);
+        }
+        if response.middle_clicked() {
+            actions.details_id = Some(task.id);
+        }
 
         if response.double_clicked() {
             if matches!(task.status, TaskStatus::Archived) {
-                *pending_deletion = Some(task.id);
+                actions.deletion_id = Some(task.id);
             } else {
                 let mut updated_task = task.clone();
                 updated_task.status = match task.status {
@@ -353,109 +1000,267 @@ impl Tako {
                     completed_tasks.remove(&updated_task.id);
                 }
 
-                *pending_update = Some(updated_task);
+                actions.update_task = Some(updated_task);
             }
         }
 
-        if response.secondary_clicked () {
-            let mut updated_task = task.clone();
-            updated_task.status = match task.status {
-                TaskStatus::Archived => TaskStatus::Open,
-                _ => TaskStatus::Archived
+        response.context_menu(|ui| {
+            let archive_label = match task.status {
+                TaskStatus::Archived => "Unarchive",
+                _ => "Archive"
             };
-            *pending_update = Some(updated_task);
-        }
+            if ui.button(archive_label).clicked() {
+                let mut updated_task = task.clone();
+                updated_task.status = match task.status {
+                    TaskStatus::Archived => TaskStatus::Open,
+                    _ => TaskStatus::Archived
+                };
+                actions.update_task = Some(updated_task);
+                ui.close_menu();
+            }
+            let timer_label = match running_task_id == Some(task.id) {
+                true => "Stop timer",
+                false => "Start timer"
+            };
+            if ui.button(timer_label).clicked() {
+                actions.timer_id = Some(task.id);
+                ui.close_menu();
+            }
+            if task.is_deferred() {
+                if ui.button("Unsnooze").clicked() {
+                    actions.snooze = Some((task.id, None));
+                    ui.close_menu();
+                }
+            } else {
+                ui.menu_button("Snooze", |ui| {
+                    let today = Local::now().date_naive();
+                    for (label, days) in SNOOZE_OPTIONS {
+                        if ui.button(label).clicked() {
+                            actions.snooze = Some((task.id, Some(today + chrono::Duration::days(days))));
+                            ui.close_menu();
+                        }
+                    }
+                });
+            }
+            if task.placement.is_some() && ui.button("Unpin").clicked() {
+                actions.placement = Some((task.id, None));
+                ui.close_menu();
+            }
+            if ui.button("Details").clicked() {
+                actions.details_id = Some(task.id);
+                ui.close_menu();
+            }
+        });
+    }
+
+    // Visible tasks split into the calendar columns, starting today
+    fn get_overview<'a>(
+        oswald: &'a Oswald,
+        settings: &Settings,
+        completed_ids: &HashSet<u32>,
+        search_matches: Option<HashSet<u32>>
+    ) -> Overview<'a> {
+        let tag_filter = parse_tags(&settings.tag_filter);
+        let tasks: Vec<&Task> = oswald.get_all_tasks()
+            .into_iter()
+            .filter(|task| {
+                task.has_tags(&tag_filter)
+                    && !task.is_deferred()
+                    && search_matches.as_ref().is_none_or(|ids| ids.contains(&task.id))
+            })
+            .collect();
+        let today = Local::now().date_naive();
+        plan_overview(&tasks, completed_ids, oswald.get_daily_plan(), &settings.get_planning_settings(), today)
+    }
+
+    // Weight of what's been completed today, so planning leaves room for it
+    fn get_today_used(&self, settings: &PlanningSettings) -> u32 {
+        self.overview_completed_tasks.iter()
+            .filter_map(|id| self.oswald.get_task(*id))
+            .fold(0_u32, |used, task| used.saturating_add(settings.get_task_weight(task)))
+    }
 
+    fn auto_open_day(&mut self) {
+        let settings = self.settings.get_planning_settings();
+        let today_used = self.get_today_used(&settings);
+        let today = Local::now().date_naive();
+        self.oswald.open_day(today, |tasks| pick_today(tasks, &settings, today, today_used));
+    }
 
+    fn replan_day(&mut self) {
+        let settings = self.settings.get_planning_settings();
+        let today_used = self.get_today_used(&settings);
+        let today = Local::now().date_naive();
+        self.oswald.replan_day(today, |tasks| pick_today(tasks, &settings, today, today_used));
     }
 
     fn show_overview_frame(&mut self, ui: &mut Ui, ctx: &Context) {
+        ui.horizontal(|ui| {
+            if ui.button("Re-plan today").clicked() {
+                self.replan_day();
+            }
+            self.show_tag_filter(ui);
+        });
+        ui.separator();
         Frame::default()
             .show(ui, |ui| {
-                let all_tasks = self.oswald.get_all_tasks();
-                let mut completed_tasks: Vec<&Task> = vec![];
-                let mut tasks: Vec<&Task> = vec![];
-                for task in all_tasks.into_iter() {
-                    if self.overview_completed_tasks.contains(&task.id) {
-                        completed_tasks.push(task);
-                    } else {
-                        tasks.push(task);
-                    }
-                }
-                let mut pending_update_task: Option<Task> = None;
-                let mut pending_deletion_id: Option<u32> = None;
+                let overview = Tako::get_overview(
+                    &self.oswald,
+                    &self.settings,
+                    &self.overview_completed_tasks,
+                    self.search_matches()
+                );
+                let running_timer = self.oswald.get_running_timer();
+                let running_task_id = running_timer.map(|entry| entry.task_id);
+                let mut actions = OverviewActions::default();
+                let first_days: Vec<NaiveDate> = overview.columns.iter().map(|column| column.first_day).collect();
                 ScrollArea::vertical().show(ui, |ui| {
-                    let num_columns = 2 + self.settings.overview_columns.len();
+                    let num_columns = 1 + overview.columns.len();
                     assert!(num_columns >= 2, "There should be at least two columns");
 
                     ui.columns(num_columns, |columns| {
+                        // Today is on the right, going back in time towards the backlog
                         let today_col_idx = num_columns - 1;
 
                         // Naming the columns
-                        let backlog_column = &mut columns[0];
-                        backlog_column.label(&self.settings.backlog_column_label);
-
-                        let named_columns = &mut columns[1..today_col_idx];
-                        named_columns.iter_mut().enumerate()
-                            .for_each(|(idx, col)| {col.label(&self.settings.overview_columns[idx]);});
-
-                        let today_column = &mut columns[today_col_idx];
-                        today_column.label(&self.settings.today_column_label);
-
-                        // Painting the tasks
-                        let target_tasks = min(self.settings.target_daily_tasks, tasks.len() + completed_tasks.len());
-                        let remaining_tasks = if target_tasks > completed_tasks.len() { target_tasks - completed_tasks.len() } else { 0 };
-                        for task in &tasks[..remaining_tasks] {
-                            let response = task.show_overview(today_column);
-                            Tako::handle_overview_task_response(ctx, task, response, &mut self.overview_completed_tasks, &mut pending_update_task, &mut pending_deletion_id);
+                        columns[0].label(&self.settings.backlog_column_label);
+                        for (idx, column) in overview.columns.iter().enumerate() {
+                            let range = (column.first_day, column.last_day);
+                            columns[today_col_idx - idx].label(format!("{}\n{}", column.label, format_date_range(range)));
                         }
 
-                        let mut curr_column = today_col_idx - 1;
-                        let enumerated_tasks = tasks[remaining_tasks..].into_iter().enumerate();
-                        for (idx, task) in enumerated_tasks {
-                            if idx > 0 && idx % self.settings.target_daily_tasks == 0 && curr_column > 0 { curr_column -= 1; }
-                            if let Some(column) = columns.get_mut(curr_column) {
+                        // Every column takes dropped tasks, pinning them there
+                        let drop_zones: Vec<Response> = columns.iter_mut().enumerate()
+                            .map(|(col_idx, col)| col.interact(col.max_rect(), Id::new(("overview_column", col_idx)), Sense::hover()))
+                            .collect();
+
+                        // Painting the tasks, starting from today and leaving the overflow in the backlog
+                        let day_tasks = overview.columns.into_iter().map(|column| column.tasks);
+                        for (col_idx, tasks) in day_tasks.chain([overview.backlog]).enumerate() {
+                            let column = &mut columns[today_col_idx - col_idx];
+                            for task in tasks {
                                 let response = task.show_overview(column);
-                                Tako::handle_overview_task_response(ctx, task, response, &mut self.overview_completed_tasks, &mut pending_update_task, &mut pending_deletion_id);
+                                paint_timer_indicator(column, task, response.rect, running_timer);
+                                paint_keyboard_focus(column, task, response.rect, self.keyboard_focus);
+                                paint_selection(column, task, response.rect, &self.selected_tasks);
+                                self.keyboard_ring.push(task.id);
+                                Tako::handle_overview_task_response(ctx, task, response, running_task_id, &mut self.overview_completed_tasks, &mut actions);
                             }
                         }
 
-                        for task in completed_tasks {
+                        for task in overview.completed {
                             let response = task.show_overview(&mut columns[today_col_idx]);
-                            Tako::handle_overview_task_response(ctx, &task, response, &mut self.overview_completed_tasks, &mut pending_update_task, &mut pending_deletion_id);
+                            paint_timer_indicator(&columns[today_col_idx], task, response.rect, running_timer);
+                            paint_keyboard_focus(&columns[today_col_idx], task, response.rect, self.keyboard_focus);
+                            paint_selection(&columns[today_col_idx], task, response.rect, &self.selected_tasks);
+                            self.keyboard_ring.push(task.id);
+                            Tako::handle_overview_task_response(ctx, task, response, running_task_id, &mut self.overview_completed_tasks, &mut actions);
+                        }
+
+                        for (col_idx, drop_zone) in drop_zones.into_iter().enumerate() {
+                            if drop_zone.dnd_hover_payload::<DraggedTask>().is_some() {
+                                columns[col_idx].painter().rect_stroke(drop_zone.rect, TASK_RADIUS, DROP_ZONE_STROKE);
+                            }
+                            if let Some(dragged_task) = drop_zone.dnd_release_payload::<DraggedTask>() {
+                                let placement = match col_idx {
+                                    0 => Placement::Backlog,
+                                    _ => Placement::Day(first_days[today_col_idx - col_idx])
+                                };
+                                actions.placement = Some((dragged_task.0, Some(placement)));
+                            }
                         }
                     });
                 });
-                if let Some(task) = pending_update_task {
+                if let Some(task) = actions.update_task {
                     self.oswald.add_task(Box::new(task));
                 }
-                if let Some(task_id) = pending_deletion_id {
+                if let Some(task_id) = actions.deletion_id {
                     self.oswald.delete_task(task_id);
                 }
+                if let Some(task_id) = actions.timer_id {
+                    self.toggle_timer(task_id);
+                }
+                if let Some((task_id, until)) = actions.snooze {
+                    self.snooze_task(task_id, until);
+                }
+                if let Some((task_id, placement)) = actions.placement {
+                    self.place_task(task_id, placement);
+                }
+                if let Some(task_id) = actions.select_id {
+                    self.toggle_selection(task_id);
+                }
+                if actions.details_id.is_some() {
+                    self.detail_task = actions.details_id;
+                }
             });
     }
 
+    // Drops the levels that were deleted, and follows the arranged task when it was moved elsewhere
+    fn sync_arrange_path(&mut self) {
+        while let Some(parent_id) = self.arrange_path.last().copied() {
+            if let Some(mut path) = self.oswald.get_task_path(parent_id) {
+                path.push(parent_id);
+                self.arrange_path = path;
+                return;
+            }
+            self.arrange_path.pop();
+        }
+    }
+
+    // Ancestors can be clicked to jump back to them, or dropped onto to promote tasks to their level
+    fn show_arrange_breadcrumbs(&mut self, ui: &mut Ui) {
+        let mut pending_level: Option<usize> = None;
+        let mut pending_move: Option<(u32, Option<u32>)> = None;
+        ui.horizontal(|ui| {
+            if self.arrange_path.is_empty() {
+                ui.label("Home");
+                return;
+            }
+            let mut crumbs = vec![("Home", None)];
+            crumbs.extend(self.arrange_path.iter().map(|ancestor_id| {
+                let desc = self.oswald.get_task(*ancestor_id).map(|task| task.desc.as_str()).unwrap_or_default();
+                (desc, Some(*ancestor_id))
+            }));
+            let last_level = crumbs.len() - 1;
+            for (level, (desc, parent_id)) in crumbs.into_iter().enumerate() {
+                if level > 0 {
+                    ui.label(QUICK_OPEN_PATH_SEPARATOR.trim());
+                }
+                if level == last_level {
+                    ui.label(RichText::new(desc).strong());
+                    continue;
+                }
+                let response = ui.link(desc);
+                if response.clicked() {
+                    pending_level = Some(level);
+                }
+                if response.dnd_hover_payload::<DraggedTask>().is_some() {
+                    ui.painter().rect_stroke(response.rect.expand(TAG_CHIP_PADDING), TAG_CHIP_RADIUS, DROP_ZONE_STROKE);
+                }
+                if let Some(dragged_task) = response.dnd_release_payload::<DraggedTask>() {
+                    pending_move = Some((dragged_task.0, parent_id));
+                }
+            }
+        });
+        if let Some(level) = pending_level {
+            self.arrange_path.truncate(level);
+        }
+        if let Some((task_id, new_parent)) = pending_move {
+            self.move_task(task_id, new_parent);
+        }
+    }
+
     fn show_arrange_frame(&mut self, ui: &mut Ui, ctx: &Context) {
-        self.show_task_form(ctx);
+        self.sync_arrange_path();
         Frame::default()
             .show(ui, |ui| {
                 ui.vertical(|ui| {
                     ui.horizontal(|ui| {
-                        ui.horizontal(|ui| {
-                            if let Some(parent_task) = self.arrange_nested_tasks.last() {
-                                ui.label(parent_task.desc.clone());
-
-                                if ui.button("Home").clicked() {
-                                    self.arrange_nested_tasks.clear();
-                                };
-                                if ui.button("Back").clicked() {
-                                    self.arrange_nested_tasks.pop();
-                                }
-                            }
-                        });
+                        self.show_arrange_breadcrumbs(ui);
                         if ui.add_sized(Vec2::new(144.0, 16.0), Button::new("Add Task")).clicked() {
-                            self.form_task = Some(Task::new_with_id(self.next_task_id));
+                            self.open_task_form(Task::new_with_id(self.next_task_id));
                         }
+                        self.show_tag_filter(ui);
                     });
                     ui.separator();
                     let (_, area_rect) = ui.allocate_space(ui.available_size());
@@ -464,26 +1269,60 @@ impl Tako {
                         .default_size(ui.available_size())
                         .constrain_to(area_rect)
                         .show(ctx, |ui| {
-                            let tasks = match self.arrange_nested_tasks.last() {
-                                Some(parent_task) => parent_task.get_subtasks(),
+                            let tag_filter = parse_tags(&self.settings.tag_filter);
+                            let search_matches = self.search_matches();
+                            let tasks: Vec<&Task> = match self.arrange_path.last() {
+                                Some(parent_id) => self.oswald.get_task(*parent_id)
+                                    .map(|parent_task| parent_task.get_subtasks())
+                                    .unwrap_or_default(),
                                 None => self.oswald.get_tasks()
-                            };
+                            }.into_iter()
+                                .filter(|task| task.has_tags(&tag_filter))
+                                .filter(|task| search_matches.as_ref().is_none_or(|ids| ids.contains(&task.id)))
+                                .collect();
                             let mut pending_update_task: Option<Task> = None;
+                            let mut pending_form_task: Option<Task> = None;
+                            let mut pending_details_id: Option<u32> = None;
+                            let mut pending_select_id: Option<u32> = None;
+                            let mut task_rects: Vec<(u32, Rect)> = vec![];
+                            // Added before the cards so they keep their own clicks and drags
+                            let selection_response = ui.interact(area_rect, Id::new("arrange_selection"), Sense::click_and_drag());
                             let mut pending_deletion_id: Option<u32> = None;
-                            let mut new_parent_task: Option<Task> = None;
+                            let mut new_parent_id: Option<u32> = None;
+                            let mut pending_move: Option<(u32, u32)> = None;
 
                             for task in tasks {
                                 let response = task.show_arrange(ui, &area_rect);
+                                paint_timer_indicator(ui, task, response.rect, self.oswald.get_running_timer());
+                                paint_keyboard_focus(ui, task, response.rect, self.keyboard_focus);
+                                paint_selection(ui, task, response.rect, &self.selected_tasks);
+                                self.keyboard_ring.push(task.id);
+                                task_rects.push((task.id, response.rect));
 
                                 if response.hovered() {
                                     ui.ctx().set_cursor_icon(CursorIcon::Grab);
                                 }
 
+                                if response.clicked() {
+                                    match ui.input(|input| input.modifiers.command) {
+                                        true => pending_select_id = Some(task.id),
+                                        false => pending_details_id = Some(task.id)
+                                    }
+                                }
                                 if response.middle_clicked() {
-                                    self.form_task = Some(task.clone());
+                                    pending_form_task = Some(task.clone());
                                 } 
                                 if response.double_clicked() {
-                                    new_parent_task = Some(task.clone());
+                                    new_parent_id = Some(task.id);
+                                }
+
+                                // Dropping a card onto another one nests it there
+                                response.dnd_set_drag_payload(DraggedTask(task.id));
+                                if response.dnd_hover_payload::<DraggedTask>().is_some_and(|dragged_task| dragged_task.0 != task.id) {
+                                    ui.painter().rect_stroke(response.rect, TASK_RADIUS, DROP_ZONE_STROKE);
+                                    if let Some(dragged_task) = response.dnd_release_payload::<DraggedTask>() {
+                                        pending_move = Some((dragged_task.0, task.id));
+                                    }
                                 }
 
                                 if response.secondary_clicked() {
@@ -509,26 +1348,33 @@ impl Tako {
                                 }
                             }
                             self.show_arrange_labels(ui, &area_rect);
+                            self.handle_selection_rect(ui, &selection_response, &task_rects);
+                            if let Some(task_id) = pending_select_id {
+                                self.toggle_selection(task_id);
+                            }
 
                             if let Some(task) = pending_update_task { 
-                                let task = Box::new(task);
-                                match &mut self.arrange_nested_tasks.last_mut() {
-                                    Some(parent) => parent.add_subtask(task),
-                                    None => self.oswald.add_task(task)
-                                }
-                                self.save_arrange();
+                                self.oswald.add_task(Box::new(task));
                             }
 
                             if let Some(task_id) = pending_deletion_id {
-                                match &mut self.arrange_nested_tasks.last_mut() {
-                                    Some(parent) => parent.delete_subtask(task_id),
-                                    None => self.oswald.delete_task(task_id)
-                                }
-                                self.save_arrange();
+                                self.oswald.delete_task(task_id);
+                            }
+
+                            if let Some(parent_id) = new_parent_id {
+                                self.arrange_path.push(parent_id);
+                            }
+
+                            if let Some((task_id, new_parent_id)) = pending_move {
+                                self.move_task(task_id, Some(new_parent_id));
+                            }
+
+                            if let Some(task) = pending_form_task {
+                                self.open_task_form(task);
                             }
 
-                            if let Some(new_parent_task) = new_parent_task.take() {
-                                self.arrange_nested_tasks.push(new_parent_task);
+                            if pending_details_id.is_some() {
+                                self.detail_task = pending_details_id;
                             }
                         });
                 });
@@ -536,16 +1382,16 @@ impl Tako {
     }
 
     fn show_arrange_all_frame(&mut self, ui: &mut Ui, ctx: &Context) {
-        self.show_task_form(ctx);
         Frame::default()
             .show(ui, |ui| {
                 ui.vertical(|ui| {
                     ui.horizontal(|ui| {
                         if ui.add_sized(Vec2::new(144.0, 16.0), Button::new("Add Task")).clicked() {
-                            self.form_task = Some(Task::new_with_id(self.next_task_id));
+                            self.open_task_form(Task::new_with_id(self.next_task_id));
                         }
                         ui.checkbox(&mut self.settings.arrange_hide_parent_tasks, "Hide parent tasks");
                         ui.checkbox(&mut self.settings.arrange_hide_completed_tasks, "Hide completed tasks");
+                        self.show_tag_filter(ui);
                     });
                     ui.separator();
                     let (_, area_rect) = ui.allocate_space(ui.available_size());
@@ -554,21 +1400,41 @@ impl Tako {
                         .default_size(ui.available_size())
                         .constrain_to(area_rect)
                         .show(ctx, |ui| {
-                            let tasks: Vec<&Task> = self.oswald.get_all_tasks().into_iter()
+                            let tag_filter = parse_tags(&self.settings.tag_filter);
+                            let search_matches = self.search_matches();
+                            let tasks: Vec<&Task> = self.oswald.get_all_tasks_with_tags(&tag_filter).into_iter()
+                                .filter(|task| search_matches.as_ref().is_none_or(|ids| ids.contains(&task.id)))
                                 .filter(|task| !self.settings.arrange_hide_completed_tasks || !matches!(task.status, TaskStatus::Done))
                                 .filter(|task| !self.settings.arrange_hide_parent_tasks || task.get_complexity() == 1)
                                 .collect();
                             let mut pending_update_task: Option<Task> = None;
+                            let mut pending_form_task: Option<Task> = None;
+                            let mut pending_details_id: Option<u32> = None;
+                            let mut pending_select_id: Option<u32> = None;
+                            let mut task_rects: Vec<(u32, Rect)> = vec![];
+                            // Added before the cards so they keep their own clicks and drags
+                            let selection_response = ui.interact(area_rect, Id::new("arrange_selection"), Sense::click_and_drag());
 
                             for task in tasks {
                                 let response = task.show_arrange(ui, &area_rect);
+                                paint_timer_indicator(ui, task, response.rect, self.oswald.get_running_timer());
+                                paint_keyboard_focus(ui, task, response.rect, self.keyboard_focus);
+                                paint_selection(ui, task, response.rect, &self.selected_tasks);
+                                self.keyboard_ring.push(task.id);
+                                task_rects.push((task.id, response.rect));
 
                                 if response.hovered() {
                                     ui.ctx().set_cursor_icon(CursorIcon::Grab);
                                 }
 
+                                if response.clicked() {
+                                    match ui.input(|input| input.modifiers.command) {
+                                        true => pending_select_id = Some(task.id),
+                                        false => pending_details_id = Some(task.id)
+                                    }
+                                }
                                 if response.triple_clicked() {
-                                    self.form_task = Some(task.clone());
+                                    pending_form_task = Some(task.clone());
                                 }
 
                                 if response.dragged() {
@@ -582,15 +1448,140 @@ impl Tako {
                                 }
                             }
                             self.show_arrange_labels(ui, &area_rect);
+                            self.handle_selection_rect(ui, &selection_response, &task_rects);
+                            if let Some(task_id) = pending_select_id {
+                                self.toggle_selection(task_id);
+                            }
 
                             if let Some(task) = pending_update_task {
                                 self.oswald.add_task(Box::new(task));
                             }
+
+                            if let Some(task) = pending_form_task {
+                                self.open_task_form(task);
+                            }
+
+                            if pending_details_id.is_some() {
+                                self.detail_task = pending_details_id;
+                            }
                     });
                 });
             });
     }
-    fn show_task_form(&mut self, ctx: &Context) {
+    // Logs the finished work intervals, even when the focus view isn't open
+    fn advance_focus_session(&mut self) {
+        let Some(session) = &mut self.focus_session else {
+            return;
+        };
+        if let Some(entry) = session.advance(Utc::now()) {
+            self.oswald.add_time_entry(entry);
+        }
+        if session.get_phase() == FocusPhase::Finished {
+            self.focus_session = None;
+        }
+    }
+
+    fn show_focus_frame(&mut self, ui: &mut Ui) {
+        let now = Utc::now();
+        let mut pending_start: Option<u32> = None;
+        let mut pending_stop = false;
+        let mut pending_done: Option<u32> = None;
+        let mut pending_split: Option<u32> = None;
+
+        ui.vertical_centered(|ui| {
+            ui.add_space(DEFAULT_MARGIN);
+            match &self.focus_session {
+                None => {
+                    let overview = Tako::get_overview(
+                        &self.oswald,
+                        &self.settings,
+                        &self.overview_completed_tasks,
+                        self.search_matches()
+                    );
+                    let top_task = overview.columns.first()
+                        .and_then(|today| today.tasks.iter().find(|task| matches!(task.status, TaskStatus::Open | TaskStatus::Blocked)));
+                    let Some(task) = top_task else {
+                        ui.label(format!("Nothing left in {}", self.settings.today_column_label));
+                        return;
+                    };
+                    ui.label(RichText::new(&task.desc).strong().size(FOCUS_TASK_SIZE));
+                    ui.add_space(DEFAULT_MARGIN);
+                    ui.horizontal(|ui| {
+                        ui.label("Work:");
+                        ui.add(DragValue::new(&mut self.settings.focus_work_minutes).range(1..=u32::MAX).suffix(" min"));
+                        ui.label("Break:");
+                        ui.add(DragValue::new(&mut self.settings.focus_break_minutes).range(1..=u32::MAX).suffix(" min"));
+                    });
+                    if ui.button("Start").clicked() {
+                        pending_start = Some(task.id);
+                    }
+                },
+                Some(session) => {
+                    let desc = self.oswald.get_task(session.task_id)
+                        .map(|task| task.desc.as_str())
+                        .unwrap_or_default();
+                    let phase_label = match session.get_phase() {
+                        FocusPhase::Work => "Focus",
+                        _ => "Break"
+                    };
+                    ui.label(phase_label);
+                    ui.label(RichText::new(format_duration(session.get_remaining(now))).size(FOCUS_CLOCK_SIZE));
+                    ui.label(RichText::new(desc).strong().size(FOCUS_TASK_SIZE));
+                    ui.add_space(DEFAULT_MARGIN);
+
+                    if session.get_phase() == FocusPhase::Work {
+                        // Giving up doesn't log the interval
+                        if ui.button("Give up").clicked() {
+                            pending_stop = true;
+                        }
+                        return;
+                    }
+
+                    // The work interval is over, so it's a good time to decide what's next for the task
+                    ui.label("Session logged. What's next for this task?");
+                    if ui.button("Mark as done").clicked() {
+                        pending_done = Some(session.task_id);
+                    }
+                    ui.add(TextEdit::multiline(&mut self.focus_subtasks).hint_text("One subtask per line"));
+                    if ui.button("Split into subtasks").clicked() {
+                        pending_split = Some(session.task_id);
+                    }
+                    if ui.button("Skip break").clicked() {
+                        pending_stop = true;
+                    }
+                }
+            }
+        });
+
+        if let Some(task_id) = pending_start {
+            self.focus_session = Some(FocusSession::new(
+                task_id,
+                chrono::Duration::minutes(self.settings.focus_work_minutes.into()),
+                chrono::Duration::minutes(self.settings.focus_break_minutes.into()),
+                now
+            ));
+        }
+        if pending_stop {
+            self.focus_session = None;
+        }
+        if let Some(mut task) = pending_done.and_then(|id| self.oswald.get_task(id)).cloned() {
+            task.status = TaskStatus::Done;
+            self.overview_completed_tasks.insert(task.id);
+            self.oswald.add_task(Box::new(task));
+        }
+        if let Some(mut task) = pending_split.and_then(|id| self.oswald.get_task(id)).cloned() {
+            for desc in self.focus_subtasks.lines().map(str::trim).filter(|desc| !desc.is_empty()) {
+                let mut subtask = Task::new_with_id(self.next_task_id);
+                subtask.desc = desc.to_owned();
+                task.add_subtask(Box::new(subtask));
+                self.next_task_id = self.next_task_id.saturating_add(1);
+            }
+            self.focus_subtasks.clear();
+            self.oswald.add_task(Box::new(task));
+        }
+    }
+
+    fn show_task_form(&mut self, ctx: &Context) {
         let mut pending_cancel = false;
         let mut pending_save = false;
         if let Some(task) = &mut self.form_task {
@@ -599,6 +1590,30 @@ impl Tako {
                 .show(ctx, |ui| { 
                 ui.vertical(|ui| {
                     ui.text_edit_singleline(&mut task.desc);
+                    ui.horizontal(|ui| {
+                        ui.label("Tags:");
+                        ui.text_edit_singleline(&mut self.form_tags);
+                    });
+                    ui.horizontal(|ui| {
+                        let mut has_estimate = task.estimate.is_some();
+                        if ui.checkbox(&mut has_estimate, "Estimate").changed() {
+                            task.estimate = has_estimate.then_some(self.settings.default_estimate);
+                        }
+                        if let Some(estimate) = &mut task.estimate {
+                            ui.add(DragValue::new(estimate).suffix(self.settings.effort_unit.suffix()));
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Due:");
+                        ui.add(TextEdit::singleline(&mut self.form_due_date).hint_text("YYYY-MM-DD"));
+                        if !self.form_due_date.trim().is_empty() && self.form_due_date.trim().parse::<NaiveDate>().is_err() {
+                            ui.colored_label(MENU_ERROR_FG, "Invalid date");
+                        }
+                    });
+                    ui.label("Notes (Markdown):");
+                    ui.text_edit_multiline(&mut task.notes);
+                    ui.label("Links (one per line):");
+                    ui.text_edit_multiline(&mut self.form_links);
                     ui.horizontal(|ui| {
                         if ui.button("Cancel").clicked() {
                             pending_cancel = true;
@@ -612,36 +1627,532 @@ impl Tako {
         }
         if pending_cancel { self.form_task = None; }
         if pending_save {
-            if let Some(task) = self.form_task.take() {
-                let task = Box::new(task);
-
+            if let Some(mut task) = self.form_task.take() {
+                task.tags = parse_tags(&self.form_tags);
+                task.due_date = self.form_due_date.trim().parse().ok();
+                task.links = self.form_links.lines()
+                    .map(|link| link.trim().to_owned())
+                    .filter(|link| !link.is_empty())
+                    .collect();
                 if task.id == self.next_task_id {
-                    self.next_task_id += 1;
+                    self.next_task_id = self.next_task_id.saturating_add(1);
                 }
 
-                match self.arrange_nested_tasks.last_mut() {
-                    Some(parent_task) => {
-                        parent_task.add_subtask(task);
+                // Existing tasks are updated wherever they live, new ones go under the arranged parent
+                let task = Box::new(task);
+                match self.arrange_path.last() {
+                    Some(parent_id) if self.oswald.get_task(task.id).is_none() => {
+                        if let Err(err) = self.oswald.add_subtask(*parent_id, task) {
+                            println!("Couldn't add the task: {err}");
+                        }
                     },
-                    None => {
-                        self.oswald.add_task(task);
+                    _ => self.oswald.add_task(task)
+                }
+            }
+        }
+    }
+
+    fn show_quick_open(&mut self, ctx: &Context) {
+        if ctx.input_mut(|i| i.consume_key(Modifiers::COMMAND, Key::P)) {
+            self.quick_open = Some(String::new());
+        }
+        let Some(search_text) = &mut self.quick_open else {
+            return;
+        };
+        let mut pending_close = ctx.input(|i| i.key_pressed(Key::Escape));
+        let mut pending_open: Option<(u32, Vec<u32>)> = None;
+        Window::new("Quick Open")
+            .title_bar(false)
+            .resizable(false)
+            .fixed_size(Vec2::new(QUICK_OPEN_WIDTH, 0.0))
+            .anchor(Align2::CENTER_TOP, Vec2::new(0.0, QUICK_OPEN_OFFSET))
+            .show(ctx, |ui| {
+                let response = ui.add(TextEdit::singleline(search_text)
+                    .hint_text("Search tasks...")
+                    .desired_width(f32::INFINITY));
+                response.request_focus();
+                let submitted = ui.input(|i| i.key_pressed(Key::Enter));
+
+                let results = self.oswald.search(search_text);
+                for (idx, result) in results.into_iter().take(QUICK_OPEN_MAX_RESULTS).enumerate() {
+                    let Some(task) = self.oswald.get_task(result.id) else {
+                        continue;
+                    };
+                    let mut labels: Vec<&str> = result.path.iter()
+                        .filter_map(|id| self.oswald.get_task(*id))
+                        .map(|ancestor| ancestor.desc.as_str())
+                        .collect();
+                    labels.push(&task.desc);
+
+                    if ui.selectable_label(false, labels.join(QUICK_OPEN_PATH_SEPARATOR)).clicked() || (submitted && idx == 0) {
+                        pending_open = Some((result.id, result.path));
+                    }
+                }
+            });
+        if let Some((id, path)) = pending_open {
+            // Opening the task where it lives, so saving the form updates it in place
+            self.arrange_path = path;
+            self.current_view = View::Arrange;
+            if let Some(task) = self.oswald.get_task(id).cloned() {
+                self.open_task_form(task);
+            }
+            pending_close = true;
+        }
+        if pending_close {
+            self.quick_open = None;
+        }
+    }
+
+    // Updates the task in Oswald and in the arrange stack, so saving the stack doesn't revert it
+    fn show_task_details(&mut self, ctx: &Context) {
+        let Some(task) = self.detail_task.and_then(|id| self.oswald.get_task(id)) else {
+            self.detail_task = None;
+            return;
+        };
+        let mut task = task.clone();
+        let mut task_changed = false;
+        let mut pending_close = false;
+        let mut pending_edit = false;
+        let mut pending_timer = false;
+        let mut pending_unsnooze = false;
+        let mut pending_template = false;
+        let mut pending_assign: Option<(Option<u32>, Option<NaiveDate>)> = None;
+        let timer_running = self.oswald.get_running_timer().is_some_and(|entry| entry.task_id == task.id);
+
+        SidePanel::right("Details")
+            .exact_width(DETAILS_WIDTH)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ScrollArea::vertical().show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.button("Close").clicked() {
+                            pending_close = true;
+                        }
+                        if ui.button("Edit").clicked() {
+                            pending_edit = true;
+                        }
+                    });
+                    ui.label(RichText::new(&task.desc).strong().size(DETAILS_HEADING_SIZE));
+                    if !task.tags.is_empty() {
+                        let tags: Vec<String> = task.tags.iter().map(|tag| format!("#{tag}")).collect();
+                        ui.label(tags.join(" "));
+                    }
+                    ui.label(format!(
+                        "Effort: {}{}",
+                        task.get_effort(self.settings.default_estimate),
+                        self.settings.effort_unit.suffix()
+                    ));
+                    if let Some(due_date) = task.due_date {
+                        ui.label(format!("Due {}", due_date.format("%a %d %b %Y")));
+                    }
+                    if let Some(until) = task.deferred_until {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("Snoozed until {until}"));
+                            if ui.button("Unsnooze").clicked() {
+                                pending_unsnooze = true;
+                            }
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        let assignee_name = task.assignee
+                            .and_then(|id| self.oswald.get_person(id))
+                            .map_or("Nobody".to_owned(), |person| person.name.clone());
+                        ui.label("Assignee");
+                        ComboBox::from_id_source("detail_assignee")
+                            .selected_text(assignee_name)
+                            .show_ui(ui, |ui| {
+                                if ui.selectable_label(task.assignee.is_none(), "Nobody").clicked() {
+                                    pending_assign = Some((None, None));
+                                }
+                                for person in self.oswald.get_people() {
+                                    if ui.selectable_label(task.assignee == Some(person.id), &person.name).clicked() {
+                                        pending_assign = Some((Some(person.id), task.follow_up));
+                                    }
+                                }
+                            });
+                    });
+                    if task.assignee.is_some() {
+                        if let Some(follow_up) = task.follow_up {
+                            ui.label(format!("Follow up on {}", follow_up.format("%a %d %b %Y")));
+                        }
+                        ui.horizontal(|ui| {
+                            ui.add(TextEdit::singleline(&mut self.detail_follow_up).hint_text("YYYY-MM-DD"));
+                            let follow_up = self.detail_follow_up.trim().parse::<NaiveDate>().ok();
+                            if ui.add_enabled(follow_up.is_some(), Button::new("Follow up")).clicked() {
+                                pending_assign = Some((task.assignee, follow_up));
+                            }
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Tracked: {}", format_duration(self.oswald.get_tracked_time(task.id, Utc::now()))));
+                        let timer_label = match timer_running {
+                            true => "Stop timer",
+                            false => "Start timer"
+                        };
+                        if ui.button(timer_label).clicked() {
+                            pending_timer = true;
+                        }
+                    });
+                    ui.separator();
+
+                    show_markdown(ui, &task.notes);
+
+                    if !task.links.is_empty() {
+                        ui.separator();
+                        for link in task.links.iter() {
+                            ui.hyperlink(link);
+                        }
+                    }
+
+                    ui.separator();
+                    let (done, total) = task.get_checklist_progress();
+                    ui.label(format!("Checklist ({done}/{total})"));
+                    let mut item_to_remove: Option<usize> = None;
+                    for (idx, item) in task.checklist.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            task_changed |= ui.checkbox(&mut item.done, &item.text).changed();
+                            if ui.small_button("x").clicked() {
+                                item_to_remove = Some(idx);
+                            }
+                        });
+                    }
+                    if let Some(idx) = item_to_remove {
+                        task.checklist.remove(idx);
+                        task_changed = true;
+                    }
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut self.detail_checklist_item);
+                        if ui.button("Add").clicked() {
+                            task.add_checklist_item(&self.detail_checklist_item);
+                            self.detail_checklist_item.clear();
+                            task_changed = true;
+                        }
+                    });
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.add(TextEdit::singleline(&mut self.detail_template_name).hint_text("Template name"));
+                        if ui.button("Save as template").clicked() {
+                            pending_template = true;
+                        }
+                    });
+                });
+            });
+
+        if pending_edit {
+            self.open_task_form(task.clone());
+        }
+        if pending_timer {
+            self.toggle_timer(task.id);
+        }
+        if pending_unsnooze {
+            self.snooze_task(task.id, None);
+        }
+        if pending_template {
+            match self.oswald.add_template(Template::new(&self.detail_template_name, task.clone())) {
+                Ok(()) => {
+                    self.detail_template_name.clear();
+                    self.open_templates = true;
+                },
+                Err(err) => println!("Couldn't save the template: {err}")
+            }
+        }
+        if task_changed {
+            self.oswald.add_task(Box::new(task.clone()));
+        }
+        if let Some((assignee, follow_up)) = pending_assign {
+            match self.oswald.assign_task(task.id, assignee, follow_up) {
+                Ok(()) => self.detail_follow_up.clear(),
+                Err(err) => println!("Couldn't assign the task: {err}")
+            }
+        }
+        if pending_close {
+            self.detail_task = None;
+        }
+    }
+
+    // What each person owes us, with the follow-ups that are due in red
+    fn show_waiting_for(&mut self, ctx: &Context) {
+        let today = Local::now().date_naive();
+        let mut pending_add = false;
+        let mut pending_delete: Option<u32> = None;
+        let mut pending_details: Option<u32> = None;
+        Window::new("Waiting For")
+            .collapsible(false)
+            .open(&mut self.open_waiting_for)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(TextEdit::singleline(&mut self.new_person_name).hint_text("Name"));
+                    if ui.button("Add person").clicked() {
+                        pending_add = true;
+                    }
+                });
+                for person in self.oswald.get_people() {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(&person.name).strong());
+                        if ui.small_button("x").on_hover_text("Take their tasks back").clicked() {
+                            pending_delete = Some(person.id);
+                        }
+                    });
+                    let tasks = self.oswald.get_waiting_for(person.id);
+                    if tasks.is_empty() {
+                        ui.label("All caught up");
+                    }
+                    for task in tasks {
+                        ui.horizontal(|ui| {
+                            if ui.link(&task.desc).clicked() {
+                                pending_details = Some(task.id);
+                            }
+                            if let Some(follow_up) = task.follow_up {
+                                let label = format!("follow up {}", follow_up.format("%a %d %b"));
+                                match follow_up <= today {
+                                    true => ui.colored_label(MENU_ERROR_FG, label),
+                                    false => ui.label(label)
+                                };
+                            }
+                        });
+                    }
+                }
+            });
+
+        if pending_add {
+            let result = self.oswald.get_next_person_id()
+                .and_then(|id| self.oswald.add_person(Person::new(id, &self.new_person_name)));
+            match result {
+                Ok(()) => self.new_person_name.clear(),
+                Err(err) => println!("Couldn't add the person: {err}")
+            }
+        }
+        if let Some(id) = pending_delete {
+            if let Err(err) = self.oswald.delete_person(id) {
+                println!("Couldn't delete the person: {err}");
+            }
+        }
+        if pending_details.is_some() {
+            self.detail_task = pending_details;
+        }
+    }
+
+    // New copies go under the arranged task, or at the top level from the other views
+    fn show_templates(&mut self, ctx: &Context) {
+        let mut pending_use: Option<String> = None;
+        let mut pending_delete: Option<String> = None;
+        let mut pending_instantiate = false;
+        let mut pending_cancel = false;
+        Window::new("Templates")
+            .collapsible(false)
+            .open(&mut self.open_templates)
+            .show(ctx, |ui| {
+                if self.oswald.get_templates().is_empty() {
+                    ui.label("Save a task as a template from its details");
+                }
+                for template in self.oswald.get_templates() {
+                    ui.horizontal(|ui| {
+                        if ui.button("Use").clicked() {
+                            pending_use = Some(template.name.clone());
+                        }
+                        if ui.button("Delete").clicked() {
+                            pending_delete = Some(template.name.clone());
+                        }
+                        ui.label(&template.name);
+                    });
+                }
+                let Some((name, values)) = &mut self.template_values else {
+                    return;
+                };
+                ui.separator();
+                ui.label(RichText::new(name.as_str()).strong());
+                for (placeholder, value) in values.iter_mut() {
+                    ui.horizontal(|ui| {
+                        ui.label(placeholder);
+                        ui.text_edit_singleline(value);
+                    });
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        pending_cancel = true;
+                    }
+                    if ui.button("Create").clicked() {
+                        pending_instantiate = true;
                     }
+                });
+            });
+
+        if let Some(template) = pending_use.and_then(|name| self.oswald.get_template(&name)) {
+            let values = template.get_placeholders()
+                .into_iter()
+                .map(|placeholder| (placeholder, String::new()))
+                .collect();
+            self.template_values = Some((template.name.clone(), values));
+        }
+        if let Some(name) = pending_delete {
+            if let Err(err) = self.oswald.delete_template(&name) {
+                println!("Couldn't delete the template: {err}");
+            }
+        }
+        if pending_instantiate {
+            if let Some((name, values)) = self.template_values.take() {
+                let parent = match self.current_view {
+                    View::Arrange => self.arrange_path.last().copied(),
+                    _ => None
+                };
+                match self.oswald.instantiate_template(&name, &values, parent) {
+                    Ok(_) => self.next_task_id = max(self.next_task_id, self.oswald.get_next_id().unwrap_or(u32::MAX)),
+                    Err(err) => println!("Couldn't use the template: {err}")
                 }
-                self.save_arrange();
             }
         }
+        if pending_cancel || !self.open_templates {
+            self.template_values = None;
+        }
     }
 
-    fn save_arrange(&mut self) {
-        let mut curr: Option<Task> = None;
-        for task in self.arrange_nested_tasks.iter_mut().rev() {
-            if let Some(prev_task) = curr.take() {
-                task.add_subtask(Box::new(prev_task));
+    // Applies the shortcuts caught in `raw_input_hook` to the focused task. The focus ring is
+    // made of the tasks painted in the last frame, so it follows the current view and filters.
+    fn handle_key_actions(&mut self) {
+        if !self.keyboard_focus.is_some_and(|id| self.keyboard_ring.contains(&id)) {
+            self.keyboard_focus = None;
+        }
+        for action in std::mem::take(&mut self.pending_key_actions) {
+            let focused_task = self.keyboard_focus.and_then(|id| self.oswald.get_task(id)).cloned();
+            match action {
+                KeyAction::FocusNext | KeyAction::FocusPrevious => {
+                    let ring = &self.keyboard_ring;
+                    if ring.is_empty() {
+                        continue;
+                    }
+                    let focus_idx = self.keyboard_focus.and_then(|id| ring.iter().position(|ring_id| *ring_id == id));
+                    let next_idx = match (action, focus_idx) {
+                        (KeyAction::FocusNext, Some(idx)) => (idx + 1) % ring.len(),
+                        (KeyAction::FocusNext, None) => 0,
+                        (_, Some(idx)) => (idx + ring.len() - 1) % ring.len(),
+                        (_, None) => ring.len() - 1
+                    };
+                    self.keyboard_focus = Some(ring[next_idx]);
+                },
+                KeyAction::Edit => {
+                    if let Some(task) = focused_task {
+                        self.open_task_form(task);
+                    }
+                },
+                KeyAction::NewTask => self.open_task_form(Task::new_with_id(self.next_task_id)),
+                KeyAction::MoreImportant | KeyAction::LessImportant | KeyAction::MoreUrgent | KeyAction::LessUrgent => {
+                    let Some(mut task) = focused_task else { continue };
+                    match action {
+                        KeyAction::MoreImportant => task.importance += KEYBOARD_NUDGE,
+                        KeyAction::LessImportant => task.importance -= KEYBOARD_NUDGE,
+                        KeyAction::MoreUrgent => task.urgency += KEYBOARD_NUDGE,
+                        _ => task.urgency -= KEYBOARD_NUDGE
+                    }
+                    self.oswald.add_task(Box::new(task));
+                },
+                KeyAction::GoDown => {
+                    if let (View::Arrange, Some(task)) = (&self.current_view, focused_task) {
+                        self.arrange_path.push(task.id);
+                        self.keyboard_focus = None;
+                    }
+                },
+                KeyAction::GoUp => {
+                    if matches!(self.current_view, View::Arrange) {
+                        self.keyboard_focus = self.arrange_path.pop();
+                    }
+                },
+                KeyAction::ToggleDone => {
+                    let Some(mut task) = focused_task else { continue };
+                    task.status = match task.status {
+                        TaskStatus::Done => TaskStatus::Open,
+                        _ => TaskStatus::Done
+                    };
+                    if matches!(task.status, TaskStatus::Done) {
+                        self.overview_completed_tasks.insert(task.id);
+                    } else {
+                        self.overview_completed_tasks.remove(&task.id);
+                    }
+                    self.oswald.add_task(Box::new(task));
+                },
+                KeyAction::ToggleArchived => {
+                    let Some(mut task) = focused_task else { continue };
+                    task.status = match task.status {
+                        TaskStatus::Archived => TaskStatus::Open,
+                        _ => TaskStatus::Archived
+                    };
+                    self.oswald.add_task(Box::new(task));
+                },
+                KeyAction::CheatSheet => self.open_cheat_sheet = !self.open_cheat_sheet
+            }
+        }
+    }
+
+    fn show_cheat_sheet(&mut self, ctx: &Context) {
+        Window::new("Keyboard shortcuts")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut self.open_cheat_sheet)
+            .show(ctx, |ui| {
+                Grid::new("cheat_sheet").striped(true).show(ui, |ui| {
+                    for (action, shortcut) in self.settings.keymap.iter() {
+                        ui.monospace(ctx.format_shortcut(shortcut));
+                        ui.label(action.label());
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+
+    // Brings back the snoozed tasks on start and once the day changes
+    fn auto_resurface_tasks(&mut self, ctx: &Context) {
+        let now = Local::now().naive_local();
+        if self.last_resurface != Some(now.date()) {
+            for task_id in self.oswald.resurface_tasks(now.date()) {
+                if let Some(task) = self.oswald.get_task(task_id).cloned() {
+                    self.oswald.add_task(Box::new(task));
+                }
             }
-            curr = Some(task.clone());
+            self.last_resurface = Some(now.date());
         }
-        if let Some(last_task) = curr.take() {
-            self.oswald.add_task(Box::new(last_task));
+        let next_midnight = now.date().succ_opt().unwrap_or(now.date()).and_time(NaiveTime::MIN);
+        ctx.request_repaint_after((next_midnight - now).to_std().unwrap_or_default());
+    }
+
+    fn toggle_live_feed(&mut self, ctx: &Context) {
+        if self.live_feed.take().is_none() {
+            self.live_feed_error = None;
+            self.live_feed = Some(LiveFeed::connect(ctx, &self.settings.server_address, &self.settings.server_token));
+        }
+    }
+
+    // Remote changes wait while anything is dragged, so cards don't jump from under the pointer
+    // and a drop isn't undone by an older version of the task
+    fn apply_remote_events(&mut self, ctx: &Context) {
+        if let Some(live_feed) = &self.live_feed {
+            while let Ok(result) = live_feed.events.try_recv() {
+                match result {
+                    Ok(event) => self.remote_events.push(event),
+                    Err(err) => {
+                        self.live_feed_error = Some(err.to_string());
+                        break;
+                    }
+                }
+            }
+        }
+        if self.live_feed_error.is_some() {
+            self.live_feed = None;
+        }
+        if self.remote_events.is_empty() || ctx.dragged_id().is_some() {
+            return;
+        }
+        // Resets are merged with the changes made here, so they aren't lost on reconnecting
+        for event in self.remote_events.drain(..) {
+            if let Err(err) = merge_remote_event(&mut self.oswald, event) {
+                self.live_feed_error = Some(err.to_string());
+                self.live_feed = None;
+                break;
+            }
+        }
+        self.next_task_id = max(self.next_task_id, self.oswald.get_next_id().unwrap_or(u32::MAX));
+        if let Some(idx) = self.arrange_path.iter().position(|id| self.oswald.get_task(*id).is_none()) {
+            self.arrange_path.truncate(idx);
         }
     }
 
@@ -681,23 +2192,120 @@ impl eframe::App for Tako {
             },
             Err(err) => { println!("Couldn't save the last flush date: {err}") }
         }
+
+        match serde_json::to_string(self.oswald.get_time_entries()) {
+            Ok(time_entries) => {
+                storage.set_string("time_entries", time_entries);
+            },
+            Err(err) => { println!("Couldn't save time entries: {err}") }
+        }
+
+        match serde_json::to_string(&self.oswald.get_people()) {
+            Ok(people) => {
+                storage.set_string("people", people);
+            },
+            Err(err) => { println!("Couldn't save people: {err}") }
+        }
+
+        match serde_json::to_string(&self.oswald.get_templates()) {
+            Ok(templates) => {
+                storage.set_string("templates", templates);
+            },
+            Err(err) => { println!("Couldn't save templates: {err}") }
+        }
+
+        match serde_json::to_string(&self.oswald.get_daily_plan()) {
+            Ok(daily_plan) => {
+                storage.set_string("daily_plan", daily_plan);
+            },
+            Err(err) => { println!("Couldn't save the daily plan: {err}") }
+        }
+
+        storage.set_string("server_address", self.settings.server_address.clone());
+        storage.set_string("server_token", self.settings.server_token.clone());
     }
 
     fn auto_save_interval(&self) -> Duration { AUTO_SAVE_INTERVAL }
 
+    // Shortcuts are taken out of the input before egui sees it, so Tab and the arrows don't
+    // also move egui's own focus around
+    fn raw_input_hook(&mut self, ctx: &Context, raw_input: &mut RawInput) {
+        if let Some(action) = self.recording_shortcut {
+            let pressed_key = raw_input.events.iter().find_map(|event| match event {
+                Event::Key { key, pressed: true, modifiers, .. } => Some(KeyboardShortcut::new(*modifiers, *key)),
+                _ => None
+            });
+            if let Some(shortcut) = pressed_key {
+                self.recording_shortcut = None;
+                raw_input.events.retain(|event| !matches!(event, Event::Key { .. } | Event::Text(_)));
+                if shortcut.logical_key == Key::Escape {
+                    return;
+                }
+                // Whatever used the shortcut gets the old one, so every action stays bound
+                let keymap = &mut self.settings.keymap;
+                let old_shortcut = keymap.iter().find(|(bound_action, _)| *bound_action == action).map(|(_, shortcut)| *shortcut);
+                for (bound_action, bound_shortcut) in keymap.iter_mut() {
+                    if *bound_action == action {
+                        *bound_shortcut = shortcut;
+                    } else if *bound_shortcut == shortcut {
+                        *bound_shortcut = old_shortcut.unwrap_or(shortcut);
+                    }
+                }
+            }
+            return;
+        }
+        if is_typing(ctx) || self.form_task.is_some() || self.quick_open.is_some() {
+            return;
+        }
+        raw_input.events.retain(|event| {
+            let Event::Key { key, pressed, modifiers, .. } = event else { return true };
+            match get_key_action(&self.settings.keymap, *modifiers, *key) {
+                Some(action) => {
+                    if *pressed {
+                        self.pending_key_actions.push(action);
+                    }
+                    false
+                },
+                None => true
+            }
+        });
+    }
+
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) { 
         self.auto_flush_overview_completed_tasks();
+        self.auto_resurface_tasks(ctx);
+        self.auto_open_day();
+        self.apply_remote_events(ctx);
+        self.oswald.set_complexity_mode(match self.settings.effort_complexity {
+            true => ComplexityMode::Effort { default_estimate: self.settings.default_estimate },
+            false => ComplexityMode::Subtasks
+        });
+        self.advance_focus_session();
+        self.handle_key_actions();
+        self.keyboard_ring.clear();
+        if self.oswald.get_running_timer().is_some() || self.focus_session.is_some() {
+            ctx.request_repaint_after(TIMER_REPAINT_INTERVAL);
+        }
         self.show_menu(ctx);
+        self.show_selection_bar(ctx);
+        self.show_quick_open(ctx);
+        self.show_task_details(ctx);
+        self.show_task_form(ctx);
+        self.show_cheat_sheet(ctx);
+        self.show_templates(ctx);
+        self.show_waiting_for(ctx);
 
         CentralPanel::default().show(ctx, |ui| {
             match self.current_view {
                 View::Overview => self.show_overview_frame(ui, ctx),
                 View::Arrange => self.show_arrange_frame(ui, ctx),
-                View::ArrangeAll => self.show_arrange_all_frame(ui, ctx)
+                View::ArrangeAll => self.show_arrange_all_frame(ui, ctx),
+                View::Focus => self.show_focus_frame(ui)
             }
         });
 
         let mut column_to_remove: Option<usize> = None;
+        let mut toggle_live_feed = false;
         Window::new("Settings")
             .max_width(MENU_WIDTH)
             .open(&mut self.open_settings)
@@ -708,6 +2316,30 @@ impl eframe::App for Tako {
                         ui.label("# of tasks / day:");
                         ui.add(Slider::new(&mut self.settings.target_daily_tasks, 1..=MAX_TARGET_DAILY_TASKS))
                     });
+                    ui.vertical(|ui| {
+                        ui.add_space(DEFAULT_MARGIN);
+                        ui.label("Effort:");
+                        ComboBox::from_id_source("effort_unit")
+                            .selected_text(self.settings.effort_unit.suffix().trim())
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.settings.effort_unit, EffortUnit::Minutes, EffortUnit::Minutes.suffix().trim());
+                                ui.selectable_value(&mut self.settings.effort_unit, EffortUnit::Points, EffortUnit::Points.suffix().trim());
+                            });
+                        let suffix = self.settings.effort_unit.suffix();
+                        ui.horizontal(|ui| {
+                            ui.label("Default estimate:");
+                            ui.add(DragValue::new(&mut self.settings.default_estimate).suffix(suffix));
+                        });
+                        ui.checkbox(&mut self.settings.effort_complexity, "Effort-based complexity");
+                        ui.checkbox(&mut self.settings.fill_by_budget, "Fill days by budget");
+                        ui.horizontal(|ui| {
+                            ui.label("Daily budget:");
+                            ui.add_enabled(
+                                self.settings.fill_by_budget,
+                                DragValue::new(&mut self.settings.daily_budget).range(1..=u32::MAX).suffix(suffix)
+                            );
+                        });
+                    });
                     ui.vertical(|ui| {
                         ui.add_space(DEFAULT_MARGIN);
                         ui.label("Overview columns:");
@@ -717,21 +2349,70 @@ impl eframe::App for Tako {
                                 if ui.button("Remove").clicked() {
                                     column_to_remove = Some(idx);
                                 }
-                                ui.text_edit_singleline(column);
+                                ui.text_edit_singleline(&mut column.label);
+                                ui.add(DragValue::new(&mut column.days).range(1..=MAX_COLUMN_DAYS).suffix(" days"));
                             });
                         }
                         ui.text_edit_singleline(&mut self.settings.today_column_label);
                         if ui.button("Add column").clicked() {
-                            self.settings.overview_columns.push("".to_owned());
+                            self.settings.overview_columns.push(CalendarColumn::new("", 1));
                         }
                         ui.shrink_width_to_current();
                     });
+                    ui.vertical(|ui| {
+                        ui.add_space(DEFAULT_MARGIN);
+                        ui.label("Capacity per weekday:");
+                        for (label, capacity) in WEEKDAY_LABELS.iter().zip(self.settings.weekday_capacity.iter_mut()) {
+                            ui.horizontal(|ui| {
+                                ui.label(*label);
+                                ui.add(DragValue::new(capacity).range(0..=MAX_WEEKDAY_CAPACITY).suffix("%"));
+                            });
+                        }
+                    });
+                    ui.vertical(|ui| {
+                        ui.add_space(DEFAULT_MARGIN);
+                        ui.label("Keyboard shortcuts:");
+                        for (action, shortcut) in self.settings.keymap.iter() {
+                            ui.horizontal(|ui| {
+                                let shortcut_label = match self.recording_shortcut == Some(*action) {
+                                    true => "Press a key…".to_owned(),
+                                    false => ctx.format_shortcut(shortcut)
+                                };
+                                if ui.button(shortcut_label).clicked() {
+                                    self.recording_shortcut = Some(*action);
+                                }
+                                ui.label(action.label());
+                            });
+                        }
+                        if ui.button("Reset shortcuts").clicked() {
+                            self.settings.keymap = DEFAULT_KEYMAP.to_vec();
+                        }
+                    });
+                    ui.vertical(|ui| {
+                        ui.add_space(DEFAULT_MARGIN);
+                        ui.label("Live updates:");
+                        let is_connected = self.live_feed.is_some();
+                        ui.add_enabled(!is_connected, TextEdit::singleline(&mut self.settings.server_address).hint_text("127.0.0.1:1337"));
+                        ui.add_enabled(!is_connected, TextEdit::singleline(&mut self.settings.server_token).hint_text("Token").password(true));
+                        if ui.button(if is_connected { "Disconnect" } else { "Connect" }).clicked() {
+                            toggle_live_feed = true;
+                        }
+                        if let Some(err) = &self.live_feed_error {
+                            ui.colored_label(MENU_ERROR_FG, err);
+                        }
+                    });
                     ui.add_space(DEFAULT_MARGIN);
                 });
             });
         if let Some(column_id) = column_to_remove {
             self.settings.overview_columns.remove(column_id);
         }
+        if toggle_live_feed {
+            self.toggle_live_feed(ctx);
+        }
+        if !self.open_settings {
+            self.recording_shortcut = None;
+        }
     }
 }
 pub async fn start(mut oswald: Oswald) -> eframe::Result {
@@ -742,12 +2423,14 @@ pub async fn start(mut oswald: Oswald) -> eframe::Result {
     run_native("Tako", options, Box::new(|cc| {
         let mut overview_completed_tasks: HashSet<u32> = HashSet::new();
         let mut overview_completed_tasks_last_flush: Option<NaiveDate> = None;
+        let mut server_address = String::new();
+        let mut server_token = String::new();
         if let Some(storage) = cc.storage { 
             // Retrieve all tasks
             let tasks_str = storage.get_string("tasks").unwrap_or("[]".to_owned());
             let raw_tasks: Vec<Task> = serde_json::from_str(&tasks_str)?;
             for task in raw_tasks { 
-                oswald.add_task(Box::new(task)); 
+                oswald.restore_task(Box::new(task)); 
             }
 
             // Retrieve completed tasks
@@ -760,32 +2443,177 @@ pub async fn start(mut oswald: Oswald) -> eframe::Result {
             if let Some(raw_date) = last_flush_date_str {
                 overview_completed_tasks_last_flush = serde_json::from_str(&raw_date)?;
             }
-        }
-        let mut next_task_id: u32 = 1;
 
-        for task in oswald.get_all_tasks() {
-            next_task_id = max(next_task_id, task.id + 1);
+            // Retrieve time entries
+            let time_entries_str = storage.get_string("time_entries").unwrap_or("[]".to_owned());
+            let time_entries: Vec<TimeEntry> = serde_json::from_str(&time_entries_str)?;
+            for entry in time_entries {
+                oswald.add_time_entry(entry);
+            }
+
+            // Retrieve people
+            let people_str = storage.get_string("people").unwrap_or("[]".to_owned());
+            let people: Vec<Person> = serde_json::from_str(&people_str)?;
+            for person in people {
+                oswald.add_person(person)?;
+            }
+
+            // Retrieve templates
+            let templates_str = storage.get_string("templates").unwrap_or("[]".to_owned());
+            let templates: Vec<Template> = serde_json::from_str(&templates_str)?;
+            for template in templates {
+                oswald.add_template(template)?;
+            }
+
+            // Retrieve the last daily plan
+            let daily_plan_str = storage.get_string("daily_plan").unwrap_or("null".to_owned());
+            let daily_plan: Option<DailyPlan> = serde_json::from_str(&daily_plan_str)?;
+            if let Some(plan) = daily_plan {
+                oswald.set_daily_plan(plan);
+            }
+
+            // Retrieve the server to take live updates from
+            server_address = storage.get_string("server_address").unwrap_or_default();
+            server_token = storage.get_string("server_token").unwrap_or_default();
         }
+        // Reconnecting to the server from last time
+        let live_feed = (!server_address.is_empty() && !server_token.is_empty())
+            .then(|| LiveFeed::connect(&cc.egui_ctx, &server_address, &server_token));
+        let next_task_id = oswald.get_next_id().unwrap_or(u32::MAX);
         // Defaults
         Ok(Box::new(Tako {
             oswald, 
-            arrange_nested_tasks: vec![],
+            arrange_path: vec![],
             current_view: View::Overview,
+            detail_task: None,
+            detail_checklist_item: String::new(),
+            detail_template_name: String::new(),
+            detail_follow_up: String::new(),
+            focus_session: None,
+            focus_subtasks: String::new(),
             form_task: None,
+            form_due_date: String::new(),
+            form_links: String::new(),
+            form_tags: String::new(),
+            keyboard_focus: None,
+            keyboard_ring: vec![],
+            last_resurface: None,
+            live_feed,
+            live_feed_error: None,
             next_task_id,
+            open_cheat_sheet: false,
             open_settings: false,
+            open_templates: false,
+            open_waiting_for: false,
+            new_person_name: String::new(),
+            pending_key_actions: vec![],
             overview_completed_tasks,
             overview_completed_tasks_last_flush,
+            quick_open: None,
+            quick_add_text: String::new(),
+            quick_add_error: None,
+            recording_shortcut: None,
+            remote_events: vec![],
+            selected_tasks: HashSet::new(),
+            selection_origin: None,
+            selection_tag: String::new(),
             settings: Settings {
                 arrange_hide_parent_tasks: true,
                 arrange_hide_completed_tasks: true,
                 target_daily_tasks: 5,
                 backlog_column_label: "Backlog".to_owned(),
+                daily_budget: 240,
+                default_estimate: 30,
+                effort_complexity: false,
+                effort_unit: EffortUnit::Minutes,
+                fill_by_budget: false,
+                focus_break_minutes: 5,
+                focus_work_minutes: 25,
+                keymap: DEFAULT_KEYMAP.to_vec(),
                 overview_columns: vec![
-                    "Tomorrow".to_owned(),
+                    CalendarColumn::new("Tomorrow", 1),
                 ],
-                today_column_label: "Today".to_owned()
+                search_query: String::new(),
+                server_address,
+                server_token,
+                tag_filter: String::new(),
+                today_column_label: "Today".to_owned(),
+                weekday_capacity: [100, 100, 100, 100, 100, 0, 0]
             },
+            template_values: None,
         }))
     }))
 }
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod wasm_app_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_events() {
+        let mut parser = EventParser::default();
+        let task = Task::new(4, "Café".to_owned(), 1.0, 1.0, TaskStatus::Open);
+        let upserted = serde_json::to_string(&TaskEvent::Upserted { parent: None, task: Box::new(task.clone()) }).unwrap();
+        let raw_events = format!(
+            "data: {{\"event\":\"reset\",\"tasks\":[]}}\n\n:\n\nevent: shared\ndata: {{\"event\":\"deleted\",\"id\":1}}\n\ndata: {upserted}\r\n\r\n"
+        );
+        // Chunks can end anywhere, even within a character
+        let (first, rest) = raw_events.as_bytes().split_at(raw_events.find('é').unwrap() + 1);
+        assert_eq!(parser.push(first).unwrap(), vec![TaskEvent::Reset { tasks: vec![] }]);
+        let events = parser.push(rest).unwrap();
+        assert_eq!(events.len(), 1);
+        let TaskEvent::Upserted { task: parsed_task, .. } = &events[0] else { panic!("{events:?}") };
+        assert_eq!(parsed_task.desc, "Café");
+
+        assert!(parser.push(b"data: not json\n\n").is_err());
+    }
+
+    #[cfg(feature = "rest_api")]
+    async fn next_event(live_feed: &LiveFeed) -> TaskEvent {
+        for _ in 0..500 {
+            if let Ok(result) = live_feed.events.try_recv() {
+                return result.unwrap();
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("No event came");
+    }
+
+    #[cfg(feature = "rest_api")]
+    #[tokio::test]
+    async fn test_live_feed() {
+        use crate::clients::api;
+        use crate::ports::DummyStore;
+        use serde_json::{Value, json};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let app = api::app(DummyStore).await;
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let http = reqwest::Client::new();
+        let user: Value = http.post(format!("http://{address}/users"))
+            .json(&json!({ "name": "Ada" }))
+            .send().await.unwrap()
+            .json().await.unwrap();
+        let token = user["token"].as_str().unwrap();
+
+        let live_feed = LiveFeed::connect(&Context::default(), &address, token);
+        let mut oswald = Oswald::new(DummyStore);
+        oswald.add_task(Box::new(Task::new(1, "Local".to_owned(), 1.0, 1.0, TaskStatus::Open)));
+        merge_remote_event(&mut oswald, next_event(&live_feed).await).unwrap();
+        assert_eq!(oswald.get_task(1).unwrap().desc, "Local");
+
+        let task = Task::new(1, "Remote".to_owned(), 1.0, 1.0, TaskStatus::Open);
+        http.post(format!("http://{address}/tasks/"))
+            .bearer_auth(token)
+            .json(&task)
+            .send().await.unwrap();
+        let event = next_event(&live_feed).await;
+        assert!(matches!(&event, TaskEvent::Upserted { task, .. } if task.desc == "Remote"));
+        merge_remote_event(&mut oswald, event).unwrap();
+        assert_eq!(oswald.get_task(1).unwrap().desc, "Remote");
+    }
+}
+
+/* ========================================================================= */
@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+
+/* USER ==================================================================== */
+// Account on a shared server, each with a task forest of its own. Only a hash
+// of the user's token is kept, so leaking it doesn't give access away.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub id: u32,
+    pub name: String,
+    #[serde(skip_serializing, default)]
+    pub token_hash: String,
+}
+
+/* ACCOUNTS ================================================================ */
+#[derive(Debug, Default, Clone)]
+pub struct Accounts {
+    users: BTreeMap<u32, User>,
+}
+impl Accounts {
+    pub fn new(users: Vec<User>) -> Self {
+        Accounts { users: users.into_iter().map(|user| (user.id, user)).collect() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+
+    pub fn get_users(&self) -> Vec<&User> {
+        self.users.values().collect()
+    }
+
+    pub fn get_user(&self, id: u32) -> Option<&User> {
+        self.users.get(&id)
+    }
+
+    pub fn find_by_token_hash(&self, token_hash: &str) -> Option<&User> {
+        self.users.values().find(|user| user.token_hash == token_hash)
+    }
+
+    // Names are unique, regardless of their case
+    pub fn add_user(&mut self, name: &str, token_hash: &str) -> anyhow::Result<u32> {
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("Users need a name");
+        }
+        if self.users.values().any(|user| user.name.eq_ignore_ascii_case(name)) {
+            anyhow::bail!("User \"{name}\" already exists");
+        }
+        // The largest id is left to the projects shared between users
+        let id = self.users.keys().max().map_or(Some(1), |id| id.checked_add(1)).filter(|id| *id < u32::MAX);
+        let Some(id) = id else {
+            anyhow::bail!("There are no user ids left");
+        };
+        self.users.insert(id, User { id, name: name.to_owned(), token_hash: token_hash.to_owned() });
+        Ok(id)
+    }
+
+    // Rotating the token logs the old one out
+    pub fn set_token_hash(&mut self, id: u32, token_hash: &str) -> anyhow::Result<()> {
+        let Some(user) = self.users.get_mut(&id) else {
+            anyhow::bail!("User #{id} doesn't exist");
+        };
+        user.token_hash = token_hash.to_owned();
+        Ok(())
+    }
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod accounts_tests {
+    use super::*;
+
+    #[test]
+    fn test_add_users() {
+        let mut accounts = Accounts::default();
+        assert!(accounts.is_empty());
+
+        assert_eq!(accounts.add_user(" Ada ", "hash-a").unwrap(), 1);
+        assert_eq!(accounts.add_user("Grace", "hash-g").unwrap(), 2);
+        assert!(accounts.add_user("ADA", "hash-x").is_err());
+        assert!(accounts.add_user("  ", "hash-x").is_err());
+
+        assert_eq!(accounts.get_user(1).unwrap().name, "Ada");
+        assert_eq!(accounts.find_by_token_hash("hash-g").map(|user| user.id), Some(2));
+        assert_eq!(accounts.find_by_token_hash("hash-x"), None);
+    }
+
+    #[test]
+    fn test_user_ids_run_out() {
+        let mut accounts = Accounts::new(vec![User { id: u32::MAX - 1, name: "Ada".to_owned(), token_hash: "hash-a".to_owned() }]);
+        assert!(accounts.add_user("Grace", "hash-g").is_err());
+    }
+
+    #[test]
+    fn test_rotate_token() {
+        let mut accounts = Accounts::new(vec![User { id: 4, name: "Ada".to_owned(), token_hash: "old".to_owned() }]);
+
+        accounts.set_token_hash(4, "new").unwrap();
+        assert_eq!(accounts.find_by_token_hash("old"), None);
+        assert_eq!(accounts.find_by_token_hash("new").map(|user| user.id), Some(4));
+        assert!(accounts.set_token_hash(5, "new").is_err());
+
+        // The token hash never leaves the server
+        let raw_user = serde_json::to_string(accounts.get_user(4).unwrap()).unwrap();
+        assert!(!raw_user.contains("new"));
+    }
+}
+
+/* ========================================================================= */
@@ -0,0 +1,227 @@
+use chrono::{Duration, NaiveDate};
+use serde::{Serialize, Deserialize};
+use crate::core::tasks::Task;
+
+/* CALENDAR COLUMN ========================================================= */
+// Overview column spanning `days` consecutive days, right after the previous column
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct CalendarColumn {
+    pub label: String,
+    pub days: u32,
+}
+impl CalendarColumn {
+    pub fn new(label: &str, days: u32) -> Self {
+        CalendarColumn { label: label.to_owned(), days }
+    }
+}
+
+// Binds the columns, in chronological order, to consecutive date ranges starting on `start`.
+// Columns always span at least one day.
+pub fn bind_columns(columns: &[CalendarColumn], start: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut first_day = start;
+    columns.iter()
+        .map(|column| {
+            let last_day = first_day + Duration::days(i64::from(column.days.max(1)) - 1);
+            let range = (first_day, last_day);
+            first_day = last_day + Duration::days(1);
+            range
+        })
+        .collect()
+}
+
+/* DISTRIBUTION ============================================================ */
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    pub capacity: u32,
+    // Already taken, e.g. by completed or pinned tasks
+    pub used: u32,
+}
+impl CalendarDay {
+    pub fn new(date: NaiveDate, capacity: u32) -> Self {
+        CalendarDay { date, capacity, used: 0 }
+    }
+}
+
+// Fills the days in order with the ordered tasks, closing a day once the next task doesn't fit.
+// Days without capacity are skipped, while an empty day always takes the next task so big
+// tasks still get a day. Tasks that would land after their due date are pulled back to the
+// latest day before it with room (or overbooked, as the deadline wins), and whatever doesn't
+// fit in the days goes to the backlog.
+pub fn distribute<'a>(
+    tasks: &[&'a Task],
+    days: &[CalendarDay],
+    weight: impl Fn(&Task) -> u32
+) -> (Vec<Vec<&'a Task>>, Vec<&'a Task>) {
+    let mut assigned: Vec<Vec<&Task>> = vec![vec![]; days.len()];
+    let mut used: Vec<u32> = days.iter().map(|day| day.used).collect();
+    let mut backlog: Vec<&Task> = vec![];
+    let fits = |assigned: &[Vec<&Task>], used: &[u32], day_idx: usize, task_weight: u32| {
+        let day = &days[day_idx];
+        let is_empty = used[day_idx] == 0 && assigned[day_idx].is_empty();
+        day.capacity > 0 && (is_empty || used[day_idx].saturating_add(task_weight) <= day.capacity)
+    };
+
+    let mut cursor = 0;
+    for task in tasks.iter().copied() {
+        let task_weight = weight(task);
+        while cursor < days.len() && !fits(&assigned, &used, cursor, task_weight) {
+            cursor += 1;
+        }
+
+        // Overdue tasks count as due on the first day
+        let deadline_idx = task.due_date
+            .filter(|due_date| days.last().is_some_and(|day| *due_date <= day.date))
+            .map(|due_date| days.iter().rposition(|day| day.date <= due_date).unwrap_or_default());
+        let day_idx = match deadline_idx {
+            Some(deadline_idx) if cursor > deadline_idx => (0..=deadline_idx).rev()
+                .find(|day_idx| fits(&assigned, &used, *day_idx, task_weight))
+                .or_else(|| (0..=deadline_idx).rev().find(|day_idx| days[*day_idx].capacity > 0))
+                .or(Some(0)),
+            _ if cursor < days.len() => Some(cursor),
+            _ => None
+        };
+
+        match day_idx {
+            Some(day_idx) => {
+                used[day_idx] = used[day_idx].saturating_add(task_weight);
+                assigned[day_idx].push(task);
+            },
+            None => backlog.push(task)
+        }
+    }
+    (assigned, backlog)
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod calendar_tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        // 2024-08-05 is a Monday
+        NaiveDate::from_ymd_opt(2024, 8, day).unwrap()
+    }
+
+    fn week(capacities: &[u32]) -> Vec<CalendarDay> {
+        capacities.iter().enumerate()
+            .map(|(idx, capacity)| CalendarDay::new(date(5 + idx as u32), *capacity))
+            .collect()
+    }
+
+    fn ids(days: Vec<Vec<&Task>>) -> Vec<Vec<u32>> {
+        days.into_iter()
+            .map(|day| day.into_iter().map(|task| task.id).collect())
+            .collect()
+    }
+
+    fn tasks(count: u32) -> Vec<Task> {
+        (1..=count).map(Task::new_with_id).collect()
+    }
+
+    #[test]
+    fn test_bind_columns() {
+        let columns = vec![
+            CalendarColumn::new("Today", 1),
+            CalendarColumn::new("Tomorrow", 1),
+            CalendarColumn::new("This week", 4),
+            CalendarColumn::new("Broken", 0),
+        ];
+
+        assert_eq!(bind_columns(&columns, date(5)), vec![
+            (date(5), date(5)),
+            (date(6), date(6)),
+            (date(7), date(10)),
+            (date(11), date(11)),
+        ]);
+    }
+
+    #[test]
+    fn test_distribute_by_count() {
+        let tasks = tasks(5);
+        let tasks: Vec<&Task> = tasks.iter().collect();
+
+        let (days, backlog) = distribute(&tasks, &week(&[2, 2]), |_| 1);
+        assert_eq!(ids(days), vec![vec![1, 2], vec![3, 4]]);
+        assert_eq!(backlog.len(), 1);
+    }
+
+    #[test]
+    fn test_distribute_skips_days_without_capacity() {
+        let tasks = tasks(3);
+        let tasks: Vec<&Task> = tasks.iter().collect();
+
+        // Friday, then the weekend off, then Monday
+        let mut days = week(&[1, 0, 0, 5]);
+        days.iter_mut().for_each(|day| day.date += Duration::days(4));
+
+        let (days, backlog) = distribute(&tasks, &days, |_| 1);
+        assert_eq!(ids(days), vec![vec![1], vec![], vec![], vec![2, 3]]);
+        assert!(backlog.is_empty());
+    }
+
+    #[test]
+    fn test_distribute_respects_used_capacity() {
+        let tasks = tasks(3);
+        let tasks: Vec<&Task> = tasks.iter().collect();
+        let mut days = week(&[60, 60]);
+        days[0].used = 45;
+
+        // Big tasks still get a whole day to themselves
+        let (days, backlog) = distribute(&tasks, &days, |task| if task.id == 2 { 90 } else { 30 });
+        assert_eq!(ids(days), vec![vec![], vec![1]]);
+        assert_eq!(backlog.into_iter().map(|task| task.id).collect::<Vec<u32>>(), vec![2, 3]);
+
+        let tasks: Vec<&Task> = vec![tasks[1], tasks[0]];
+        let (days, _) = distribute(&tasks, &week(&[60, 60]), |task| if task.id == 2 { 90 } else { 30 });
+        assert_eq!(ids(days), vec![vec![2], vec![1]]);
+    }
+
+    #[test]
+    fn test_distribute_pulls_tasks_before_their_due_date() {
+        let mut tasks = tasks(4);
+        tasks[3].due_date = Some(date(6));
+        let tasks: Vec<&Task> = tasks.iter().collect();
+
+        // #4 would land on Wednesday, but it's due on Tuesday (already full, so it's overbooked)
+        let (days, _) = distribute(&tasks, &week(&[1, 1, 2]), |_| 1);
+        assert_eq!(ids(days), vec![vec![1], vec![2, 4], vec![3]]);
+    }
+
+    #[test]
+    fn test_distribute_pulls_tasks_into_the_room_left() {
+        let mut tasks = tasks(4);
+        tasks[3].due_date = Some(date(5));
+        let tasks: Vec<&Task> = tasks.iter().collect();
+        let weight = |task: &Task| if task.id == 2 { 45 } else { 30 };
+
+        // #2 closes Monday early, leaving just enough room for #4, which is due that day
+        let (days, _) = distribute(&tasks, &week(&[60, 60, 60]), weight);
+        assert_eq!(ids(days), vec![vec![1, 4], vec![2], vec![3]]);
+    }
+
+    #[test]
+    fn test_distribute_overdue_and_far_away_due_dates() {
+        let mut tasks = tasks(3);
+        tasks[1].due_date = Some(date(1));
+        tasks[2].due_date = Some(date(30));
+        let tasks: Vec<&Task> = tasks.iter().collect();
+
+        // Overdue tasks land on the first day, whatever its capacity
+        let (days, backlog) = distribute(&tasks, &week(&[1]), |_| 1);
+        assert_eq!(ids(days), vec![vec![1, 2]]);
+        assert_eq!(backlog.into_iter().map(|task| task.id).collect::<Vec<u32>>(), vec![3]);
+    }
+
+    #[test]
+    fn test_distribute_without_days() {
+        let tasks = tasks(2);
+        let tasks: Vec<&Task> = tasks.iter().collect();
+
+        let (days, backlog) = distribute(&tasks, &[], |_| 1);
+        assert!(days.is_empty());
+        assert_eq!(backlog.len(), 2);
+    }
+}
+
+/* ========================================================================= */
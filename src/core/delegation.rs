@@ -0,0 +1,49 @@
+use serde::{Serialize, Deserialize};
+use crate::core::tasks::Task;
+
+/* PERSON ================================================================== */
+// Someone tasks get delegated to. Assigned tasks that are still pending are
+// the ones we're waiting for.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Person {
+    pub id: u32,
+    pub name: String,
+}
+impl Person {
+    pub fn new(id: u32, name: &str) -> Self {
+        Person { id, name: name.trim().to_owned() }
+    }
+}
+
+/* WAITING FOR ============================================================= */
+// What a person owes us
+#[derive(Debug, Serialize)]
+pub struct WaitingFor<'a> {
+    pub person: &'a Person,
+    pub tasks: Vec<&'a Task>,
+}
+
+// Earliest follow-ups first, then the tasks without one
+pub fn sort_by_follow_up(tasks: &mut [&Task]) {
+    tasks.sort_by_key(|task| (task.follow_up.is_none(), task.follow_up, task.id));
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod delegation_tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_sort_by_follow_up() {
+        let mut tasks: Vec<Task> = (1..=3).map(Task::new_with_id).collect();
+        tasks[1].follow_up = NaiveDate::from_ymd_opt(2024, 8, 9);
+        tasks[2].follow_up = NaiveDate::from_ymd_opt(2024, 8, 5);
+        let mut tasks: Vec<&Task> = tasks.iter().collect();
+
+        sort_by_follow_up(&mut tasks);
+        assert_eq!(tasks.into_iter().map(|task| task.id).collect::<Vec<u32>>(), vec![3, 2, 1]);
+    }
+}
+
+/* ========================================================================= */
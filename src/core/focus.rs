@@ -0,0 +1,121 @@
+use chrono::{DateTime, Duration, Utc};
+use crate::core::tracking::TimeEntry;
+
+/* FOCUS SESSION =========================================================== */
+// Pomodoro-style session on a single task: a work interval followed by a break.
+// Only work intervals that run to the end are logged against the task.
+const FOCUS_SESSION_NOTE: &str = "Focus session";
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FocusPhase {
+    Work,
+    Break,
+    Finished
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FocusSession {
+    pub task_id: u32,
+    phase: FocusPhase,
+    phase_start: DateTime<Utc>,
+    work_length: Duration,
+    break_length: Duration,
+}
+impl FocusSession {
+    pub fn new(task_id: u32, work_length: Duration, break_length: Duration, now: DateTime<Utc>) -> Self {
+        FocusSession {
+            task_id,
+            phase: FocusPhase::Work,
+            phase_start: now,
+            work_length,
+            break_length
+        }
+    }
+
+    pub fn get_phase(&self) -> FocusPhase {
+        self.phase
+    }
+
+    fn get_phase_length(&self) -> Duration {
+        match self.phase {
+            FocusPhase::Work => self.work_length,
+            FocusPhase::Break => self.break_length,
+            FocusPhase::Finished => Duration::zero()
+        }
+    }
+
+    pub fn get_remaining(&self, now: DateTime<Utc>) -> Duration {
+        (self.phase_start + self.get_phase_length() - now).max(Duration::zero())
+    }
+
+    // Moves on to the next phase once the current one is over. Finishing the work
+    // interval returns its time entry, ending when the interval did rather than `now`.
+    pub fn advance(&mut self, now: DateTime<Utc>) -> Option<TimeEntry> {
+        if self.phase == FocusPhase::Finished || !self.get_remaining(now).is_zero() {
+            return None;
+        }
+        let phase_end = self.phase_start + self.get_phase_length();
+        let finished_phase = self.phase;
+        self.phase = match finished_phase {
+            FocusPhase::Work => FocusPhase::Break,
+            _ => FocusPhase::Finished
+        };
+        self.phase_start = phase_end;
+
+        match finished_phase {
+            FocusPhase::Work => {
+                let mut entry = TimeEntry::new(self.task_id, phase_end - self.work_length, FOCUS_SESSION_NOTE);
+                entry.stop = Some(phase_end);
+                Some(entry)
+            },
+            _ => None
+        }
+    }
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod focus_tests {
+    use super::*;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(minutes * 60, 0).unwrap()
+    }
+
+    #[test]
+    fn test_remaining_time() {
+        let session = FocusSession::new(1, Duration::minutes(25), Duration::minutes(5), at(0));
+
+        assert_eq!(session.get_remaining(at(10)), Duration::minutes(15));
+        assert_eq!(session.get_remaining(at(30)), Duration::zero());
+    }
+
+    #[test]
+    fn test_advance_logs_the_work_interval() {
+        let mut session = FocusSession::new(1, Duration::minutes(25), Duration::minutes(5), at(0));
+
+        assert_eq!(session.advance(at(10)), None);
+        assert_eq!(session.get_phase(), FocusPhase::Work);
+
+        // Noticing the end late doesn't stretch the logged interval
+        let entry = session.advance(at(27)).expect("Expected the work interval to be logged");
+        assert_eq!(entry.task_id, 1);
+        assert_eq!(entry.start, at(0));
+        assert_eq!(entry.stop, Some(at(25)));
+        assert_eq!(session.get_phase(), FocusPhase::Break);
+        assert_eq!(session.get_remaining(at(27)), Duration::minutes(3));
+    }
+
+    #[test]
+    fn test_advance_through_the_break() {
+        let mut session = FocusSession::new(1, Duration::minutes(25), Duration::minutes(5), at(0));
+
+        // One phase per call, so a long absence still logs the work interval first
+        assert!(session.advance(at(60)).is_some());
+        assert_eq!(session.advance(at(60)), None);
+        assert_eq!(session.get_phase(), FocusPhase::Finished);
+        assert_eq!(session.advance(at(90)), None);
+    }
+}
+
+/* ========================================================================= */
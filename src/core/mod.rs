@@ -1 +1,13 @@
 pub mod tasks;
+pub mod query;
+pub mod search;
+pub mod tracking;
+pub mod focus;
+pub mod planning;
+pub mod calendar;
+pub mod templates;
+pub mod quick_add;
+pub mod delegation;
+pub mod accounts;
+pub mod sharing;
+pub mod sync;
@@ -0,0 +1,336 @@
+use std::collections::{HashMap, HashSet};
+use chrono::{Datelike, NaiveDate};
+use serde::{Serialize, Deserialize};
+use crate::core::calendar::{CalendarColumn, CalendarDay, bind_columns, distribute};
+use crate::core::tasks::{Placement, Task};
+
+/* DAILY PLAN ============================================================== */
+// What the user committed to for the day, in order. It's kept as is until they
+// explicitly re-plan, so rearranging tasks doesn't reshuffle the day.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct DailyPlan {
+    pub date: NaiveDate,
+    pub task_ids: Vec<u32>,
+}
+impl DailyPlan {
+    pub fn new(date: NaiveDate, task_ids: Vec<u32>) -> Self {
+        DailyPlan { date, task_ids }
+    }
+
+    pub fn contains(&self, id: u32) -> bool {
+        self.task_ids.contains(&id)
+    }
+}
+
+/* PLANNING SETTINGS ======================================================= */
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct PlanningSettings {
+    // In chronological order, starting today
+    pub columns: Vec<CalendarColumn>,
+    // Tasks per day, or effort per day when filling by budget
+    pub daily_capacity: u32,
+    // Percentage of the daily capacity, from Monday to Sunday
+    pub weekday_capacity: [u32; 7],
+    // Every task counts as one unless days are filled by effort, with this default estimate
+    pub budget_default_estimate: Option<u32>,
+}
+impl Default for PlanningSettings {
+    fn default() -> Self {
+        PlanningSettings {
+            columns: vec![
+                CalendarColumn::new("Today", 1),
+                CalendarColumn::new("Tomorrow", 1),
+            ],
+            daily_capacity: 5,
+            weekday_capacity: [100, 100, 100, 100, 100, 0, 0],
+            budget_default_estimate: None
+        }
+    }
+}
+impl PlanningSettings {
+    // How much of a day the task takes up
+    pub fn get_task_weight(&self, task: &Task) -> u32 {
+        match self.budget_default_estimate {
+            Some(default_estimate) => task.get_effort(default_estimate),
+            None => 1
+        }
+    }
+
+    pub fn get_day_capacity(&self, date: NaiveDate) -> u32 {
+        let percentage = self.weekday_capacity[date.weekday().num_days_from_monday() as usize];
+        self.daily_capacity.saturating_mul(percentage) / 100
+    }
+}
+
+/* OVERVIEW ================================================================ */
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct OverviewColumn<'a> {
+    pub label: String,
+    pub first_day: NaiveDate,
+    pub last_day: NaiveDate,
+    pub tasks: Vec<&'a Task>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Overview<'a> {
+    // In chronological order, starting today
+    pub columns: Vec<OverviewColumn<'a>>,
+    pub backlog: Vec<&'a Task>,
+    // Completed today, so they're shown along with today's tasks
+    pub completed: Vec<&'a Task>,
+}
+
+// Today's share of the ordered tasks, after what's been completed already
+pub fn pick_today(tasks: &[&Task], settings: &PlanningSettings, today: NaiveDate, today_used: u32) -> Vec<u32> {
+    let day = CalendarDay { date: today, capacity: settings.get_day_capacity(today), used: today_used };
+    let (days, _) = distribute(tasks, &[day], |task| settings.get_task_weight(task));
+    days.into_iter()
+        .flatten()
+        .map(|task| task.id)
+        .collect()
+}
+
+// Splits the ordered tasks into the columns. Today's plan, when there is one, is kept as today
+// and pinned tasks stay in their column; the rest fills the room left, overflowing into the
+// backlog. Tasks missing from `tasks` (e.g. filtered out) are left out of the plan as well.
+pub fn plan_overview<'a>(
+    tasks: &[&'a Task],
+    completed_ids: &HashSet<u32>,
+    plan: Option<&DailyPlan>,
+    settings: &PlanningSettings,
+    today: NaiveDate
+) -> Overview<'a> {
+    let plan = plan.filter(|plan| plan.date == today);
+    let ranges = bind_columns(&settings.columns, today);
+    let weight = |task: &Task| settings.get_task_weight(task);
+    // Overdue days land in the first column and the ones past the last column in the backlog
+    let get_column_idx = |date: NaiveDate| ranges.iter().position(|(_, last_day)| date <= *last_day);
+
+    let mut columns: Vec<OverviewColumn> = settings.columns.iter()
+        .zip(ranges.iter())
+        .map(|(column, (first_day, last_day))| OverviewColumn {
+            label: column.label.clone(),
+            first_day: *first_day,
+            last_day: *last_day,
+            tasks: vec![]
+        })
+        .collect();
+    let mut backlog: Vec<&Task> = vec![];
+    let mut completed: Vec<&Task> = vec![];
+
+    // Planned tasks go first, unless they've been pinned somewhere else since
+    let is_planned = |task: &Task| plan.is_some_and(|plan| plan.contains(task.id)) && task.placement.is_none();
+    if let Some(plan) = plan {
+        let tasks_by_id: HashMap<u32, &Task> = tasks.iter().map(|task| (task.id, *task)).collect();
+        let planned_tasks = plan.task_ids.iter()
+            .filter_map(|id| tasks_by_id.get(id).copied())
+            .filter(|task| is_planned(task));
+        match columns.first_mut() {
+            Some(column) => column.tasks.extend(planned_tasks),
+            None => backlog.extend(planned_tasks)
+        }
+    }
+
+    let mut unplaced_tasks: Vec<&Task> = vec![];
+    let mut pinned_tasks: Vec<(NaiveDate, &Task)> = vec![];
+    for task in tasks.iter().copied() {
+        match task.placement {
+            _ if is_planned(task) => {},
+            Some(Placement::Day(date)) => pinned_tasks.push((date.max(today), task)),
+            Some(Placement::Backlog) => backlog.push(task),
+            None if completed_ids.contains(&task.id) => completed.push(task),
+            None => unplaced_tasks.push(task)
+        }
+    }
+
+    // Pinned and completed tasks take up room in their days, today is taken when it's planned
+    let mut days: Vec<CalendarDay> = match ranges.last() {
+        Some((_, last_day)) => today.iter_days()
+            .take_while(|date| date <= last_day)
+            .skip(usize::from(plan.is_some()))
+            .map(|date| CalendarDay::new(date, settings.get_day_capacity(date)))
+            .collect(),
+        None => vec![]
+    };
+    for (date, task) in pinned_tasks {
+        if let Some(day) = days.iter_mut().find(|day| day.date == date) {
+            day.used = day.used.saturating_add(weight(task));
+        }
+        match get_column_idx(date) {
+            Some(column_idx) => columns[column_idx].tasks.push(task),
+            None => backlog.push(task)
+        }
+    }
+    if let Some(day) = days.first_mut().filter(|day| day.date == today) {
+        day.used = completed.iter().fold(day.used, |used, task| used.saturating_add(weight(task)));
+    }
+
+    let (filled_days, overflow) = distribute(&unplaced_tasks, &days, weight);
+    for (day, day_tasks) in days.iter().zip(filled_days) {
+        if let Some(column_idx) = get_column_idx(day.date) {
+            columns[column_idx].tasks.extend(day_tasks);
+        }
+    }
+    backlog.extend(overflow);
+
+    Overview { columns, backlog, completed }
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod planning_tests {
+    use super::*;
+
+    fn monday() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 8, 5).unwrap()
+    }
+
+    fn ids(tasks: &[&Task]) -> Vec<u32> {
+        tasks.iter().map(|task| task.id).collect()
+    }
+
+    fn column_ids(overview: &Overview) -> Vec<Vec<u32>> {
+        overview.columns.iter().map(|column| ids(&column.tasks)).collect()
+    }
+
+    fn settings(column_days: &[u32], daily_capacity: u32) -> PlanningSettings {
+        PlanningSettings {
+            columns: column_days.iter().map(|days| CalendarColumn::new("", *days)).collect(),
+            daily_capacity,
+            weekday_capacity: [100; 7],
+            budget_default_estimate: None
+        }
+    }
+
+    // Tiny deterministic generator, so the property tests don't need an extra crate
+    struct Lcg(u64);
+    impl Lcg {
+        fn next(&mut self, bound: u32) -> u32 {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((self.0 >> 33) % u64::from(bound.max(1))) as u32
+        }
+    }
+
+    #[test]
+    fn test_plan_overview_by_count() {
+        let tasks: Vec<Task> = (1..=6).map(Task::new_with_id).collect();
+        let tasks: Vec<&Task> = tasks.iter().collect();
+
+        let overview = plan_overview(&tasks, &HashSet::new(), None, &settings(&[1, 1], 2), monday());
+        assert_eq!(column_ids(&overview), vec![vec![1, 2], vec![3, 4]]);
+        assert_eq!(ids(&overview.backlog), vec![5, 6]);
+        assert_eq!(overview.columns[1].first_day, monday().succ_opt().unwrap());
+    }
+
+    #[test]
+    fn test_plan_overview_without_columns() {
+        let tasks: Vec<Task> = (1..=3).map(Task::new_with_id).collect();
+        let mut tasks: Vec<&Task> = tasks.iter().collect();
+        let mut pinned_task = Task::new_with_id(4);
+        pinned_task.placement = Some(Placement::Day(monday()));
+        tasks.push(&pinned_task);
+        let plan = DailyPlan::new(monday(), vec![2]);
+
+        let overview = plan_overview(&tasks, &HashSet::from([3]), Some(&plan), &settings(&[], 2), monday());
+        assert!(overview.columns.is_empty());
+        assert_eq!(ids(&overview.backlog), vec![2, 4, 1]);
+        assert_eq!(ids(&overview.completed), vec![3]);
+    }
+
+    #[test]
+    fn test_plan_overview_with_more_completions_than_target() {
+        let tasks: Vec<Task> = (1..=6).map(Task::new_with_id).collect();
+        let tasks: Vec<&Task> = tasks.iter().collect();
+        let completed_ids = HashSet::from([1, 2, 3]);
+
+        let overview = plan_overview(&tasks, &completed_ids, None, &settings(&[1, 1], 2), monday());
+        assert_eq!(column_ids(&overview), vec![vec![], vec![4, 5]]);
+        assert_eq!(ids(&overview.completed), vec![1, 2, 3]);
+        assert_eq!(ids(&overview.backlog), vec![6]);
+    }
+
+    #[test]
+    fn test_plan_overview_keeps_the_daily_plan() {
+        let tasks: Vec<Task> = (1..=5).map(Task::new_with_id).collect();
+        let mut tasks: Vec<&Task> = tasks.iter().collect();
+        let mut pinned_task = Task::new_with_id(6);
+        pinned_task.placement = Some(Placement::Backlog);
+        tasks.push(&pinned_task);
+
+        // Stale plans are ignored, and planned tasks pinned elsewhere leave today
+        let plan = DailyPlan::new(monday(), vec![5, 6, 99]);
+        let overview = plan_overview(&tasks, &HashSet::new(), Some(&plan), &settings(&[1, 2], 2), monday());
+        assert_eq!(column_ids(&overview), vec![vec![5], vec![1, 2, 3, 4]]);
+        assert_eq!(ids(&overview.backlog), vec![6]);
+
+        let stale_plan = DailyPlan::new(monday().pred_opt().unwrap(), vec![5]);
+        let overview = plan_overview(&tasks, &HashSet::new(), Some(&stale_plan), &settings(&[1, 2], 2), monday());
+        assert_eq!(column_ids(&overview), vec![vec![1, 2], vec![3, 4, 5]]);
+    }
+
+    #[test]
+    fn test_pick_today() {
+        let tasks: Vec<Task> = (1..=4).map(Task::new_with_id).collect();
+        let tasks: Vec<&Task> = tasks.iter().collect();
+        let saturday = monday() + chrono::Duration::days(5);
+
+        assert_eq!(pick_today(&tasks, &settings(&[1], 3), monday(), 1), vec![1, 2]);
+        assert_eq!(pick_today(&tasks, &PlanningSettings::default(), saturday, 0), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_plan_overview_properties() {
+        let mut rng = Lcg(42);
+        for _ in 0..200 {
+            let num_tasks = rng.next(12);
+            let column_days: Vec<u32> = (0..rng.next(4)).map(|_| rng.next(3)).collect();
+            let mut settings = settings(&column_days, rng.next(4));
+            settings.weekday_capacity = [100, 100, 100, 100, 100, rng.next(2) * 100, 0];
+
+            let today = monday() + chrono::Duration::days(i64::from(rng.next(7)));
+            let tasks: Vec<Task> = (1..=num_tasks)
+                .map(|id| {
+                    let mut task = Task::new_with_id(id);
+                    task.due_date = match rng.next(4) {
+                        0 => Some(today + chrono::Duration::days(i64::from(rng.next(10)) - 3)),
+                        _ => None
+                    };
+                    task.placement = match rng.next(6) {
+                        0 => Some(Placement::Backlog),
+                        1 => Some(Placement::Day(today + chrono::Duration::days(i64::from(rng.next(6)) - 1))),
+                        _ => None
+                    };
+                    task
+                })
+                .collect();
+            let tasks: Vec<&Task> = tasks.iter().collect();
+            let completed_ids: HashSet<u32> = (1..=num_tasks).filter(|_| rng.next(3) == 0).collect();
+            let plan = DailyPlan::new(today, (1..=num_tasks).filter(|_| rng.next(3) == 0).collect());
+
+            let overview = plan_overview(&tasks, &completed_ids, Some(&plan), &settings, today);
+
+            // Every task shows up exactly once
+            let mut seen: Vec<u32> = overview.columns.iter()
+                .flat_map(|column| ids(&column.tasks))
+                .chain(ids(&overview.backlog))
+                .chain(ids(&overview.completed))
+                .collect();
+            seen.sort();
+            assert_eq!(seen, (1..=num_tasks).collect::<Vec<u32>>());
+
+            // Columns are consecutive and start today
+            assert_eq!(overview.columns.len(), settings.columns.len());
+            let mut next_day = today;
+            for column in overview.columns.iter() {
+                assert_eq!(column.first_day, next_day);
+                assert!(column.first_day <= column.last_day);
+                next_day = column.last_day.succ_opt().unwrap();
+            }
+
+            // Completed tasks are only the unplaced ones
+            assert!(overview.completed.iter().all(|task| completed_ids.contains(&task.id) && task.placement.is_none()));
+        }
+    }
+}
+
+/* ========================================================================= */
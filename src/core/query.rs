@@ -0,0 +1,316 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+use crate::core::tasks::{Task, TaskStatus, Quadrant, QuadrantThresholds};
+
+/* QUERY =================================================================== */
+// Small selection language over the task tree, e.g.
+//
+//     status:open tag:work complexity>3 quadrant:do-first under:#42
+//
+// Every term must match (AND). A leading `-` negates a term and bare words
+// are matched against the task description. Snoozed tasks are left out unless
+// the query asks for them with `deferred:true`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+impl Comparison {
+    fn holds(&self, lhs: u32, rhs: u32) -> bool {
+        match self {
+            Comparison::Less => lhs < rhs,
+            Comparison::LessOrEqual => lhs <= rhs,
+            Comparison::Equal => lhs == rhs,
+            Comparison::GreaterOrEqual => lhs >= rhs,
+            Comparison::Greater => lhs > rhs,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Term {
+    Status(TaskStatus),
+    Tag(String),
+    Complexity(Comparison, u32),
+    // Only counting estimated tasks
+    Effort(Comparison, u32),
+    Quadrant(Quadrant),
+    Under(u32),
+    Deferred(bool),
+    Text(String),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct Filter {
+    negated: bool,
+    term: Term,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Query {
+    filters: Vec<Filter>,
+}
+impl Query {
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    fn parse_term(raw: &str) -> anyhow::Result<Term> {
+        let Some(split_at) = raw.find([':', '<', '>', '=']) else {
+            return Ok(Term::Text(raw.to_lowercase()));
+        };
+        let (field, rest) = raw.split_at(split_at);
+
+        if let Some(value) = rest.strip_prefix(':') {
+            return match field {
+                "status" => Ok(Term::Status(value.parse()?)),
+                "tag" => Ok(Term::Tag(value.trim_start_matches('#').to_lowercase())),
+                "complexity" => Ok(Term::Complexity(Comparison::Equal, value.parse()?)),
+                "effort" => Ok(Term::Effort(Comparison::Equal, value.parse()?)),
+                "quadrant" => Ok(Term::Quadrant(value.parse()?)),
+                "under" => Ok(Term::Under(value.trim_start_matches('#').parse()?)),
+                "deferred" => Ok(Term::Deferred(value.parse()?)),
+                _ => anyhow::bail!("Unknown query field `{field}`")
+            };
+        }
+
+        // Comparisons (only complexity and effort support them for now)
+        if field != "complexity" && field != "effort" {
+            anyhow::bail!("Field `{field}` can't be compared");
+        }
+        let (comparison, value) = [
+            (">=", Comparison::GreaterOrEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">", Comparison::Greater),
+            ("<", Comparison::Less),
+            ("=", Comparison::Equal),
+        ]
+            .into_iter()
+            .find_map(|(symbol, comparison)| rest.strip_prefix(symbol).map(|value| (comparison, value)))
+            .ok_or_else(|| anyhow::anyhow!("Invalid comparison `{rest}`"))?;
+        let value = value.parse()?;
+        match field {
+            "effort" => Ok(Term::Effort(comparison, value)),
+            _ => Ok(Term::Complexity(comparison, value))
+        }
+    }
+
+    fn matches(&self, task: &Task, descendants: &[(u32, HashSet<u32>)], thresholds: &QuadrantThresholds) -> bool {
+        self.filters.iter().all(|filter| {
+            let is_match = match &filter.term {
+                Term::Status(status) => task.status == *status,
+                Term::Tag(tag) => task.tags.contains(tag),
+                Term::Complexity(comparison, value) => comparison.holds(task.get_complexity(), *value),
+                Term::Effort(comparison, value) => comparison.holds(task.get_effort(0), *value),
+                Term::Quadrant(quadrant) => task.get_quadrant_with(thresholds) == *quadrant,
+                Term::Under(id) => descendants.iter()
+                    .any(|(ancestor_id, ids)| ancestor_id == id && ids.contains(&task.id)),
+                Term::Deferred(deferred) => task.is_deferred() == *deferred,
+                Term::Text(text) => task.desc.to_lowercase().contains(text),
+            };
+            is_match != filter.negated
+        })
+    }
+
+    pub fn select<'a>(&self, root: &'a Task) -> Vec<&'a Task> {
+        self.select_with(root, &QuadrantThresholds::default())
+    }
+
+    pub fn select_with<'a>(&self, root: &'a Task, thresholds: &QuadrantThresholds) -> Vec<&'a Task> {
+        // Resolving the ancestry terms once, instead of walking up the tree for each task
+        let descendants: Vec<(u32, HashSet<u32>)> = self.filters.iter()
+            .filter_map(|filter| match filter.term {
+                Term::Under(id) => Some(id),
+                _ => None
+            })
+            .map(|id| {
+                let ids = root.get_subtask(id)
+                    .map(|ancestor| ancestor.get_all_subtasks().into_iter().map(|task| task.id).collect())
+                    .unwrap_or_default();
+                (id, ids)
+            })
+            .collect();
+
+        let include_deferred = self.filters.iter().any(|filter| matches!(filter.term, Term::Deferred(_)));
+
+        root.get_all_subtasks()
+            .into_iter()
+            .filter(|task| include_deferred || !task.is_deferred())
+            .filter(|task| self.matches(task, &descendants, thresholds))
+            .collect()
+    }
+}
+impl FromStr for Query {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let filters = raw.split_whitespace()
+            .map(|raw_term| {
+                let (negated, raw_term) = match raw_term.strip_prefix('-') {
+                    Some(stripped) if !stripped.is_empty() => (true, stripped),
+                    _ => (false, raw_term)
+                };
+                Ok(Filter { negated, term: Query::parse_term(raw_term)? })
+            })
+            .collect::<anyhow::Result<Vec<Filter>>>()?;
+        Ok(Query { filters })
+    }
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod query_tests {
+    use super::*;
+
+    fn ids(tasks: Vec<&Task>) -> Vec<u32> {
+        let mut ids: Vec<u32> = tasks.into_iter().map(|task| task.id).collect();
+        ids.sort();
+        ids
+    }
+
+    fn sample_tree() -> Task {
+        /*
+         *           (r)
+         *          /   \
+         *       (tA)   (tB)
+         *             /   \
+         *           (sA) (sB)
+         */
+        let mut root = Task::default();
+        let mut task_a = Box::new(Task::new_with_id(1));
+        task_a.desc = "Write quarterly report".to_owned();
+        task_a.importance = 10.0;
+        task_a.urgency = 10.0;
+        task_a.add_tag("work");
+
+        let mut task_b = Box::new(Task::new_with_id(2));
+        task_b.desc = "Clean the garage".to_owned();
+        task_b.importance = -10.0;
+        task_b.urgency = -10.0;
+        task_b.add_tag("home");
+
+        let mut subtask_a = Box::new(Task::new_with_id(3));
+        subtask_a.add_tag("home");
+        let mut subtask_b = Box::new(Task::new_with_id(4));
+        subtask_b.status = TaskStatus::Done;
+
+        task_b.add_subtask(subtask_a);
+        task_b.add_subtask(subtask_b);
+        root.add_subtask(task_a);
+        root.add_subtask(task_b);
+        root
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let query: Query = "status:open -tag:#Work complexity>=2 quadrant:do-first under:#42 report"
+            .parse()
+            .expect("Expected a valid query");
+        let terms: Vec<(bool, Term)> = query.filters.into_iter()
+            .map(|filter| (filter.negated, filter.term))
+            .collect();
+
+        assert_eq!(terms, vec![
+            (false, Term::Status(TaskStatus::Open)),
+            (true, Term::Tag("work".to_owned())),
+            (false, Term::Complexity(Comparison::GreaterOrEqual, 2)),
+            (false, Term::Quadrant(Quadrant::Do)),
+            (false, Term::Under(42)),
+            (false, Term::Text("report".to_owned())),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_invalid_query() {
+        assert!("status:maybe".parse::<Query>().is_err());
+        assert!("owner:me".parse::<Query>().is_err());
+        assert!("tag>3".parse::<Query>().is_err());
+        assert!("complexity>many".parse::<Query>().is_err());
+    }
+
+    #[test]
+    fn test_empty_query_selects_everything() {
+        let root = sample_tree();
+        let query: Query = "".parse().unwrap();
+
+        assert!(query.is_empty());
+        assert_eq!(ids(query.select(&root)), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_select_by_status_and_tag() {
+        let root = sample_tree();
+
+        let query: Query = "status:open tag:home".parse().unwrap();
+        assert_eq!(ids(query.select(&root)), vec![2, 3]);
+
+        let query: Query = "-status:open".parse().unwrap();
+        assert_eq!(ids(query.select(&root)), vec![4]);
+    }
+
+    #[test]
+    fn test_select_by_complexity_and_quadrant() {
+        let root = sample_tree();
+
+        let query: Query = "complexity>1".parse().unwrap();
+        assert_eq!(ids(query.select(&root)), vec![2]);
+
+        let query: Query = "quadrant:eliminate".parse().unwrap();
+        assert_eq!(ids(query.select(&root)), vec![2]);
+    }
+
+    #[test]
+    fn test_select_by_effort() {
+        let mut root = sample_tree();
+        let mut estimated_task = Box::new(Task::new_with_id(5));
+        estimated_task.estimate = Some(45);
+        root.add_subtask(estimated_task);
+
+        let query: Query = "effort>=30".parse().unwrap();
+        assert_eq!(ids(query.select(&root)), vec![5]);
+
+        let query: Query = "effort:0".parse().unwrap();
+        assert_eq!(ids(query.select(&root)), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_select_under_ancestor() {
+        let root = sample_tree();
+
+        let query: Query = "under:#2".parse().unwrap();
+        assert_eq!(ids(query.select(&root)), vec![3, 4]);
+
+        let query: Query = "under:99".parse().unwrap();
+        assert!(query.select(&root).is_empty());
+    }
+
+    #[test]
+    fn test_select_deferred() {
+        let mut root = sample_tree();
+        let mut snoozed_task = Box::new(Task::new_with_id(5));
+        snoozed_task.deferred_until = chrono::NaiveDate::from_ymd_opt(2024, 8, 1);
+        root.add_subtask(snoozed_task);
+
+        let query: Query = "".parse().unwrap();
+        assert_eq!(ids(query.select(&root)), vec![1, 2, 3, 4]);
+
+        let query: Query = "deferred:true".parse().unwrap();
+        assert_eq!(ids(query.select(&root)), vec![5]);
+
+        let query: Query = "-deferred:false".parse().unwrap();
+        assert_eq!(ids(query.select(&root)), vec![5]);
+    }
+
+    #[test]
+    fn test_select_by_text() {
+        let root = sample_tree();
+
+        let query: Query = "REPORT".parse().unwrap();
+        assert_eq!(ids(query.select(&root)), vec![1]);
+    }
+}
+
+/* ========================================================================= */
@@ -0,0 +1,146 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use crate::core::tasks::Task;
+
+/* QUICK ADD =============================================================== */
+// One-line task entry, e.g.
+//
+//     Write report !high ~urgent #work due friday under:42
+//
+// `!` sets the importance and `~` the urgency, either with a level (high,
+// medium, low, none) or a number between -100 and 100. `#` adds tags, `due`
+// (or `due:`) takes today, tomorrow, a weekday or an ISO date, and `under:`
+// nests the task. Everything else makes up the description.
+const MAX_LEVEL: f32 = 100.0;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct QuickAdd {
+    // Without an id yet, see `Oswald::add_quick_task`
+    pub task: Task,
+    pub parent: Option<u32>,
+}
+impl QuickAdd {
+    fn parse_level(raw: &str) -> anyhow::Result<f32> {
+        let level = match raw.to_lowercase().as_str() {
+            "high" | "urgent" => 75.0,
+            "medium" | "med" | "soon" => 25.0,
+            "low" | "later" => -25.0,
+            "none" | "someday" => -75.0,
+            number => number.parse().map_err(|_| anyhow::anyhow!("Unknown level `{raw}`"))?
+        };
+        if !(-MAX_LEVEL..=MAX_LEVEL).contains(&level) {
+            anyhow::bail!("Level `{raw}` is out of range");
+        }
+        Ok(level)
+    }
+
+    // Weekdays are the next ones to come, today included
+    fn parse_date(raw: &str, today: NaiveDate) -> Option<NaiveDate> {
+        match raw.to_lowercase().as_str() {
+            "today" => Some(today),
+            "tomorrow" => Some(today + Duration::days(1)),
+            date => match date.parse::<Weekday>() {
+                Ok(weekday) => {
+                    let days_ahead = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+                    Some(today + Duration::days(i64::from(days_ahead)))
+                },
+                Err(_) => date.parse().ok()
+            }
+        }
+    }
+
+    pub fn parse(raw: &str, today: NaiveDate) -> anyhow::Result<Self> {
+        let mut task = Task::default();
+        let mut parent = None;
+        let mut desc: Vec<&str> = vec![];
+
+        let mut words = raw.split_whitespace().peekable();
+        while let Some(word) = words.next() {
+            if let Some(level) = word.strip_prefix('!').filter(|level| !level.is_empty()) {
+                task.importance = QuickAdd::parse_level(level)?;
+            } else if let Some(level) = word.strip_prefix('~').filter(|level| !level.is_empty()) {
+                task.urgency = QuickAdd::parse_level(level)?;
+            } else if word.len() > 1 && word.starts_with('#') {
+                task.add_tag(word);
+            } else if let Some(id) = word.strip_prefix("under:") {
+                let id = id.trim_start_matches('#');
+                parent = Some(id.parse().map_err(|_| anyhow::anyhow!("Invalid parent `{id}`"))?);
+            } else if let Some(date) = word.strip_prefix("due:") {
+                task.due_date = Some(QuickAdd::parse_date(date, today)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid due date `{date}`"))?);
+            } else if word.eq_ignore_ascii_case("due") {
+                // A plain "due" stays in the description unless a date follows it
+                match words.peek().and_then(|date| QuickAdd::parse_date(date, today)) {
+                    Some(date) => {
+                        task.due_date = Some(date);
+                        words.next();
+                    },
+                    None => desc.push(word)
+                }
+            } else {
+                desc.push(word);
+            }
+        }
+
+        if desc.is_empty() {
+            anyhow::bail!("The task needs a description");
+        }
+        task.desc = desc.join(" ");
+        Ok(QuickAdd { task, parent })
+    }
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod quick_add_tests {
+    use super::*;
+
+    fn monday() -> NaiveDate {
+        // 2024-08-05 is a Monday
+        NaiveDate::from_ymd_opt(2024, 8, 5).unwrap()
+    }
+
+    #[test]
+    fn test_parse_quick_add() {
+        let quick_add = QuickAdd::parse("Write report !high ~urgent #work due friday under:42", monday()).unwrap();
+        assert_eq!(quick_add.parent, Some(42));
+        assert_eq!(quick_add.task.desc, "Write report");
+        assert_eq!(quick_add.task.importance, 75.0);
+        assert_eq!(quick_add.task.urgency, 75.0);
+        assert_eq!(quick_add.task.tags.iter().collect::<Vec<&String>>(), vec!["work"]);
+        assert_eq!(quick_add.task.due_date, NaiveDate::from_ymd_opt(2024, 8, 9));
+    }
+
+    #[test]
+    fn test_parse_quick_add_levels_and_dates() {
+        let quick_add = QuickAdd::parse("Call mom !-40 ~low due:2024-09-01", monday()).unwrap();
+        assert_eq!(quick_add.task.importance, -40.0);
+        assert_eq!(quick_add.task.urgency, -25.0);
+        assert_eq!(quick_add.task.due_date, NaiveDate::from_ymd_opt(2024, 9, 1));
+        assert_eq!(quick_add.parent, None);
+
+        // Weekdays never land in the past
+        assert_eq!(QuickAdd::parse_date("mon", monday()), Some(monday()));
+        assert_eq!(QuickAdd::parse_date("Sunday", monday()), NaiveDate::from_ymd_opt(2024, 8, 11));
+        assert_eq!(QuickAdd::parse_date("tomorrow", monday()), NaiveDate::from_ymd_opt(2024, 8, 6));
+    }
+
+    #[test]
+    fn test_parse_quick_add_keeps_plain_words() {
+        // Lone markers and a "due" without a date are part of the description
+        let quick_add = QuickAdd::parse("Find what's due ! # soon", monday()).unwrap();
+        assert_eq!(quick_add.task.desc, "Find what's due ! # soon");
+        assert_eq!(quick_add.task.due_date, None);
+        assert!(quick_add.task.tags.is_empty());
+    }
+
+    #[test]
+    fn test_parse_quick_add_errors() {
+        assert!(QuickAdd::parse("!high #work", monday()).is_err());
+        assert!(QuickAdd::parse("Report !huge", monday()).is_err());
+        assert!(QuickAdd::parse("Report ~500", monday()).is_err());
+        assert!(QuickAdd::parse("Report under:abc", monday()).is_err());
+        assert!(QuickAdd::parse("Report due:someday", monday()).is_err());
+    }
+}
+
+/* ========================================================================= */
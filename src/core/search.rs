@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+
+/* SEARCH INDEX ============================================================ */
+// In-memory inverted index over the task descriptions, kept up to date by Oswald
+const EXACT_MATCH_WEIGHT: f32 = 1.0;
+const PREFIX_MATCH_WEIGHT: f32 = 0.75;
+const FUZZY_MATCH_WEIGHT: f32 = 0.5;
+const MIN_FUZZY_TOKEN_LEN: usize = 4;
+const LONG_FUZZY_TOKEN_LEN: usize = 8;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub id: u32,
+    pub score: f32,
+    // Ancestor ids, from the top-level task down to the direct parent
+    pub path: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    // token -> (task id -> term frequency)
+    postings: HashMap<String, HashMap<u32, u32>>,
+    // task id -> indexed tokens, so documents can be dropped without scanning every posting
+    documents: HashMap<u32, Vec<String>>,
+}
+impl SearchIndex {
+    pub fn index(&mut self, id: u32, text: &str) {
+        self.remove(id);
+        let tokens = tokenize(text);
+        for token in tokens.iter() {
+            *self.postings
+                .entry(token.clone())
+                .or_default()
+                .entry(id)
+                .or_default() += 1;
+        }
+        self.documents.insert(id, tokens);
+    }
+
+    pub fn remove(&mut self, id: u32) {
+        let Some(tokens) = self.documents.remove(&id) else {
+            return;
+        };
+        for token in tokens {
+            if let Some(posting) = self.postings.get_mut(&token) {
+                posting.remove(&id);
+                if posting.is_empty() {
+                    self.postings.remove(&token);
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.postings.clear();
+        self.documents.clear();
+    }
+
+    fn match_weight(query_token: &str, token: &str) -> Option<f32> {
+        if query_token == token {
+            return Some(EXACT_MATCH_WEIGHT);
+        }
+        if token.starts_with(query_token) {
+            return Some(PREFIX_MATCH_WEIGHT);
+        }
+        let query_len = query_token.chars().count();
+        if query_len < MIN_FUZZY_TOKEN_LEN {
+            return None;
+        }
+        let max_distance = if query_len >= LONG_FUZZY_TOKEN_LEN { 2 } else { 1 };
+        if edit_distance(query_token, token) <= max_distance {
+            return Some(FUZZY_MATCH_WEIGHT);
+        }
+        None
+    }
+
+    // Ranked (id, score) pairs, best match first
+    pub fn search(&self, text: &str) -> Vec<(u32, f32)> {
+        let total_documents = self.documents.len() as f32;
+        let mut scores: HashMap<u32, f32> = HashMap::new();
+
+        for query_token in tokenize(text) {
+            // Only the best matching token of each document counts towards a query token
+            let mut token_scores: HashMap<u32, f32> = HashMap::new();
+            for (token, posting) in self.postings.iter() {
+                let Some(weight) = SearchIndex::match_weight(&query_token, token) else {
+                    continue;
+                };
+                let idf = (1.0 + total_documents / posting.len() as f32).ln();
+                for (id, frequency) in posting.iter() {
+                    let score = weight * idf * (1.0 + (*frequency as f32).ln());
+                    let best = token_scores.entry(*id).or_default();
+                    *best = best.max(score);
+                }
+            }
+            for (id, score) in token_scores {
+                *scores.entry(id).or_default() += score;
+            }
+        }
+
+        let mut results: Vec<(u32, f32)> = scores.into_iter().collect();
+        results.sort_by(|(id, score), (other_id, other_score)| {
+            other_score.total_cmp(score).then(id.cmp(other_id))
+        });
+        results
+    }
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+fn edit_distance(lhs: &str, rhs: &str) -> usize {
+    let rhs: Vec<char> = rhs.chars().collect();
+    let mut previous: Vec<usize> = (0..=rhs.len()).collect();
+    for (i, lhs_char) in lhs.chars().enumerate() {
+        let mut current = vec![i + 1; rhs.len() + 1];
+        for (j, rhs_char) in rhs.iter().enumerate() {
+            let substitution = previous[j] + usize::from(lhs_char != *rhs_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[rhs.len()]
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod search_tests {
+    use super::*;
+
+    fn ids(results: Vec<(u32, f32)>) -> Vec<u32> {
+        results.into_iter().map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(tokenize("Fix the API's 2nd-level cache!"), vec!["fix", "the", "api", "s", "2nd", "level", "cache"]);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("report", "report"), 0);
+        assert_eq!(edit_distance("reprot", "report"), 2);
+        assert_eq!(edit_distance("repot", "report"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_search_ranks_exact_before_prefix_and_fuzzy() {
+        let mut index = SearchIndex::default();
+        index.index(1, "Write the reporting tool");
+        index.index(2, "Send report to Alice");
+        index.index(3, "Water the plants");
+        index.index(4, "Review the repot of the ficus");
+
+        assert_eq!(ids(index.search("report")), vec![2, 1, 4]);
+    }
+
+    #[test]
+    fn test_search_ranks_documents_matching_more_tokens_first() {
+        let mut index = SearchIndex::default();
+        index.index(1, "Plan the team offsite");
+        index.index(2, "Plan the quarterly budget");
+        index.index(3, "Budget review");
+
+        assert_eq!(ids(index.search("plan budget"))[0], 2);
+    }
+
+    #[test]
+    fn test_reindex_and_remove() {
+        let mut index = SearchIndex::default();
+        index.index(1, "Buy milk");
+        index.index(1, "Buy bread");
+
+        assert!(index.search("milk").is_empty());
+        assert_eq!(ids(index.search("bread")), vec![1]);
+
+        index.remove(1);
+        assert!(index.search("bread").is_empty());
+        assert!(index.postings.is_empty());
+    }
+}
+
+/* ========================================================================= */
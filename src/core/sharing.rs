@@ -0,0 +1,58 @@
+use std::str::FromStr;
+use serde::{Serialize, Deserialize};
+
+/* ROLE ==================================================================== */
+// What a member can do with a shared project (a top-level task and its
+// subtree). Each role can do everything the ones before it can.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+pub enum Role {
+    Viewer,
+    Editor,
+    // Manages the members and can delete the project
+    Owner,
+}
+impl FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw.to_lowercase().as_str() {
+            "viewer" => Ok(Role::Viewer),
+            "editor" => Ok(Role::Editor),
+            "owner" => Ok(Role::Owner),
+            _ => anyhow::bail!("Unknown role `{raw}`")
+        }
+    }
+}
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Viewer => write!(f, "viewer"),
+            Role::Editor => write!(f, "editor"),
+            Role::Owner => write!(f, "owner")
+        }
+    }
+}
+
+/* MEMBERSHIP ============================================================== */
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Membership {
+    pub project_id: u32,
+    pub user_id: u32,
+    pub role: Role,
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod sharing_tests {
+    use super::*;
+
+    #[test]
+    fn test_roles() {
+        assert!(Role::Owner > Role::Editor && Role::Editor > Role::Viewer);
+        assert_eq!("Editor".parse::<Role>().unwrap(), Role::Editor);
+        assert_eq!(Role::Owner.to_string().parse::<Role>().unwrap(), Role::Owner);
+        assert!("admin".parse::<Role>().is_err());
+    }
+}
+
+/* ========================================================================= */
@@ -0,0 +1,458 @@
+use std::collections::{BTreeMap, BTreeSet};
+use serde::{Serialize, Deserialize};
+use crate::core::tasks::{BatchOperation, Oswald, Task, TaskEvent};
+
+/* SYNCED TASK ============================================================= */
+// A task on its own (without its subtasks), along with where it sits in the tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncedTask {
+    pub parent: Option<u32>,
+    pub task: Task,
+}
+
+fn flatten_into(tasks: &[&Task], parent: Option<u32>, flat: &mut BTreeMap<u32, SyncedTask>) {
+    for task in tasks {
+        let mut own_task = (*task).clone();
+        for subtask in task.get_subtasks() {
+            own_task.delete_subtask(subtask.id);
+        }
+        flat.insert(task.id, SyncedTask { parent, task: own_task });
+        flatten_into(&task.get_subtasks(), Some(task.id), flat);
+    }
+}
+
+fn flatten(tasks: &[&Task]) -> BTreeMap<u32, SyncedTask> {
+    let mut flat = BTreeMap::new();
+    flatten_into(tasks, None, &mut flat);
+    flat
+}
+
+fn is_changed(base: &SyncedTask, synced: &SyncedTask) -> bool {
+    base.parent != synced.parent || !base.task.has_same_fields(&synced.task)
+}
+
+/* SYNC BASE =============================================================== */
+// The server's tasks as of the last sync, which tells the changes made
+// locally apart from the ones made on the server
+#[derive(Debug, Default, Clone)]
+pub struct SyncBase {
+    tasks: BTreeMap<u32, SyncedTask>,
+}
+impl SyncBase {
+    pub fn new(tasks: &[&Task]) -> Self {
+        SyncBase { tasks: flatten(tasks) }
+    }
+
+    pub fn from_synced_tasks(tasks: Vec<SyncedTask>) -> Self {
+        SyncBase { tasks: tasks.into_iter().map(|synced| (synced.task.id, synced)).collect() }
+    }
+
+    pub fn get_synced_tasks(&self) -> Vec<&SyncedTask> {
+        self.tasks.values().collect()
+    }
+
+    // Never synced
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    // Keeps up with the server's changes as they come (e.g. over `/events`)
+    pub fn apply_event(&mut self, event: &TaskEvent) {
+        match event {
+            TaskEvent::Reset { tasks } => *self = SyncBase::new(&tasks.iter().collect::<Vec<&Task>>()),
+            TaskEvent::Upserted { parent, task } => flatten_into(&[task.as_ref()], *parent, &mut self.tasks),
+            TaskEvent::Deleted { id } => {
+                // Along with its subtasks
+                let mut ids = vec![*id];
+                while let Some(id) = ids.pop() {
+                    self.tasks.remove(&id);
+                    ids.extend(self.tasks.values().filter(|synced| synced.parent == Some(id)).map(|synced| synced.task.id));
+                }
+            }
+        }
+    }
+}
+
+/* MERGING ================================================================= */
+// The side that changed the field, or the server's when both did
+fn pick<T: PartialEq + Clone>(base: &T, local: &T, remote: &T) -> T {
+    match local != base && remote == base {
+        true => local.clone(),
+        false => remote.clone()
+    }
+}
+
+// Three-way merge of a task changed on both sides. Fields changed on one side
+// only are taken from it, while the ones changed on both follow these rules:
+// - importance and urgency: the highest, so nothing gets buried
+// - status: the furthest along (open, blocked, archived, then done)
+// - tags: both sides' additions and removals
+// - the parent, and every other field: the server's
+fn merge_task(base: &SyncedTask, local: &SyncedTask, remote: &SyncedTask) -> SyncedTask {
+    let (base_task, local_task, remote_task) = (&base.task, &local.task, &remote.task);
+    let both_changed = |base: &f32, local: &f32, remote: &f32| local != base && remote != base;
+
+    let mut task = remote_task.clone();
+    task.importance = match both_changed(&base_task.importance, &local_task.importance, &remote_task.importance) {
+        true => local_task.importance.max(remote_task.importance),
+        false => pick(&base_task.importance, &local_task.importance, &remote_task.importance)
+    };
+    task.urgency = match both_changed(&base_task.urgency, &local_task.urgency, &remote_task.urgency) {
+        true => local_task.urgency.max(remote_task.urgency),
+        false => pick(&base_task.urgency, &local_task.urgency, &remote_task.urgency)
+    };
+    task.status = match local_task.status != base_task.status && remote_task.status != base_task.status {
+        true => local_task.status.max(remote_task.status),
+        false => pick(&base_task.status, &local_task.status, &remote_task.status)
+    };
+    task.tags = remote_task.tags.iter()
+        .chain(local_task.tags.difference(&base_task.tags))
+        .filter(|tag| local_task.tags.contains(*tag) || !base_task.tags.contains(*tag))
+        .cloned()
+        .collect();
+    task.desc = pick(&base_task.desc, &local_task.desc, &remote_task.desc);
+    task.notes = pick(&base_task.notes, &local_task.notes, &remote_task.notes);
+    task.links = pick(&base_task.links, &local_task.links, &remote_task.links);
+    task.checklist = pick(&base_task.checklist, &local_task.checklist, &remote_task.checklist);
+    task.estimate = pick(&base_task.estimate, &local_task.estimate, &remote_task.estimate);
+    task.due_date = pick(&base_task.due_date, &local_task.due_date, &remote_task.due_date);
+    task.deferred_until = pick(&base_task.deferred_until, &local_task.deferred_until, &remote_task.deferred_until);
+    task.placement = pick(&base_task.placement, &local_task.placement, &remote_task.placement);
+    task.assignee = pick(&base_task.assignee, &local_task.assignee, &remote_task.assignee);
+    task.follow_up = pick(&base_task.follow_up, &local_task.follow_up, &remote_task.follow_up);
+
+    SyncedTask { parent: pick(&base.parent, &local.parent, &remote.parent), task }
+}
+
+fn get_depth(tasks: &BTreeMap<u32, SyncedTask>, id: u32) -> usize {
+    let mut depth = 0;
+    let mut parent = tasks.get(&id).and_then(|synced| synced.parent);
+    while let Some(parent_id) = parent {
+        depth += 1;
+        if parent_id == id || depth > tasks.len() {
+            break;
+        }
+        parent = tasks.get(&parent_id).and_then(|synced| synced.parent);
+    }
+    depth
+}
+
+fn is_in_cycle(tasks: &BTreeMap<u32, SyncedTask>, id: u32) -> bool {
+    let mut parent = tasks.get(&id).and_then(|synced| synced.parent);
+    for _ in 0..tasks.len() {
+        match parent {
+            Some(parent_id) if parent_id == id => return true,
+            Some(parent_id) => parent = tasks.get(&parent_id).and_then(|synced| synced.parent),
+            None => return false
+        }
+    }
+    false
+}
+
+/* SYNC PLAN =============================================================== */
+#[derive(Debug, Default, PartialEq)]
+pub struct SyncPlan {
+    // Brings the server's tasks in line with the merged ones, checking their
+    // versions first so changes made there in the meantime aren't overwritten
+    pub operations: Vec<BatchOperation>,
+    // Local tasks that got a new id, as theirs was taken on the server meanwhile
+    pub renumbered: BTreeMap<u32, u32>,
+}
+
+// Merges the local tasks with the server's, given what the server had at the
+// last sync. Tasks added on one side are kept, as are the ones deleted on one
+// side but changed on the other, and the ones changed on both get merged (see
+// `merge_task`). Tasks whose parent is gone end up at the top level.
+pub fn plan_sync(base: &SyncBase, local: &[&Task], remote: &[&Task]) -> anyhow::Result<SyncPlan> {
+    let mut local = flatten(local);
+    let remote = flatten(remote);
+
+    // Tasks added on both sides with the same id: the server's keeps it
+    let mut next_id = base.tasks.keys().chain(local.keys()).chain(remote.keys()).max().map_or(Some(1), |id| id.checked_add(1));
+    let mut renumbered: BTreeMap<u32, u32> = BTreeMap::new();
+    for id in local.keys().filter(|id| !base.tasks.contains_key(id) && remote.contains_key(id)) {
+        let Some(new_id) = next_id else {
+            anyhow::bail!("There are no task ids left to renumber #{id} with");
+        };
+        renumbered.insert(*id, new_id);
+        next_id = new_id.checked_add(1);
+    }
+    local = local.into_values()
+        .map(|mut synced| {
+            synced.task.id = *renumbered.get(&synced.task.id).unwrap_or(&synced.task.id);
+            synced.parent = synced.parent.map(|parent| *renumbered.get(&parent).unwrap_or(&parent));
+            (synced.task.id, synced)
+        })
+        .collect();
+
+    let ids: BTreeSet<u32> = base.tasks.keys().chain(local.keys()).chain(remote.keys()).copied().collect();
+    let mut merged: BTreeMap<u32, SyncedTask> = BTreeMap::new();
+    for id in ids {
+        let synced = match (base.tasks.get(&id), local.get(&id), remote.get(&id)) {
+            (Some(base), Some(local), Some(remote)) => Some(merge_task(base, local, remote)),
+            // Edits beat deletions
+            (Some(base), Some(synced), None) | (Some(base), None, Some(synced)) => {
+                is_changed(base, synced).then(|| synced.clone())
+            },
+            (None, Some(synced), _) | (None, None, Some(synced)) => Some(synced.clone()),
+            (_, None, None) => None
+        };
+        if let Some(synced) = synced {
+            merged.insert(id, synced);
+        }
+    }
+
+    let ids: Vec<u32> = merged.keys().copied().collect();
+    for id in ids.iter() {
+        let parent = merged[id].parent;
+        if parent.is_some_and(|parent| !merged.contains_key(&parent)) {
+            merged.get_mut(id).unwrap().parent = None;
+        }
+    }
+    // Moves on either side can add up to a cycle, broken with the server's parent
+    for id in ids.iter() {
+        if !is_in_cycle(&merged, *id) {
+            continue;
+        }
+        let remote_parent = remote.get(id).and_then(|synced| synced.parent).filter(|parent| merged.contains_key(parent));
+        merged.get_mut(id).unwrap().parent = remote_parent;
+        if is_in_cycle(&merged, *id) {
+            merged.get_mut(id).unwrap().parent = None;
+        }
+    }
+
+    let mut checked = vec![];
+    let mut creates = vec![];
+    let mut moves = vec![];
+    let mut updates = vec![];
+    let mut deletes = vec![];
+    for (id, synced) in merged.iter() {
+        match remote.get(id) {
+            Some(remote_synced) => {
+                if !remote_synced.task.has_same_fields(&synced.task) {
+                    updates.push(BatchOperation::Update { task: Box::new(synced.task.clone()) });
+                    checked.push(remote_synced);
+                }
+                if remote_synced.parent != synced.parent {
+                    moves.push((get_depth(&merged, *id), *id, synced.parent));
+                    checked.push(remote_synced);
+                }
+            },
+            None => {
+                creates.push(BatchOperation::Upsert { task: Box::new(synced.task.clone()) });
+                if synced.parent.is_some() {
+                    moves.push((get_depth(&merged, *id), *id, synced.parent));
+                }
+            }
+        }
+    }
+    for (id, remote_synced) in remote.iter().filter(|(id, _)| !merged.contains_key(id)) {
+        checked.push(remote_synced);
+        // Subtasks go along with their parent
+        if remote_synced.parent.is_none_or(|parent| merged.contains_key(&parent)) {
+            deletes.push(*id);
+        }
+    }
+
+    checked.sort_by_key(|synced| synced.task.id);
+    checked.dedup_by_key(|synced| synced.task.id);
+    // Parents are in place before their subtasks move under them
+    moves.sort_by_key(|(depth, id, _)| (*depth, *id));
+
+    let mut operations: Vec<BatchOperation> = checked.into_iter()
+        .map(|synced| BatchOperation::CheckVersion { id: synced.task.id, version: synced.task.version })
+        .collect();
+    operations.extend(creates);
+    operations.extend(moves.into_iter().map(|(_, id, parent)| BatchOperation::Move { ids: vec![id], parent }));
+    operations.extend(updates);
+    if !deletes.is_empty() {
+        operations.push(BatchOperation::Delete { ids: deletes });
+    }
+    Ok(SyncPlan { operations, renumbered })
+}
+
+/* REMOTE EVENTS =========================================================== */
+// Mirrors a server's event into a forest with changes of its own, keeping its
+// sync base in step. A reset gets merged with the local changes (see
+// `plan_sync`) rather than replacing them, which are left for the next sync
+// to push. Returns the ids the local tasks were renumbered to.
+pub fn merge_remote_event(oswald: &mut Oswald, event: TaskEvent) -> anyhow::Result<BTreeMap<u32, u32>> {
+    let mut base = oswald.get_sync_base().clone();
+    base.apply_event(&event);
+    let TaskEvent::Reset { tasks } = &event else {
+        oswald.set_sync_base(base);
+        oswald.apply_event(event);
+        return Ok(BTreeMap::new());
+    };
+    let plan = plan_sync(oswald.get_sync_base(), &oswald.get_tasks(), &tasks.iter().collect::<Vec<&Task>>())?;
+    // All or nothing, like the batches
+    let mut merged = oswald.clone();
+    merged.set_sync_base(base);
+    merged.apply_event(event);
+    merged.apply_batch(&plan.operations)?;
+    merged.renumber_tasks(&plan.renumbered);
+    *oswald = merged;
+    Ok(plan.renumbered)
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod sync_tests {
+    use super::*;
+    use crate::core::tasks::TaskStatus;
+
+    fn new_task(id: u32, desc: &str) -> Task {
+        Task::new(id, desc.to_owned(), 0.0, 0.0, TaskStatus::Open)
+    }
+
+    fn with_subtask(mut task: Task, subtask: Task) -> Task {
+        task.add_subtask(Box::new(subtask));
+        task
+    }
+
+    #[test]
+    fn test_merge_task_rules() {
+        let mut base_task = new_task(1, "Report");
+        base_task.add_tag("work");
+        base_task.add_tag("q3");
+        let base = SyncedTask { parent: None, task: base_task.clone() };
+
+        let mut local = SyncedTask { parent: Some(2), task: base_task.clone() };
+        local.task.importance = 40.0;
+        local.task.urgency = 10.0;
+        local.task.status = TaskStatus::Done;
+        local.task.desc = "Quarterly report".to_owned();
+        local.task.add_tag("writing");
+        local.task.remove_tag("q3");
+
+        let mut remote = SyncedTask { parent: Some(3), task: base_task.clone() };
+        remote.task.importance = 20.0;
+        remote.task.status = TaskStatus::Blocked;
+        remote.task.notes = "Ask for the numbers".to_owned();
+        remote.task.add_tag("urgent");
+
+        let merged = merge_task(&base, &local, &remote);
+        assert_eq!(merged.task.importance, 40.0);
+        assert_eq!(merged.task.urgency, 10.0);
+        assert_eq!(merged.task.status, TaskStatus::Done);
+        assert_eq!(merged.task.desc, "Quarterly report");
+        assert_eq!(merged.task.notes, "Ask for the numbers");
+        assert_eq!(merged.task.tags.iter().collect::<Vec<&String>>(), vec!["urgent", "work", "writing"]);
+        // The server's move wins
+        assert_eq!(merged.parent, Some(3));
+
+        // Merging is the same whichever way around it happens
+        let merged = merge_task(&base, &remote, &local);
+        assert_eq!((merged.task.importance, merged.task.status), (40.0, TaskStatus::Done));
+    }
+
+    #[test]
+    fn test_plan_sync() {
+        let base_tasks = vec![with_subtask(new_task(1, "Home"), new_task(2, "Paint")), new_task(3, "Work")];
+        let base = SyncBase::new(&base_tasks.iter().collect::<Vec<&Task>>());
+
+        // Locally, "Paint" moved under "Work", "Home" was deleted and #4 was added
+        let local = [with_subtask(new_task(3, "Work"), new_task(2, "Paint")), new_task(4, "Local")];
+        // Remotely, "Work" was renamed and another #4 was added
+        let mut remote_tasks = base_tasks.clone();
+        remote_tasks[1].desc = "Office".to_owned();
+        remote_tasks.push(new_task(4, "Remote"));
+        remote_tasks[0].version = 2;
+
+        let plan = plan_sync(&base, &local.iter().collect::<Vec<&Task>>(), &remote_tasks.iter().collect::<Vec<&Task>>()).unwrap();
+        assert_eq!(plan.renumbered, BTreeMap::from([(4, 5)]));
+        assert_eq!(plan.operations, vec![
+            BatchOperation::CheckVersion { id: 1, version: 2 },
+            BatchOperation::CheckVersion { id: 2, version: 0 },
+            BatchOperation::Upsert { task: Box::new(new_task(5, "Local")) },
+            BatchOperation::Move { ids: vec![2], parent: Some(3) },
+            BatchOperation::Delete { ids: vec![1] },
+        ]);
+
+        // Nothing left to push once both sides agree
+        let plan = plan_sync(&SyncBase::new(&remote_tasks.iter().collect::<Vec<&Task>>()), &remote_tasks.iter().collect::<Vec<&Task>>(), &remote_tasks.iter().collect::<Vec<&Task>>()).unwrap();
+        assert_eq!(plan, SyncPlan::default());
+    }
+
+    #[test]
+    fn test_merge_remote_reset() {
+        let mut oswald = Oswald::new(crate::ports::DummyStore);
+        merge_remote_event(&mut oswald, TaskEvent::Reset { tasks: vec![new_task(1, "Report"), new_task(2, "Slides")] }).unwrap();
+        assert_eq!(oswald.get_tasks().len(), 2);
+
+        // Edited and added locally, while the server renamed "Slides" and added its own #3
+        let mut report = oswald.get_task(1).unwrap().clone();
+        report.notes = "Ask for the numbers".to_owned();
+        oswald.add_task(Box::new(report));
+        oswald.add_task(Box::new(new_task(3, "Local")));
+        merge_remote_event(&mut oswald, TaskEvent::Upserted { parent: None, task: Box::new(new_task(2, "Deck")) }).unwrap();
+        merge_remote_event(&mut oswald, TaskEvent::Upserted { parent: None, task: Box::new(new_task(3, "Remote")) }).unwrap();
+        assert_eq!(oswald.get_task(3).unwrap().desc, "Remote");
+        oswald.add_task(Box::new(new_task(4, "Local")));
+
+        // Reconnecting resets the tasks, which keeps the local changes
+        let remote_tasks = vec![new_task(1, "Report"), new_task(2, "Deck"), new_task(3, "Remote"), new_task(4, "Other")];
+        let renumbered = merge_remote_event(&mut oswald, TaskEvent::Reset { tasks: remote_tasks }).unwrap();
+        assert_eq!(renumbered, BTreeMap::from([(4, 5)]));
+        assert_eq!(oswald.get_task(1).unwrap().notes, "Ask for the numbers");
+        assert_eq!(oswald.get_task(2).unwrap().desc, "Deck");
+        assert_eq!(oswald.get_task(3).unwrap().desc, "Remote");
+        assert_eq!(oswald.get_task(4).unwrap().desc, "Other");
+        assert_eq!(oswald.get_task(5).unwrap().desc, "Local");
+
+        // The server's deletions carry over to the base too
+        merge_remote_event(&mut oswald, TaskEvent::Deleted { id: 2 }).unwrap();
+        assert!(oswald.get_task(2).is_none());
+        assert_eq!(oswald.get_sync_base().get_synced_tasks().len(), 3);
+    }
+
+    #[test]
+    fn test_renumbering_follows_references() {
+        use chrono::{NaiveDate, Utc};
+        use crate::core::planning::DailyPlan;
+        use crate::core::tasks::Placement;
+        use crate::core::tracking::TimeEntry;
+
+        let mut oswald = Oswald::new(crate::ports::DummyStore);
+        merge_remote_event(&mut oswald, TaskEvent::Reset { tasks: vec![new_task(1, "Report")] }).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        let mut local = new_task(2, "Local");
+        local.placement = Some(Placement::Day(today));
+        oswald.add_task(Box::new(local));
+        oswald.add_time_entry(TimeEntry::new(2, Utc::now(), "Drafting"));
+        oswald.add_time_entry(TimeEntry::new(1, Utc::now(), ""));
+        oswald.set_daily_plan(DailyPlan::new(today, vec![2, 1]));
+
+        // The server got its own #2 meanwhile
+        let remote_tasks = vec![new_task(1, "Report"), new_task(2, "Remote")];
+        let renumbered = merge_remote_event(&mut oswald, TaskEvent::Reset { tasks: remote_tasks }).unwrap();
+        assert_eq!(renumbered, BTreeMap::from([(2, 3)]));
+        let task_ids: Vec<u32> = oswald.get_time_entries().iter().map(|entry| entry.task_id).collect();
+        assert_eq!(task_ids, vec![3, 1]);
+        assert_eq!(oswald.get_daily_plan().unwrap().task_ids, vec![3, 1]);
+        assert_eq!(oswald.get_task(3).unwrap().placement, Some(Placement::Day(today)));
+        assert_eq!(oswald.get_task(2).unwrap().placement, None);
+    }
+
+    #[test]
+    fn test_plan_sync_runs_out_of_ids() {
+        let local = [new_task(u32::MAX, "Local")];
+        let remote = [new_task(u32::MAX, "Remote")];
+        assert!(plan_sync(&SyncBase::default(), &local.iter().collect::<Vec<&Task>>(), &remote.iter().collect::<Vec<&Task>>()).is_err());
+    }
+
+    #[test]
+    fn test_plan_sync_breaks_cycles() {
+        let base_tasks = [new_task(1, "A"), new_task(2, "B")];
+        let base = SyncBase::new(&base_tasks.iter().collect::<Vec<&Task>>());
+        // Each side moved a task under the other one
+        let local = [with_subtask(new_task(2, "B"), new_task(1, "A"))];
+        let remote = [with_subtask(new_task(1, "A"), new_task(2, "B"))];
+
+        let plan = plan_sync(&base, &local.iter().collect::<Vec<&Task>>(), &remote.iter().collect::<Vec<&Task>>()).unwrap();
+        // The server's tree stays as it is
+        assert_eq!(plan.operations, vec![]);
+    }
+}
+
+/* ========================================================================= */
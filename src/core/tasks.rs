@@ -1,8 +1,19 @@
 
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use serde::{Serialize, Deserialize};
+use std::str::FromStr;
 use std::sync::Arc;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use crate::core::delegation::{Person, WaitingFor, sort_by_follow_up};
+use crate::core::planning::{DailyPlan, Overview, PlanningSettings, pick_today, plan_overview};
+use crate::core::query::Query;
+use crate::core::quick_add::QuickAdd;
+use crate::core::search::{SearchIndex, SearchResult};
+use crate::core::sharing::{Membership, Role};
+use crate::core::sync::SyncBase;
+use crate::core::templates::Template;
+use crate::core::tracking::TimeEntry;
 use crate::ports::DataStore;
 
 /* TASK STATUS ============================================================= */
@@ -37,6 +48,167 @@ impl From<i32> for TaskStatus {
         }
     }
 }
+/* QUADRANT ================================================================ */
+// Eisenhower's matrix, where the origin of the arrange area splits the quadrants
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum Quadrant {
+    Do,
+    Schedule,
+    Delegate,
+    Eliminate,
+}
+impl Quadrant {
+    pub const ALL: [Quadrant; 4] = [Quadrant::Do, Quadrant::Schedule, Quadrant::Delegate, Quadrant::Eliminate];
+
+    pub fn get_suggested_action(&self) -> SuggestedAction {
+        match self {
+            Quadrant::Do => SuggestedAction::Start,
+            Quadrant::Schedule => SuggestedAction::Schedule,
+            Quadrant::Delegate => SuggestedAction::Delegate,
+            Quadrant::Eliminate => SuggestedAction::Archive,
+        }
+    }
+}
+
+// Tasks at or above both thresholds are important and urgent
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct QuadrantThresholds {
+    pub importance: f32,
+    pub urgency: f32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum SuggestedAction {
+    Start,
+    // Give it a due date or pin it to a day
+    Schedule,
+    Delegate,
+    Archive,
+}
+
+// See `Oswald::get_suggestions`
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub task_id: u32,
+    pub quadrant: Quadrant,
+    pub action: SuggestedAction,
+}
+impl FromStr for TaskStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw.to_lowercase().as_str() {
+            "open" => Ok(TaskStatus::Open),
+            "blocked" => Ok(TaskStatus::Blocked),
+            "archived" => Ok(TaskStatus::Archived),
+            "done" => Ok(TaskStatus::Done),
+            _ => anyhow::bail!("Unknown status `{raw}`")
+        }
+    }
+}
+impl FromStr for Quadrant {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw.to_lowercase().as_str() {
+            "do" | "do-first" => Ok(Quadrant::Do),
+            "schedule" => Ok(Quadrant::Schedule),
+            "delegate" => Ok(Quadrant::Delegate),
+            "eliminate" => Ok(Quadrant::Eliminate),
+            _ => anyhow::bail!("Unknown quadrant `{raw}`")
+        }
+    }
+}
+/* COMPLEXITY MODE ========================================================= */
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum ComplexityMode {
+    // Number of open subtasks in the tree
+    #[default]
+    Subtasks,
+    // Estimated effort, rolled up through the tree. Tasks without an estimate
+    // (and without open subtasks) count as `default_estimate`
+    Effort { default_estimate: u32 },
+}
+/* CHECKLIST ITEM ========================================================== */
+// Lightweight steps that don't deserve to be a full subtask
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub done: bool,
+}
+/* PLACEMENT =============================================================== */
+// Where the user pinned the task in the Overview, instead of leaving it to the ordering
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Placement {
+    Day(NaiveDate),
+    Backlog
+}
+impl FromStr for Placement {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw.to_lowercase().as_str() {
+            "backlog" => Ok(Placement::Backlog),
+            date => Ok(Placement::Day(date.parse()?))
+        }
+    }
+}
+impl std::fmt::Display for Placement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Placement::Day(date) => write!(f, "{date}"),
+            Placement::Backlog => write!(f, "backlog")
+        }
+    }
+}
+/* BATCH OPERATION ========================================================= */
+// One step of `Oswald::apply_batch`, e.g. `{ "op": "set_status", "ids": [1, 2], "status": "Done" }`
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperation {
+    Upsert { task: Box<Task> },
+    Delete { ids: Vec<u32> },
+    SetStatus { ids: Vec<u32>, status: TaskStatus },
+    AddTag { ids: Vec<u32>, tag: String },
+    RemoveTag { ids: Vec<u32>, tag: String },
+    // `None` moves the tasks to the top level
+    Move { ids: Vec<u32>, parent: Option<u32> },
+    Nudge {
+        ids: Vec<u32>,
+        #[serde(default)]
+        importance: f32,
+        #[serde(default)]
+        urgency: f32
+    },
+    // Fails the batch unless the task is at this version, see `Oswald::check_version`
+    CheckVersion { id: u32, version: u32 },
+    // Replaces the task's own fields, keeping its subtasks where they are
+    Update { task: Box<Task> }
+}
+/* VERSION CONFLICT ======================================================== */
+// A write based on an outdated version of a task (see `Task::version`), which
+// the stores fail with too when the task changed since they read it
+#[derive(Debug)]
+pub struct VersionConflict(pub String);
+impl std::fmt::Display for VersionConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+impl std::error::Error for VersionConflict {}
+
+/* TASK EVENT ============================================================== */
+// Change to the forest, as recorded by `Oswald::take_events` and mirrored by `Oswald::apply_event`,
+// e.g. `{ "event": "deleted", "id": 4 }`
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TaskEvent {
+    // Every top-level task, replacing whatever was there
+    Reset { tasks: Vec<Task> },
+    // The whole subtree, with `None` for top-level tasks
+    Upserted { parent: Option<u32>, task: Box<Task> },
+    Deleted { id: u32 }
+}
 /* TASK ==================================================================== */
 #[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Task {
@@ -45,6 +217,35 @@ pub struct Task {
     pub urgency: f32,
     pub status: TaskStatus,
     pub desc: String,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    // Markdown
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub links: Vec<String>,
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    // In whatever unit the user estimates in (e.g. minutes or story points)
+    #[serde(default)]
+    pub estimate: Option<u32>,
+    #[serde(default)]
+    pub due_date: Option<NaiveDate>,
+    // Snoozed tasks stay hidden until `Oswald::resurface_tasks` reaches this date
+    #[serde(default)]
+    pub deferred_until: Option<NaiveDate>,
+    // `None` leaves the task to the automatic distribution
+    #[serde(default)]
+    pub placement: Option<Placement>,
+    // Person id, see `Oswald::assign_task`
+    #[serde(default)]
+    pub assignee: Option<u32>,
+    // When to chase the assignee
+    #[serde(default)]
+    pub follow_up: Option<NaiveDate>,
+    // Bumped on every write, see `Oswald::add_task`
+    #[serde(default)]
+    pub version: u32,
     subtasks_map: HashMap<u32, Box<Self>>,
 }
 impl Task {
@@ -55,6 +256,17 @@ impl Task {
             urgency,
             status,
             desc,
+            tags: BTreeSet::new(),
+            notes: String::new(),
+            links: vec![],
+            checklist: vec![],
+            estimate: None,
+            due_date: None,
+            deferred_until: None,
+            placement: None,
+            assignee: None,
+            follow_up: None,
+            version: 0,
             subtasks_map: HashMap::new()
         }
     }
@@ -88,6 +300,94 @@ impl Task {
         })
     }
 
+    pub fn get_quadrant(&self) -> Quadrant {
+        self.get_quadrant_with(&QuadrantThresholds::default())
+    }
+    pub fn get_quadrant_with(&self, thresholds: &QuadrantThresholds) -> Quadrant {
+        match (self.importance >= thresholds.importance, self.urgency >= thresholds.urgency) {
+            (true, true) => Quadrant::Do,
+            (true, false) => Quadrant::Schedule,
+            (false, true) => Quadrant::Delegate,
+            (false, false) => Quadrant::Eliminate,
+        }
+    }
+
+    pub fn add_tag(&mut self, tag: &str) {
+        if let Some(tag) = normalize_tag(tag) {
+            self.tags.insert(tag);
+        }
+    }
+    pub fn remove_tag(&mut self, tag: &str) {
+        if let Some(tag) = normalize_tag(tag) {
+            self.tags.remove(&tag);
+        }
+    }
+    pub fn has_tags(&self, tags: &BTreeSet<String>) -> bool {
+        tags.is_subset(&self.tags)
+    }
+
+    // Everything but the id, version and subtasks
+    pub fn has_same_fields(&self, other: &Task) -> bool {
+        self.importance == other.importance &&
+            self.urgency == other.urgency &&
+            self.status == other.status &&
+            self.desc == other.desc &&
+            self.tags == other.tags &&
+            self.notes == other.notes &&
+            self.links == other.links &&
+            self.checklist == other.checklist &&
+            self.estimate == other.estimate &&
+            self.due_date == other.due_date &&
+            self.deferred_until == other.deferred_until &&
+            self.placement == other.placement &&
+            self.assignee == other.assignee &&
+            self.follow_up == other.follow_up
+    }
+
+    pub fn is_deferred(&self) -> bool {
+        self.deferred_until.is_some()
+    }
+
+    // Delegated and not back yet
+    pub fn is_waiting(&self) -> bool {
+        self.assignee.is_some() && matches!(self.status, TaskStatus::Open | TaskStatus::Blocked)
+    }
+
+    pub fn add_checklist_item(&mut self, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        self.checklist.push(ChecklistItem { text: text.to_owned(), done: false });
+    }
+    // (done, total)
+    pub fn get_checklist_progress(&self) -> (usize, usize) {
+        let done = self.checklist.iter().filter(|item| item.done).count();
+        (done, self.checklist.len())
+    }
+
+    pub fn get_effort(&self, default_estimate: u32) -> u32 {
+        let open_subtasks: Vec<&Self> = self.subtasks_map.values()
+            .filter(|subtask| matches!(subtask.status, TaskStatus::Open))
+            .map(|subtask| subtask.as_ref())
+            .collect();
+        let subtasks_effort = open_subtasks.iter()
+            .fold(0_u32, |result, subtask| result.saturating_add(subtask.get_effort(default_estimate)));
+
+        match self.estimate {
+            Some(estimate) => estimate.saturating_add(subtasks_effort),
+            None if !open_subtasks.is_empty() => subtasks_effort,
+            None => default_estimate
+        }
+    }
+
+    pub fn get_complexity_by(&self, mode: ComplexityMode) -> u32 {
+        match mode {
+            ComplexityMode::Subtasks => self.get_complexity(),
+            ComplexityMode::Effort { default_estimate } => self.get_effort(default_estimate)
+        }
+    }
+
     fn _add_subtask(&mut self, subtask: Box<Self>) {
         self.subtasks_map.insert(subtask.id, subtask);
     }
@@ -114,13 +414,16 @@ impl Task {
     }
 
     pub fn get_subtasks(&self) -> Vec<&Self> {
+        self.get_subtasks_by(ComplexityMode::default())
+    }
+    pub fn get_subtasks_by(&self, mode: ComplexityMode) -> Vec<&Self> {
         // TODO (maybe): Cache vector and only sort after insertion/deletion to the map, instead of each time
         let mut collected_subtasks: Vec<&Self> = self
             .subtasks_map
             .values()
             .map(|boxed_task| boxed_task.as_ref())
             .collect();
-        collected_subtasks.sort();
+        collected_subtasks.sort_by(|task, other| task.cmp_by(other, mode));
         collected_subtasks
     }
     pub fn get_all_subtasks(&self) -> Vec<&Self> {
@@ -138,6 +441,30 @@ impl Task {
         all_subtasks.sort();
         all_subtasks
     }
+    pub fn get_all_subtasks_by(&self, mode: ComplexityMode) -> Vec<&Self> {
+        let mut all_subtasks = self.get_all_subtasks();
+        all_subtasks.sort_by(|task, other| task.cmp_by(other, mode));
+        all_subtasks
+    }
+
+    pub fn get_subtask(&self, id: u32) -> Option<&Task> {
+        if let Some(subtask) = self.subtasks_map.get(&id) {
+            return Some(subtask)
+        }
+        self.subtasks_map.values().find_map(|subtask| subtask.get_subtask(id))
+    }
+
+    // Ids from the top-level subtask down to the direct parent of the given subtask
+    pub fn get_subtask_path(&self, id: u32) -> Option<Vec<u32>> {
+        if self.subtasks_map.contains_key(&id) {
+            return Some(vec![])
+        }
+        self.subtasks_map.values().find_map(|subtask| {
+            let mut path = subtask.get_subtask_path(id)?;
+            path.insert(0, subtask.id);
+            Some(path)
+        })
+    }
 
     fn get_subtask_parent(&mut self, id: u32) -> Option<&mut Task> {
         // Searching self
@@ -160,8 +487,8 @@ impl PartialEq for Task {
     }
 }
 impl Eq for Task {}
-impl Ord for Task {
-    fn cmp(&self, other: &Self) -> Ordering {
+impl Task {
+    pub fn cmp_by(&self, other: &Self, mode: ComplexityMode) -> Ordering {
         if self.id == other.id {
             return Ordering::Equal;
         }
@@ -172,8 +499,8 @@ impl Ord for Task {
         }
 
         // Compare complexity
-        let self_complexity = self.get_complexity();
-        let other_complexity = other.get_complexity();
+        let self_complexity = self.get_complexity_by(mode);
+        let other_complexity = other.get_complexity_by(mode);
         if self_complexity != other_complexity {
             return self_complexity.cmp(&other_complexity);
         }
@@ -192,6 +519,11 @@ impl Ord for Task {
         self.id.cmp(&other.id)
     }
 }
+impl Ord for Task {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_by(other, ComplexityMode::default())
+    }
+}
 impl PartialOrd for Task {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -206,6 +538,21 @@ impl std::fmt::Debug for Task {
 
 pub type BoxTaskVec = Vec<Box<Task>>;
 
+/* TAGS ==================================================================== */
+// Tags are stored lowercase and without the leading '#', so "#Work" and "work" are the same tag
+fn normalize_tag(raw: &str) -> Option<String> {
+    let tag = raw.trim().trim_start_matches('#').to_lowercase();
+    if tag.is_empty() {
+        return None;
+    }
+    Some(tag)
+}
+pub fn parse_tags(raw: &str) -> BTreeSet<String> {
+    raw.split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(normalize_tag)
+        .collect()
+}
+
 #[cfg(test)]
 mod task_tests {
     use super::*;
@@ -787,6 +1134,194 @@ mod task_tests {
 
     }
 
+    #[test]
+    fn test_add_and_remove_normalized_tags() {
+        let mut task = Task::default();
+        task.add_tag("#Work");
+        task.add_tag("  home ");
+        task.add_tag("#");
+
+        assert_eq!(task.tags, BTreeSet::from(["work".to_owned(), "home".to_owned()]));
+
+        task.remove_tag("WORK");
+        assert_eq!(task.tags, BTreeSet::from(["home".to_owned()]));
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        let tags = parse_tags("#work, Home  errands,,");
+        assert_eq!(tags, BTreeSet::from([
+            "work".to_owned(),
+            "home".to_owned(),
+            "errands".to_owned()
+        ]));
+        assert!(parse_tags(" , ").is_empty());
+    }
+
+    #[test]
+    fn test_has_tags() {
+        let mut task = Task::default();
+        task.add_tag("work");
+        task.add_tag("urgent");
+
+        assert!(task.has_tags(&BTreeSet::new()));
+        assert!(task.has_tags(&parse_tags("work")));
+        assert!(task.has_tags(&parse_tags("work urgent")));
+        assert!(!task.has_tags(&parse_tags("work home")));
+    }
+
+    #[test]
+    fn test_get_nested_subtask() {
+        let mut root = Task::default();
+        let mut task = Box::new(Task::new_with_id(1));
+        let mut subtask = Box::new(Task::new_with_id(2));
+        subtask.add_subtask(Box::new(Task::new_with_id(3)));
+        task.add_subtask(subtask);
+        root.add_subtask(task);
+
+        assert_eq!(root.get_subtask(3).expect("Expected Task #3").id, 3);
+        assert_eq!(root.get_subtask(1).expect("Expected Task #1").id, 1);
+        assert!(root.get_subtask(4).is_none());
+    }
+
+    #[test]
+    fn test_get_subtask_path() {
+        /*
+         *     (t)
+         *      |
+         *     (sA)
+         *    /   \
+         *  (sB)  (sC)
+         *          |
+         *         (sD)
+         */
+        let mut root = Task::default();
+        let mut subtask_a = Box::new(Task::new_with_id(1));
+        let mut subtask_c = Box::new(Task::new_with_id(3));
+        subtask_c.add_subtask(Box::new(Task::new_with_id(4)));
+        subtask_a.add_subtask(Box::new(Task::new_with_id(2)));
+        subtask_a.add_subtask(subtask_c);
+        root.add_subtask(subtask_a);
+
+        assert_eq!(root.get_subtask_path(1), Some(vec![]));
+        assert_eq!(root.get_subtask_path(2), Some(vec![1]));
+        assert_eq!(root.get_subtask_path(4), Some(vec![1, 3]));
+        assert_eq!(root.get_subtask_path(5), None);
+    }
+
+    #[test]
+    fn test_get_quadrant() {
+        let mut task = Task::new_with_id(1);
+        task.importance = 10.0;
+        task.urgency = 10.0;
+        assert_eq!(task.get_quadrant(), Quadrant::Do);
+        task.urgency = -10.0;
+        assert_eq!(task.get_quadrant(), Quadrant::Schedule);
+        task.importance = -10.0;
+        assert_eq!(task.get_quadrant(), Quadrant::Eliminate);
+        task.urgency = 10.0;
+        assert_eq!(task.get_quadrant(), Quadrant::Delegate);
+    }
+
+    #[test]
+    fn test_get_quadrant_with_thresholds() {
+        let mut task = Task::new_with_id(1);
+        task.importance = 10.0;
+        task.urgency = 30.0;
+        let thresholds = QuadrantThresholds { importance: 20.0, urgency: 30.0 };
+        assert_eq!(task.get_quadrant_with(&thresholds), Quadrant::Delegate);
+        task.importance = 20.0;
+        assert_eq!(task.get_quadrant_with(&thresholds), Quadrant::Do);
+    }
+
+    #[test]
+    fn test_get_effort_rolls_up_open_subtasks() {
+        /*
+         *          (t: 10)
+         *         /   |    \
+         *   (sA: 30) (sB) (sC: 60, done)
+         *             |
+         *          (sD: 15)
+         */
+        let mut task = Task::new_with_id(1);
+        task.estimate = Some(10);
+        let mut subtask_a = Box::new(Task::new_with_id(2));
+        subtask_a.estimate = Some(30);
+        let mut subtask_b = Box::new(Task::new_with_id(3));
+        let mut subtask_c = Box::new(Task::new_with_id(4));
+        subtask_c.estimate = Some(60);
+        subtask_c.status = TaskStatus::Done;
+        let mut subtask_d = Box::new(Task::new_with_id(5));
+        subtask_d.estimate = Some(15);
+
+        subtask_b.add_subtask(subtask_d);
+        task.add_subtask(subtask_a);
+        task.add_subtask(subtask_b);
+        task.add_subtask(subtask_c);
+
+        assert_eq!(task.get_effort(0), 55);
+        assert_eq!(Task::new_with_id(6).get_effort(25), 25);
+    }
+
+    #[test]
+    fn test_effort_complexity_sort() {
+        let mut root = Task::default();
+        // A single long task
+        let mut task_a = Box::new(Task::new_with_id(1));
+        task_a.estimate = Some(240);
+        // Many quick subtasks
+        let mut task_b = Box::new(Task::new_with_id(2));
+        for id in 3..6 {
+            let mut subtask = Box::new(Task::new_with_id(id));
+            subtask.estimate = Some(5);
+            task_b.add_subtask(subtask);
+        }
+        root.add_subtask(task_a);
+        root.add_subtask(task_b);
+
+        let ids: Vec<u32> = root.get_subtasks().into_iter().map(|task| task.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+
+        let mode = ComplexityMode::Effort { default_estimate: 30 };
+        let ids: Vec<u32> = root.get_subtasks_by(mode).into_iter().map(|task| task.id).collect();
+        assert_eq!(ids, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_checklist_progress() {
+        let mut task = Task::default();
+        task.add_checklist_item("Pack charger");
+        task.add_checklist_item("   ");
+        task.add_checklist_item("Print tickets");
+        task.checklist[1].done = true;
+
+        assert_eq!(task.checklist.len(), 2);
+        assert_eq!(task.get_checklist_progress(), (1, 2));
+    }
+
+    #[test]
+    fn test_deserialize_task_without_details() {
+        let task: Task = serde_json::from_str(
+            r#"{"id":1,"importance":0.0,"urgency":0.0,"status":"Open","desc":"Legacy","subtasks_map":{}}"#
+        ).expect("Expected a task stored before tags and details existed");
+
+        assert!(task.tags.is_empty());
+        assert!(task.notes.is_empty());
+        assert!(task.links.is_empty());
+        assert!(task.checklist.is_empty());
+        assert_eq!(task.placement, None);
+    }
+
+    #[test]
+    fn test_parse_placement() {
+        let day = Placement::Day(NaiveDate::from_ymd_opt(2024, 8, 1).unwrap());
+
+        assert_eq!("2024-08-01".parse::<Placement>().unwrap(), day);
+        assert_eq!("Backlog".parse::<Placement>().unwrap(), Placement::Backlog);
+        assert_eq!(day.to_string().parse::<Placement>().unwrap(), day);
+        assert!("someday".parse::<Placement>().is_err());
+    }
+
     #[test]
     fn test_add_multiple_subtasks() {
         let mut root = Box::new(Task::default());
@@ -811,115 +1346,941 @@ mod task_tests {
 #[derive(Debug, Clone)]
 pub struct Oswald {
     root: Task,
+    complexity_mode: ComplexityMode,
+    quadrant_thresholds: QuadrantThresholds,
+    search_index: SearchIndex,
+    time_entries: Vec<TimeEntry>,
+    daily_plan: Option<DailyPlan>,
+    templates: BTreeMap<String, Template>,
+    people: BTreeMap<u32, Person>,
+    // Roles per user, for each shared project
+    members: BTreeMap<u32, BTreeMap<u32, Role>>,
+    // Only kept once `record_events` is called, until they're taken
+    events: Option<Vec<TaskEvent>>,
+    // The server's tasks as of the last sync, see `clients::sync`
+    sync_base: SyncBase,
+    // What every client plans the days with
+    planning_settings: PlanningSettings,
     data_store: Arc<dyn DataStore + Send + Sync>
 }
 impl Oswald {
     pub fn new(data_store: impl DataStore + Send + Sync + 'static) -> Self {
         Oswald {
             root: Task::default(),
+            complexity_mode: ComplexityMode::default(),
+            quadrant_thresholds: QuadrantThresholds::default(),
+            search_index: SearchIndex::default(),
+            time_entries: vec![],
+            daily_plan: None,
+            templates: BTreeMap::new(),
+            people: BTreeMap::new(),
+            members: BTreeMap::new(),
+            events: None,
+            sync_base: SyncBase::default(),
+            planning_settings: PlanningSettings::default(),
             data_store: Arc::new(data_store)
         }
     }
-    pub fn add_task(&mut self, task: Box<Task>) {
-        self.root.add_subtask(task)
+
+    fn index_task(&mut self, task: &Task) {
+        self.search_index.index(task.id, &task.desc);
+        for subtask in task.get_all_subtasks() {
+            self.search_index.index(subtask.id, &subtask.desc);
+        }
+    }
+    fn unindex_task(&mut self, id: u32) {
+        let Some(task) = self.root.get_subtask(id) else {
+            return;
+        };
+        let mut ids: Vec<u32> = task.get_all_subtasks().into_iter().map(|subtask| subtask.id).collect();
+        ids.push(id);
+        ids.into_iter().for_each(|id| self.search_index.remove(id));
+    }
+
+    // Bumps the task's version past the stored one, whatever the task came with. So do its
+    // subtasks that were changed or moved, the others keep their stored version.
+    pub fn add_task(&mut self, mut task: Box<Task>) {
+        task.version = self.get_next_version(task.id);
+        self.set_subtask_versions(&mut task);
+        self.restore_task(task);
+    }
+    fn get_next_version(&self, id: u32) -> u32 {
+        self.get_task(id).map_or(0, |stored| stored.version).saturating_add(1)
+    }
+    fn set_subtask_versions(&self, task: &mut Task) {
+        for subtask in task.subtasks_map.values_mut() {
+            let is_unchanged = self.get_task(subtask.id).is_some_and(|stored| stored.has_same_fields(subtask)) &&
+                self.get_task_path(subtask.id).and_then(|path| path.last().copied()) == Some(task.id);
+            subtask.version = match is_unchanged {
+                true => self.get_task(subtask.id).map_or(0, |stored| stored.version),
+                false => self.get_next_version(subtask.id)
+            };
+            self.set_subtask_versions(subtask);
+        }
+    }
+
+    // Puts the task back as it was stored (e.g. when loading), version included
+    pub fn restore_task(&mut self, task: Box<Task>) {
+        // The task replaces the whole stored subtree, so the old subtree is dropped from the index
+        self.unindex_task(task.id);
+        self.index_task(&task);
+        let recorded_task = self.events.is_some().then(|| task.clone());
+        self.root.add_subtask(task);
+        // Nested tasks stay where they are, so the parent is only known once it's in place
+        if let Some(task) = recorded_task {
+            let parent = self.root.get_subtask_path(task.id).and_then(|path| path.last().copied());
+            self.events.get_or_insert_with(Vec::new).push(TaskEvent::Upserted { parent, task });
+        }
+    }
+
+    pub fn get_complexity_mode(&self) -> ComplexityMode {
+        self.complexity_mode
+    }
+
+    pub fn set_complexity_mode(&mut self, mode: ComplexityMode) {
+        self.complexity_mode = mode;
+    }
+
+    pub fn get_quadrant_thresholds(&self) -> QuadrantThresholds {
+        self.quadrant_thresholds
+    }
+
+    pub fn set_quadrant_thresholds(&mut self, thresholds: QuadrantThresholds) {
+        self.quadrant_thresholds = thresholds;
     }
 
     pub fn get_tasks(&self) -> Vec<&Task> {
-        self.root.get_subtasks()
+        self.root.get_subtasks_by(self.complexity_mode)
     }
 
     pub fn get_all_tasks(&self) -> Vec<&Task> {
-        self.root.get_all_subtasks()
+        self.root.get_all_subtasks_by(self.complexity_mode)
     }
 
-    pub fn delete_task(&mut self, id: u32) {
-        self.root.delete_subtask(id);
+    pub fn get_tasks_with_tags(&self, tags: &BTreeSet<String>) -> Vec<&Task> {
+        self.get_tasks()
+            .into_iter()
+            .filter(|task| task.has_tags(tags))
+            .collect()
     }
 
-    pub fn clear(&mut self) {
-        self.root = Task::default();
+    pub fn get_all_tasks_with_tags(&self, tags: &BTreeSet<String>) -> Vec<&Task> {
+        self.get_all_tasks()
+            .into_iter()
+            .filter(|task| task.has_tags(tags))
+            .collect()
     }
 
-    // TODO: Use status type design pattern in the future
-    pub async fn load(&mut self) -> anyhow::Result<()> {
-        let tasks = self.data_store.read().await?;
-        for task in tasks.into_iter() {
-            self.root.add_subtask(task)
-        }
+    pub fn get_task(&self, id: u32) -> Option<&Task> {
+        self.root.get_subtask(id)
+    }
+
+    // Ids from the top-level task down to the task's direct parent
+    pub fn get_task_path(&self, id: u32) -> Option<Vec<u32>> {
+        self.root.get_subtask_path(id)
+    }
 
+    pub fn add_subtask(&mut self, parent_id: u32, task: Box<Task>) -> anyhow::Result<()> {
+        let Some(mut parent) = self.get_task(parent_id).cloned() else {
+            anyhow::bail!("Task #{parent_id} doesn't exist");
+        };
+        parent.add_subtask(task);
+        self.add_task(Box::new(parent));
         Ok(())
     }
 
-    pub async fn save(&self) -> anyhow::Result<()> {
-        let tasks = self.get_tasks();
-        self.data_store.write(tasks).await
+    // Hands the parsed task the next free id, returning it
+    pub fn add_quick_task(&mut self, quick_add: QuickAdd) -> anyhow::Result<u32> {
+        let mut task = quick_add.task;
+        task.id = self.get_next_id()?;
+        let id = task.id;
+        match quick_add.parent {
+            Some(parent_id) => self.add_subtask(parent_id, Box::new(task))?,
+            None => self.add_task(Box::new(task))
+        }
+        Ok(id)
     }
-}
 
-/* TESTS =================================================================== */
-#[cfg(test)]
-mod oswald_tests {
-    use super::{
-        Oswald,
-        Task
-    };
-    use crate::ports::MockDataStore;
+    pub fn query(&self, query: &Query) -> Vec<&Task> {
+        let mut tasks = query.select_with(&self.root, &self.quadrant_thresholds);
+        tasks.sort_by(|task, other| task.cmp_by(other, self.complexity_mode));
+        tasks
+    }
 
-    #[tokio::test]
-    async fn test_load_all_tasks_from_data_store() {
-        let mut oswald = Oswald::new(MockDataStore);
+    pub fn search(&self, text: &str) -> Vec<SearchResult> {
+        self.search_index.search(text)
+            .into_iter()
+            .filter_map(|(id, score)| {
+                let path = self.root.get_subtask_path(id)?;
+                Some(SearchResult { id, score, path })
+            })
+            .collect()
+    }
 
-        let _ = oswald.load().await;
+    pub fn delete_task(&mut self, id: u32) {
+        if self.get_task(id).is_none() {
+            return;
+        }
+        self.unindex_task(id);
+        self.root.delete_subtask(id);
+        if let Some(events) = self.events.as_mut() {
+            events.push(TaskEvent::Deleted { id });
+        }
+    }
 
-        assert!(oswald.root.subtasks_map.contains_key(&0));
-        assert!(oswald.root.subtasks_map.contains_key(&1));
-        assert!(oswald.root.subtasks_map.contains_key(&2));
+    // Starts keeping the task changes around, for them to be pushed elsewhere
+    pub fn record_events(&mut self) {
+        self.events.get_or_insert_with(Vec::new);
+    }
 
-        assert!(oswald
-            .root
-            .subtasks_map
-            .get(&0)
-            .unwrap()
-            .subtasks_map
-            .contains_key(&3));
-        assert!(oswald
-            .root
-            .subtasks_map
-            .get(&2)
-            .unwrap()
-            .subtasks_map
-            .contains_key(&4));
-        assert!(oswald
-            .root
-            .subtasks_map
-            .get(&2)
-            .unwrap()
-            .subtasks_map
-            .contains_key(&5));
+    pub fn take_events(&mut self) -> Vec<TaskEvent> {
+        self.events.as_mut().map(std::mem::take).unwrap_or_default()
     }
 
-    #[test]
-    fn test_add_task() {
-        let mut oswald = Oswald::new(MockDataStore);
-        let task = Box::new(Task::new_with_id(1));
+    // Mirrors a change recorded by another forest (e.g. a server's)
+    pub fn apply_event(&mut self, event: TaskEvent) {
+        match event {
+            TaskEvent::Reset { tasks } => {
+                for id in self.root.get_subtasks().into_iter().map(|task| task.id).collect::<Vec<u32>>() {
+                    self.delete_task(id);
+                }
+                for task in tasks {
+                    self.restore_task(Box::new(task));
+                }
+            },
+            TaskEvent::Upserted { parent, task } => {
+                let parent = parent.filter(|parent_id| self.get_task(*parent_id).is_some());
+                // Moved tasks are taken out of their old parent first
+                if self.get_task_path(task.id).is_some_and(|path| path.last().copied() != parent) {
+                    self.delete_task(task.id);
+                }
+                // Versions are taken as they come, the parent's included
+                match parent.and_then(|parent_id| self.get_task(parent_id).cloned()) {
+                    Some(mut parent) => {
+                        parent.add_subtask(task);
+                        self.restore_task(Box::new(parent));
+                    },
+                    None => self.restore_task(task)
+                }
+            },
+            TaskEvent::Deleted { id } => self.delete_task(id)
+        }
+    }
 
-        oswald.add_task(task);
+    pub fn clear(&mut self) {
+        self.root = Task::default();
+        self.search_index.clear();
+        self.time_entries.clear();
+        self.daily_plan = None;
+        self.templates.clear();
+        self.people.clear();
+        self.members.clear();
+        self.sync_base = SyncBase::default();
+        self.planning_settings = PlanningSettings::default();
+    }
 
-        assert!(oswald.root.subtasks_map.contains_key(&1));
+    pub fn get_planning_settings(&self) -> &PlanningSettings {
+        &self.planning_settings
     }
 
-    #[test]
-    fn test_delete_task_by_id() {
-        /*
-         *           (t)
-         *          /   \
-         *       (sA)   (sB)
-         *             /   \
-         *           (sC) (sD)
-         *                 |
-         *                (sE)
-         */
-        let mut oswald = Oswald::new(MockDataStore);
+    pub fn set_planning_settings(&mut self, settings: PlanningSettings) {
+        self.planning_settings = settings;
+    }
+
+    pub fn get_sync_base(&self) -> &SyncBase {
+        &self.sync_base
+    }
+
+    pub fn set_sync_base(&mut self, base: SyncBase) {
+        self.sync_base = base;
+    }
+
+    // Points what refers to tasks by id outside of them (time entries, the daily plan and the
+    // shared projects' members) to the ids the tasks were renumbered to, see `plan_sync`
+    pub fn renumber_tasks(&mut self, renumbered: &BTreeMap<u32, u32>) {
+        let renumber = |id: &mut u32| *id = *renumbered.get(id).unwrap_or(id);
+        self.time_entries.iter_mut().for_each(|entry| renumber(&mut entry.task_id));
+        if let Some(plan) = self.daily_plan.as_mut() {
+            plan.task_ids.iter_mut().for_each(renumber);
+        }
+        self.members = std::mem::take(&mut self.members).into_iter()
+            .map(|(mut project_id, roles)| {
+                renumber(&mut project_id);
+                (project_id, roles)
+            })
+            .collect();
+    }
+
+    pub fn snooze_task(&mut self, id: u32, until: NaiveDate, today: NaiveDate) -> anyhow::Result<()> {
+        if until <= today {
+            anyhow::bail!("Tasks can only be snoozed until a future date");
+        }
+        let mut task = self.get_task(id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Task #{id} doesn't exist"))?;
+        task.deferred_until = Some(until);
+        self.add_task(Box::new(task));
+        Ok(())
+    }
+
+    pub fn unsnooze_task(&mut self, id: u32) -> anyhow::Result<()> {
+        let mut task = self.get_task(id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Task #{id} doesn't exist"))?;
+        task.deferred_until = None;
+        self.add_task(Box::new(task));
+        Ok(())
+    }
+
+    // Brings back the tasks whose snooze date has come, returning their ids
+    pub fn resurface_tasks(&mut self, today: NaiveDate) -> Vec<u32> {
+        let due_ids: Vec<u32> = self.root.get_all_subtasks()
+            .into_iter()
+            .filter(|task| task.deferred_until.is_some_and(|until| until <= today))
+            .map(|task| task.id)
+            .collect();
+        for id in due_ids.iter() {
+            let _ = self.unsnooze_task(*id);
+        }
+        due_ids
+    }
+
+    // Pins the task to a day or the backlog, or hands it back to the distribution with `None`
+    pub fn place_task(&mut self, id: u32, placement: Option<Placement>) -> anyhow::Result<()> {
+        let mut task = self.get_task(id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Task #{id} doesn't exist"))?;
+        task.placement = placement;
+        self.add_task(Box::new(task));
+        Ok(())
+    }
+
+    fn update_task(&mut self, id: u32, update: impl FnOnce(&mut Task)) -> anyhow::Result<()> {
+        let Some(mut task) = self.get_task(id).cloned() else {
+            anyhow::bail!("Task #{id} doesn't exist");
+        };
+        update(&mut task);
+        self.add_task(Box::new(task));
+        Ok(())
+    }
+
+    fn apply_operation(&mut self, operation: &BatchOperation) -> anyhow::Result<()> {
+        match operation {
+            BatchOperation::Upsert { task } => self.add_task(task.clone()),
+            BatchOperation::Delete { ids } => {
+                for id in ids {
+                    if self.get_task(*id).is_none() {
+                        anyhow::bail!("Task #{id} doesn't exist");
+                    }
+                    self.delete_task(*id);
+                }
+            },
+            BatchOperation::SetStatus { ids, status } => {
+                for id in ids {
+                    self.update_task(*id, |task| task.status = *status)?;
+                }
+            },
+            BatchOperation::AddTag { ids, tag } => {
+                for id in ids {
+                    self.update_task(*id, |task| task.add_tag(tag))?;
+                }
+            },
+            BatchOperation::RemoveTag { ids, tag } => {
+                for id in ids {
+                    self.update_task(*id, |task| task.remove_tag(tag))?;
+                }
+            },
+            BatchOperation::Move { ids, parent } => {
+                for id in ids {
+                    self.move_task(*id, *parent)?;
+                }
+            },
+            BatchOperation::Nudge { ids, importance, urgency } => {
+                for id in ids {
+                    self.update_task(*id, |task| {
+                        task.importance += importance;
+                        task.urgency += urgency;
+                    })?;
+                }
+            },
+            BatchOperation::CheckVersion { id, version } => self.check_version(*id, *version)?,
+            BatchOperation::Update { task } => self.update_task_fields(*task.clone())?
+        }
+        Ok(())
+    }
+
+    // Applies every operation in order or none of them, as they run on a copy that's only
+    // swapped in once they all succeed
+    pub fn apply_batch(&mut self, operations: &[BatchOperation]) -> anyhow::Result<()> {
+        let mut staged = self.clone();
+        for (idx, operation) in operations.iter().enumerate() {
+            match staged.apply_operation(operation) {
+                // Kept apart, as stale versions are worth retrying unlike the other failures
+                Err(err) if err.is::<VersionConflict>() => {
+                    return Err(VersionConflict(format!("Operation #{} failed: {err}", idx + 1)).into());
+                },
+                Err(err) => anyhow::bail!("Operation #{} failed: {err}", idx + 1),
+                Ok(_) => ()
+            }
+        }
+        *self = staged;
+        Ok(())
+    }
+
+    // Moves the task along with its subtasks under another parent, or to the top level with `None`
+    pub fn move_task(&mut self, id: u32, new_parent: Option<u32>) -> anyhow::Result<()> {
+        let Some(task) = self.get_task(id).cloned() else {
+            anyhow::bail!("Task #{id} doesn't exist");
+        };
+        if let Some(parent_id) = new_parent {
+            if parent_id == id || task.get_subtask(parent_id).is_some() {
+                anyhow::bail!("Task #{id} can't be moved under itself or its subtasks");
+            }
+            if self.get_task(parent_id).is_none() {
+                anyhow::bail!("Task #{parent_id} doesn't exist");
+            }
+        }
+        // Bumped before it's deleted, as its stored version is gone after
+        let mut task = Box::new(task);
+        task.version = self.get_next_version(id);
+        self.delete_task(id);
+        match new_parent {
+            Some(parent_id) => {
+                let Some(mut parent) = self.get_task(parent_id).cloned().map(Box::new) else {
+                    anyhow::bail!("Task #{parent_id} doesn't exist");
+                };
+                parent.version = self.get_next_version(parent_id);
+                parent.add_subtask(task);
+                self.restore_task(parent);
+            },
+            None => self.restore_task(task)
+        }
+        Ok(())
+    }
+
+    // For writes based on a given version of the task, which someone else may have replaced since
+    pub fn check_version(&self, id: u32, version: u32) -> anyhow::Result<()> {
+        let Some(task) = self.get_task(id) else {
+            anyhow::bail!("Task #{id} doesn't exist");
+        };
+        if task.version != version {
+            return Err(VersionConflict(format!("Task #{id} is at version {}, not {version}", task.version)).into());
+        }
+        Ok(())
+    }
+
+    // Fails once the largest id is taken, instead of wrapping around onto an existing task
+    pub fn get_next_id(&self) -> anyhow::Result<u32> {
+        let Some(max_id) = self.get_all_tasks().into_iter().map(|task| task.id).max() else {
+            return Ok(1);
+        };
+        max_id.checked_add(1).ok_or_else(|| anyhow::anyhow!("There are no task ids left"))
+    }
+
+    pub fn get_templates(&self) -> Vec<&Template> {
+        self.templates.values().collect()
+    }
+
+    pub fn get_template(&self, name: &str) -> Option<&Template> {
+        self.templates.get(name)
+    }
+
+    // Templates are named uniquely, so this replaces any template with the same name
+    pub fn add_template(&mut self, template: Template) -> anyhow::Result<()> {
+        if template.name.is_empty() {
+            anyhow::bail!("Templates need a name");
+        }
+        self.templates.insert(template.name.clone(), template);
+        Ok(())
+    }
+
+    pub fn delete_template(&mut self, name: &str) -> anyhow::Result<()> {
+        match self.templates.remove(name) {
+            Some(_) => Ok(()),
+            None => anyhow::bail!("Template \"{name}\" doesn't exist")
+        }
+    }
+
+    // Inserts a fresh copy of the template under the parent, or at the top level with `None`.
+    // Returns the id of the new top task.
+    pub fn instantiate_template(&mut self, name: &str, values: &HashMap<String, String>, parent: Option<u32>) -> anyhow::Result<u32> {
+        let Some(template) = self.templates.get(name) else {
+            anyhow::bail!("Template \"{name}\" doesn't exist");
+        };
+        let task = template.instantiate(values, self.get_next_id()?)?;
+        let id = task.id;
+        match parent {
+            Some(parent_id) => self.add_subtask(parent_id, Box::new(task))?,
+            None => self.add_task(Box::new(task))
+        }
+        Ok(id)
+    }
+
+    // Pending tasks that aren't snoozed, in the quadrant given by the thresholds
+    pub fn get_quadrant_tasks(&self, quadrant: Quadrant) -> Vec<&Task> {
+        self.get_all_tasks()
+            .into_iter()
+            .filter(|task| Oswald::is_plannable(task))
+            .filter(|task| task.get_quadrant_with(&self.quadrant_thresholds) == quadrant)
+            .collect()
+    }
+
+    // Every quadrant is there, even the empty ones
+    pub fn get_quadrant_counts(&self) -> BTreeMap<Quadrant, usize> {
+        let mut counts: BTreeMap<Quadrant, usize> = Quadrant::ALL.into_iter().map(|quadrant| (quadrant, 0)).collect();
+        for task in self.get_all_tasks().into_iter().filter(|task| Oswald::is_plannable(task)) {
+            *counts.entry(task.get_quadrant_with(&self.quadrant_thresholds)).or_default() += 1;
+        }
+        counts
+    }
+
+    // The quadrant's action for each pending task. Tasks already on the calendar don't need
+    // scheduling, and tasks with pending subtasks aren't archive candidates.
+    pub fn get_suggestions(&self) -> Vec<Suggestion> {
+        Quadrant::ALL.into_iter()
+            .flat_map(|quadrant| self.get_quadrant_tasks(quadrant)
+                .into_iter()
+                .filter(move |task| match quadrant.get_suggested_action() {
+                    SuggestedAction::Schedule => task.due_date.is_none() && task.placement.is_none(),
+                    SuggestedAction::Archive => !task.get_all_subtasks().into_iter().any(Oswald::is_plannable),
+                    _ => true
+                })
+                .map(move |task| Suggestion { task_id: task.id, quadrant, action: quadrant.get_suggested_action() }))
+            .collect()
+    }
+
+    pub fn get_people(&self) -> Vec<&Person> {
+        self.people.values().collect()
+    }
+
+    pub fn get_person(&self, id: u32) -> Option<&Person> {
+        self.people.get(&id)
+    }
+
+    // Replaces the person with the same id, if any
+    pub fn add_person(&mut self, person: Person) -> anyhow::Result<()> {
+        if person.name.is_empty() {
+            anyhow::bail!("People need a name");
+        }
+        self.people.insert(person.id, person);
+        Ok(())
+    }
+
+    // Their tasks are handed back to us
+    pub fn delete_person(&mut self, id: u32) -> anyhow::Result<()> {
+        if self.people.remove(&id).is_none() {
+            anyhow::bail!("Person #{id} doesn't exist");
+        }
+        let assigned_ids: Vec<u32> = self.get_all_tasks()
+            .into_iter()
+            .filter(|task| task.assignee == Some(id))
+            .map(|task| task.id)
+            .collect();
+        for task_id in assigned_ids {
+            self.update_task(task_id, |task| {
+                task.assignee = None;
+                task.follow_up = None;
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn get_next_person_id(&self) -> anyhow::Result<u32> {
+        let Some(max_id) = self.people.keys().max() else {
+            return Ok(1);
+        };
+        max_id.checked_add(1).ok_or_else(|| anyhow::anyhow!("There are no person ids left"))
+    }
+
+    // `None` takes the task back, dropping the follow-up as well
+    pub fn assign_task(&mut self, id: u32, assignee: Option<u32>, follow_up: Option<NaiveDate>) -> anyhow::Result<()> {
+        if let Some(person_id) = assignee.filter(|person_id| !self.people.contains_key(person_id)) {
+            anyhow::bail!("Person #{person_id} doesn't exist");
+        }
+        self.update_task(id, |task| {
+            task.assignee = assignee;
+            task.follow_up = assignee.and(follow_up);
+        })
+    }
+
+    // Pending tasks assigned to the person, earliest follow-ups first
+    pub fn get_waiting_for(&self, person_id: u32) -> Vec<&Task> {
+        let mut tasks: Vec<&Task> = self.get_all_tasks()
+            .into_iter()
+            .filter(|task| task.is_waiting() && task.assignee == Some(person_id))
+            .collect();
+        sort_by_follow_up(&mut tasks);
+        tasks
+    }
+
+    // What everybody owes us, leaving out the people who are all caught up
+    pub fn get_all_waiting_for(&self) -> Vec<WaitingFor<'_>> {
+        self.people.values()
+            .map(|person| WaitingFor { person, tasks: self.get_waiting_for(person.id) })
+            .filter(|waiting_for| !waiting_for.tasks.is_empty())
+            .collect()
+    }
+
+    // Waiting tasks whose follow-up date has come
+    pub fn get_follow_ups(&self, today: NaiveDate) -> Vec<&Task> {
+        let mut tasks: Vec<&Task> = self.get_all_tasks()
+            .into_iter()
+            .filter(|task| task.is_waiting() && task.follow_up.is_some_and(|follow_up| follow_up <= today))
+            .collect();
+        sort_by_follow_up(&mut tasks);
+        tasks
+    }
+
+    // The top-level task the task belongs to, which is itself for top-level tasks
+    pub fn get_project_id(&self, task_id: u32) -> Option<u32> {
+        let path = self.get_task_path(task_id)?;
+        Some(path.first().copied().unwrap_or(task_id))
+    }
+
+    pub fn get_role(&self, user_id: u32, task_id: u32) -> Option<Role> {
+        let project_id = self.get_project_id(task_id)?;
+        self.members.get(&project_id)?.get(&user_id).copied()
+    }
+
+    // Tasks outside the user's projects are reported as missing, so they don't give anything away
+    pub fn check_role(&self, user_id: u32, task_id: u32, role: Role) -> anyhow::Result<()> {
+        match self.get_role(user_id, task_id) {
+            Some(user_role) if user_role >= role => Ok(()),
+            Some(_) => anyhow::bail!("Task #{task_id} needs the {role} role"),
+            None => anyhow::bail!("Task #{task_id} doesn't exist")
+        }
+    }
+
+    // The projects shared with the user, and their role in each
+    pub fn get_projects(&self, user_id: u32) -> Vec<(&Task, Role)> {
+        self.get_tasks()
+            .into_iter()
+            .filter_map(|task| Some((task, self.get_role(user_id, task.id)?)))
+            .collect()
+    }
+
+    pub fn get_members(&self, project_id: u32) -> Vec<Membership> {
+        self.members.get(&project_id)
+            .into_iter()
+            .flatten()
+            .map(|(user_id, role)| Membership { project_id, user_id: *user_id, role: *role })
+            .collect()
+    }
+
+    fn get_all_memberships(&self) -> Vec<Membership> {
+        self.members.keys().flat_map(|project_id| self.get_members(*project_id)).collect()
+    }
+
+    // New top-level task, owned by the user creating it
+    pub fn add_project(&mut self, user_id: u32, desc: &str) -> anyhow::Result<u32> {
+        let desc = desc.trim();
+        if desc.is_empty() {
+            anyhow::bail!("Projects need a description");
+        }
+        let mut task = Task::new_with_id(self.get_next_id()?);
+        task.desc = desc.to_owned();
+        let id = task.id;
+        self.add_task(Box::new(task));
+        self.members.entry(id).or_default().insert(user_id, Role::Owner);
+        Ok(id)
+    }
+
+    // `None` removes the user from the project. Projects always keep at least one owner.
+    pub fn share_project(&mut self, acting_user_id: u32, project_id: u32, user_id: u32, role: Option<Role>) -> anyhow::Result<()> {
+        self.check_role(acting_user_id, project_id, Role::Owner)?;
+        if self.get_project_id(project_id) != Some(project_id) {
+            anyhow::bail!("Only top-level tasks can be shared");
+        }
+        let members = self.members.entry(project_id).or_default();
+        let other_owners = members.iter().filter(|(id, role)| **id != user_id && **role == Role::Owner).count();
+        if role != Some(Role::Owner) && members.get(&user_id) == Some(&Role::Owner) && other_owners == 0 {
+            anyhow::bail!("Projects need an owner");
+        }
+        match role {
+            Some(role) => { members.insert(user_id, role); },
+            None => { members.remove(&user_id); }
+        }
+        Ok(())
+    }
+
+    // Tasks are added one at a time, with the next free id
+    pub fn add_task_as(&mut self, user_id: u32, parent_id: u32, mut task: Task) -> anyhow::Result<u32> {
+        self.check_role(user_id, parent_id, Role::Editor)?;
+        if !task.get_subtasks().is_empty() {
+            anyhow::bail!("Subtasks have to be added one at a time");
+        }
+        task.id = self.get_next_id()?;
+        let id = task.id;
+        self.add_subtask(parent_id, Box::new(task))?;
+        Ok(id)
+    }
+
+    // Updates the task's own fields, keeping the stored subtasks
+    pub fn update_task_as(&mut self, user_id: u32, task: Task) -> anyhow::Result<()> {
+        self.check_role(user_id, task.id, Role::Editor)?;
+        self.update_task_fields(task)
+    }
+
+    fn update_task_fields(&mut self, mut task: Task) -> anyhow::Result<()> {
+        let subtask_ids: Vec<u32> = task.get_subtasks().into_iter().map(|subtask| subtask.id).collect();
+        subtask_ids.into_iter().for_each(|id| task.delete_subtask(id));
+        self.update_task(task.id, |stored| {
+            for subtask in stored.get_subtasks() {
+                task.add_subtask(Box::new(subtask.clone()));
+            }
+            *stored = task;
+        })
+    }
+
+    // Deleting the whole project takes an owner
+    pub fn delete_task_as(&mut self, user_id: u32, id: u32) -> anyhow::Result<()> {
+        let is_project = self.get_project_id(id) == Some(id);
+        self.check_role(user_id, id, if is_project { Role::Owner } else { Role::Editor })?;
+        self.delete_task(id);
+        if is_project {
+            self.members.remove(&id);
+        }
+        Ok(())
+    }
+
+    pub fn get_daily_plan(&self) -> Option<&DailyPlan> {
+        self.daily_plan.as_ref()
+    }
+
+    pub fn set_daily_plan(&mut self, plan: DailyPlan) {
+        self.daily_plan = Some(plan);
+    }
+
+    fn is_plannable(task: &Task) -> bool {
+        matches!(task.status, TaskStatus::Open | TaskStatus::Blocked) && !task.is_deferred()
+    }
+
+    // `pick` chooses the day's task ids out of the plannable tasks, in the order they come
+    fn plan_day(&mut self, today: NaiveDate, carried_over_ids: Vec<u32>, pick: impl FnOnce(&[&Task]) -> Vec<u32>) {
+        let mut candidates: Vec<&Task> = carried_over_ids.iter()
+            .filter_map(|id| self.get_task(*id))
+            .filter(|task| Oswald::is_plannable(task))
+            .collect();
+        candidates.extend(self.get_all_tasks()
+            .into_iter()
+            .filter(|task| Oswald::is_plannable(task) && !carried_over_ids.contains(&task.id)));
+        let task_ids = pick(&candidates);
+        self.daily_plan = Some(DailyPlan::new(today, task_ids));
+    }
+
+    // Plans the day on its first open, putting the unfinished tasks of the last plan first.
+    // Returns whether a new plan was made.
+    pub fn open_day(&mut self, today: NaiveDate, pick: impl FnOnce(&[&Task]) -> Vec<u32>) -> bool {
+        if self.daily_plan.as_ref().is_some_and(|plan| plan.date == today) {
+            return false;
+        }
+        let carried_over_ids = self.daily_plan.take()
+            .map(|plan| plan.task_ids)
+            .unwrap_or_default();
+        self.plan_day(today, carried_over_ids, pick);
+        true
+    }
+
+    // Throws away today's plan and starts over from the current ordering
+    pub fn replan_day(&mut self, today: NaiveDate, pick: impl FnOnce(&[&Task]) -> Vec<u32>) {
+        self.plan_day(today, vec![], pick);
+    }
+
+    // Plans the day on its first open using the planning settings, for clients without a
+    // completion history of their own
+    pub fn open_day_with(&mut self, settings: &PlanningSettings, today: NaiveDate) -> bool {
+        self.open_day(today, |tasks| pick_today(tasks, settings, today, 0))
+    }
+
+    // Visible tasks, i.e. all but the snoozed ones, split into the Overview columns
+    pub fn get_overview(&self, settings: &PlanningSettings, completed_ids: &HashSet<u32>, today: NaiveDate) -> Overview<'_> {
+        let tasks: Vec<&Task> = self.get_all_tasks()
+            .into_iter()
+            .filter(|task| !task.is_deferred())
+            .collect();
+        plan_overview(&tasks, completed_ids, self.daily_plan.as_ref(), settings, today)
+    }
+
+    pub fn get_time_entries(&self) -> &[TimeEntry] {
+        &self.time_entries
+    }
+
+    pub fn add_time_entry(&mut self, entry: TimeEntry) {
+        self.time_entries.push(entry);
+    }
+
+    pub fn get_running_timer(&self) -> Option<&TimeEntry> {
+        self.time_entries.iter().find(|entry| entry.is_running())
+    }
+
+    // Only one timer runs at a time, so starting one stops the current one
+    pub fn start_timer(&mut self, id: u32, note: &str, now: DateTime<Utc>) -> anyhow::Result<()> {
+        if self.get_task(id).is_none() {
+            anyhow::bail!("Task #{id} doesn't exist");
+        }
+        if let Some(running_id) = self.get_running_timer().map(|entry| entry.task_id) {
+            self.stop_timer(running_id, now)?;
+        }
+        self.time_entries.push(TimeEntry::new(id, now, note));
+        Ok(())
+    }
+
+    pub fn stop_timer(&mut self, id: u32, now: DateTime<Utc>) -> anyhow::Result<()> {
+        let entry = self.time_entries.iter_mut()
+            .find(|entry| entry.task_id == id && entry.is_running())
+            .ok_or_else(|| anyhow::anyhow!("Task #{id} has no running timer"))?;
+        entry.stop = Some(now.max(entry.start));
+        Ok(())
+    }
+
+    // Time tracked on the task and all of its subtasks
+    pub fn get_tracked_time(&self, id: u32, now: DateTime<Utc>) -> Duration {
+        let Some(task) = self.get_task(id) else {
+            return Duration::zero();
+        };
+        let mut ids: HashSet<u32> = task.get_all_subtasks().into_iter().map(|subtask| subtask.id).collect();
+        ids.insert(id);
+        self.time_entries.iter()
+            .filter(|entry| ids.contains(&entry.task_id))
+            .fold(Duration::zero(), |total, entry| total + entry.get_duration(now))
+    }
+
+    // TODO: Use status type design pattern in the future
+    pub async fn load(&mut self) -> anyhow::Result<()> {
+        let tasks = self.data_store.read().await?;
+        for task in tasks.into_iter() {
+            self.restore_task(task)
+        }
+        self.time_entries = self.data_store.read_time_entries().await?;
+        self.daily_plan = self.data_store.read_daily_plan().await?;
+        for template in self.data_store.read_templates().await? {
+            self.templates.insert(template.name.clone(), template);
+        }
+        for person in self.data_store.read_people().await? {
+            self.people.insert(person.id, person);
+        }
+        for membership in self.data_store.read_memberships().await? {
+            self.members.entry(membership.project_id).or_default().insert(membership.user_id, membership.role);
+        }
+        self.sync_base = SyncBase::from_synced_tasks(self.data_store.read_sync_base().await?);
+        if let Some(settings) = self.data_store.read_planning_settings().await? {
+            self.planning_settings = settings;
+        }
+
+        Ok(())
+    }
+
+    // Starts over from what's stored, e.g. once a write was turned down for being based on
+    // outdated tasks (see `VersionConflict`). Recorded as a reset, and left as it was if the
+    // store can't be read.
+    pub async fn reload(&mut self) -> anyhow::Result<()> {
+        let mut reloaded = self.clone();
+        reloaded.clear();
+        reloaded.load().await?;
+        reloaded.events = self.events.as_ref().map(|_| vec![
+            TaskEvent::Reset { tasks: reloaded.get_tasks().into_iter().cloned().collect() }
+        ]);
+        *self = reloaded;
+        Ok(())
+    }
+
+    pub async fn save(&self) -> anyhow::Result<()> {
+        let tasks = self.get_tasks();
+        self.data_store.write(tasks).await?;
+        self.data_store.write_time_entries(&self.time_entries).await?;
+        if let Some(plan) = &self.daily_plan {
+            self.data_store.write_daily_plan(plan).await?;
+        }
+        self.data_store.write_templates(&self.get_templates()).await?;
+        self.data_store.write_people(&self.get_people()).await?;
+        self.data_store.write_memberships(&self.get_all_memberships()).await?;
+        self.data_store.write_sync_base(&self.sync_base.get_synced_tasks()).await?;
+        self.data_store.write_planning_settings(&self.planning_settings).await?;
+        Ok(())
+    }
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod oswald_tests {
+    use super::{
+        BatchOperation,
+        ComplexityMode,
+        DailyPlan,
+        Oswald,
+        Placement,
+        Quadrant,
+        QuadrantThresholds,
+        SuggestedAction,
+        Task,
+        TaskEvent,
+        TaskStatus,
+        parse_tags
+    };
+    use std::collections::{HashMap, HashSet};
+    use chrono::{DateTime, Duration, NaiveDate};
+    use crate::core::delegation::Person;
+    use crate::core::planning::PlanningSettings;
+    use crate::core::quick_add::QuickAdd;
+    use crate::core::sharing::Role;
+    use crate::core::templates::Template;
+    use crate::ports::MockDataStore;
+
+    #[tokio::test]
+    async fn test_load_all_tasks_from_data_store() {
+        let mut oswald = Oswald::new(MockDataStore);
+
+        let _ = oswald.load().await;
+
+        assert!(oswald.root.subtasks_map.contains_key(&0));
+        assert!(oswald.root.subtasks_map.contains_key(&1));
+        assert!(oswald.root.subtasks_map.contains_key(&2));
+
+        assert!(oswald
+            .root
+            .subtasks_map
+            .get(&0)
+            .unwrap()
+            .subtasks_map
+            .contains_key(&3));
+        assert!(oswald
+            .root
+            .subtasks_map
+            .get(&2)
+            .unwrap()
+            .subtasks_map
+            .contains_key(&4));
+        assert!(oswald
+            .root
+            .subtasks_map
+            .get(&2)
+            .unwrap()
+            .subtasks_map
+            .contains_key(&5));
+    }
+
+    #[test]
+    fn test_add_task() {
+        let mut oswald = Oswald::new(MockDataStore);
+        let task = Box::new(Task::new_with_id(1));
+
+        oswald.add_task(task);
+
+        assert!(oswald.root.subtasks_map.contains_key(&1));
+    }
+
+    #[test]
+    fn test_delete_task_by_id() {
+        /*
+         *           (t)
+         *          /   \
+         *       (sA)   (sB)
+         *             /   \
+         *           (sC) (sD)
+         *                 |
+         *                (sE)
+         */
+        let mut oswald = Oswald::new(MockDataStore);
         // Level 1
         let subtask_a = Box::new(Task::new_with_id(1));
         let mut subtask_b = Box::new(Task::new_with_id(2));
@@ -959,6 +2320,706 @@ mod oswald_tests {
         assert!(oswald.root.subtasks_map.is_empty());
     }
 
+    #[test]
+    fn test_get_tasks_with_tags() {
+        let mut oswald = Oswald::new(MockDataStore);
+        let mut task_a = Box::new(Task::new_with_id(1));
+        task_a.add_tag("work");
+        let mut subtask = Box::new(Task::new_with_id(2));
+        subtask.add_tag("work");
+        subtask.add_tag("home");
+        task_a.add_subtask(subtask);
+        let mut task_b = Box::new(Task::new_with_id(3));
+        task_b.add_tag("home");
+
+        oswald.add_task(task_a);
+        oswald.add_task(task_b);
+
+        let top_ids: Vec<u32> = oswald.get_tasks_with_tags(&parse_tags("home"))
+            .into_iter()
+            .map(|task| task.id)
+            .collect();
+        assert_eq!(top_ids, vec![3]);
+
+        let mut all_ids: Vec<u32> = oswald.get_all_tasks_with_tags(&parse_tags("home"))
+            .into_iter()
+            .map(|task| task.id)
+            .collect();
+        all_ids.sort();
+        assert_eq!(all_ids, vec![2, 3]);
+
+        assert_eq!(oswald.get_all_tasks_with_tags(&parse_tags("work home")).len(), 1);
+        assert_eq!(oswald.get_all_tasks_with_tags(&parse_tags("")).len(), 3);
+    }
+
+    #[test]
+    fn test_get_tasks_by_complexity_mode() {
+        let mut oswald = Oswald::new(MockDataStore);
+        let mut task_a = Box::new(Task::new_with_id(1));
+        task_a.estimate = Some(120);
+        let mut task_b = Box::new(Task::new_with_id(2));
+        task_b.add_subtask(Box::new(Task::new_with_id(3)));
+        oswald.add_task(task_a);
+        oswald.add_task(task_b);
+
+        let ids: Vec<u32> = oswald.get_tasks().into_iter().map(|task| task.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+
+        oswald.set_complexity_mode(ComplexityMode::Effort { default_estimate: 30 });
+        let ids: Vec<u32> = oswald.get_tasks().into_iter().map(|task| task.id).collect();
+        assert_eq!(ids, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_start_and_stop_timer() {
+        let mut oswald = Oswald::new(MockDataStore);
+        oswald.add_task(Box::new(Task::new_with_id(1)));
+        let start = DateTime::from_timestamp(0, 0).unwrap();
+
+        assert!(oswald.start_timer(2, "", start).is_err(), "Expected unknown tasks to be rejected");
+        assert!(oswald.stop_timer(1, start).is_err(), "Expected no running timer");
+
+        oswald.start_timer(1, "Drafting", start).unwrap();
+        assert_eq!(oswald.get_running_timer().expect("Expected a running timer").task_id, 1);
+
+        oswald.stop_timer(1, start + Duration::minutes(25)).unwrap();
+        assert!(oswald.get_running_timer().is_none());
+        assert_eq!(oswald.get_time_entries().len(), 1);
+        assert_eq!(oswald.get_time_entries()[0].note, "Drafting");
+    }
+
+    #[test]
+    fn test_starting_timer_stops_running_one() {
+        let mut oswald = Oswald::new(MockDataStore);
+        oswald.add_task(Box::new(Task::new_with_id(1)));
+        oswald.add_task(Box::new(Task::new_with_id(2)));
+        let start = DateTime::from_timestamp(0, 0).unwrap();
+
+        oswald.start_timer(1, "", start).unwrap();
+        oswald.start_timer(2, "", start + Duration::minutes(10)).unwrap();
+
+        let entries = oswald.get_time_entries();
+        assert_eq!(entries[0].stop, Some(start + Duration::minutes(10)));
+        assert_eq!(oswald.get_running_timer().expect("Expected a running timer").task_id, 2);
+    }
+
+    #[test]
+    fn test_tracked_time_rolls_up_subtasks() {
+        /*
+         *     (tA)     (tB)
+         *    /    \
+         *  (sA)  (sB)
+         */
+        let mut oswald = Oswald::new(MockDataStore);
+        let mut task_a = Box::new(Task::new_with_id(1));
+        task_a.add_subtask(Box::new(Task::new_with_id(2)));
+        task_a.add_subtask(Box::new(Task::new_with_id(3)));
+        oswald.add_task(task_a);
+        oswald.add_task(Box::new(Task::new_with_id(4)));
+        let start = DateTime::from_timestamp(0, 0).unwrap();
+
+        oswald.start_timer(1, "", start).unwrap();
+        oswald.start_timer(2, "", start + Duration::minutes(5)).unwrap();
+        oswald.start_timer(4, "", start + Duration::minutes(15)).unwrap();
+        oswald.start_timer(3, "", start + Duration::minutes(45)).unwrap();
+        let now = start + Duration::minutes(50);
+
+        assert_eq!(oswald.get_tracked_time(1, now), Duration::minutes(20));
+        assert_eq!(oswald.get_tracked_time(3, now), Duration::minutes(5));
+        assert_eq!(oswald.get_tracked_time(4, now), Duration::minutes(30));
+        assert_eq!(oswald.get_tracked_time(5, now), Duration::zero());
+    }
+
+    #[test]
+    fn test_snooze_and_resurface_tasks() {
+        /*
+         *     (tA)     (tB)
+         *      |
+         *     (sA)
+         */
+        let mut oswald = Oswald::new(MockDataStore);
+        let mut task_a = Box::new(Task::new_with_id(1));
+        task_a.add_subtask(Box::new(Task::new_with_id(2)));
+        oswald.add_task(task_a);
+        oswald.add_task(Box::new(Task::new_with_id(3)));
+        let today = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        let tomorrow = today.succ_opt().unwrap();
+        let next_week = today + Duration::days(7);
+
+        assert!(oswald.snooze_task(2, today, today).is_err(), "Expected past dates to be rejected");
+        assert!(oswald.snooze_task(9, tomorrow, today).is_err(), "Expected unknown tasks to be rejected");
+
+        oswald.snooze_task(2, tomorrow, today).unwrap();
+        oswald.snooze_task(3, next_week, today).unwrap();
+        assert!(oswald.get_task(2).expect("Expected Subtask A").is_deferred());
+        assert!(oswald.resurface_tasks(today).is_empty());
+
+        assert_eq!(oswald.resurface_tasks(tomorrow), vec![2]);
+        assert!(!oswald.get_task(2).expect("Expected Subtask A").is_deferred());
+        assert!(oswald.get_task(3).expect("Expected Task B").is_deferred());
+
+        oswald.unsnooze_task(3).unwrap();
+        assert!(!oswald.get_task(3).expect("Expected Task B").is_deferred());
+    }
+
+    #[test]
+    fn test_place_task() {
+        let mut oswald = Oswald::new(MockDataStore);
+        let mut task = Box::new(Task::new_with_id(1));
+        task.add_subtask(Box::new(Task::new_with_id(2)));
+        oswald.add_task(task);
+        let placement = Placement::Day(NaiveDate::from_ymd_opt(2024, 8, 1).unwrap());
+
+        oswald.place_task(2, Some(placement)).unwrap();
+        assert_eq!(oswald.get_task(2).expect("Expected Subtask #2").placement, Some(placement));
+
+        oswald.place_task(2, None).unwrap();
+        assert_eq!(oswald.get_task(2).expect("Expected Subtask #2").placement, None);
+        assert!(oswald.place_task(9, None).is_err());
+    }
+
+    #[test]
+    fn test_move_task() {
+        /*
+         *      (1)        (4)
+         *     /   \
+         *   (2)   (3)
+         */
+        let mut oswald = Oswald::new(MockDataStore);
+        let mut task = Box::new(Task::new_with_id(1));
+        task.add_subtask(Box::new(Task::new_with_id(2)));
+        task.add_subtask(Box::new(Task::new_with_id(3)));
+        oswald.add_task(task);
+        oswald.add_task(Box::new(Task::new_with_id(4)));
+
+        // Subtrees move as a whole, with the moved task and its new parent bumped
+        oswald.move_task(1, Some(4)).unwrap();
+        assert_eq!(oswald.get_task_path(3), Some(vec![4, 1]));
+        let versions = |oswald: &Oswald| [1, 2, 3, 4].map(|id| oswald.get_task(id).unwrap().version);
+        assert_eq!(versions(&oswald), [2, 1, 1, 2]);
+
+        oswald.move_task(3, None).unwrap();
+        assert_eq!(oswald.get_task_path(3), Some(vec![]));
+        assert_eq!(versions(&oswald), [2, 1, 2, 2]);
+        assert_eq!(oswald.get_task(1).expect("Expected Task #1").get_subtasks().len(), 1);
+        assert_eq!(oswald.get_all_tasks().len(), 4);
+    }
+
+    #[test]
+    fn test_move_task_prevents_cycles() {
+        let mut oswald = Oswald::new(MockDataStore);
+        let mut task = Box::new(Task::new_with_id(1));
+        let mut subtask = Box::new(Task::new_with_id(2));
+        subtask.add_subtask(Box::new(Task::new_with_id(3)));
+        task.add_subtask(subtask);
+        oswald.add_task(task);
+
+        assert!(oswald.move_task(1, Some(1)).is_err());
+        assert!(oswald.move_task(1, Some(3)).is_err());
+        assert!(oswald.move_task(1, Some(9)).is_err());
+        assert!(oswald.move_task(9, None).is_err());
+
+        // Nothing was lost along the way
+        assert_eq!(oswald.get_task_path(3), Some(vec![1, 2]));
+        assert_eq!(oswald.get_all_tasks().len(), 3);
+    }
+
+    #[test]
+    fn test_task_versions() {
+        let mut oswald = Oswald::new(MockDataStore);
+        oswald.add_task(Box::new(Task::new_with_id(1)));
+        oswald.place_task(1, Some(Placement::Backlog)).unwrap();
+        assert_eq!(oswald.get_task(1).unwrap().version, 2);
+        assert!(oswald.check_version(1, 2).is_ok());
+        assert!(oswald.check_version(1, 1).is_err());
+        assert!(oswald.check_version(9, 0).is_err());
+
+        // Writes can't pick their own version, but restored tasks keep theirs
+        let mut task = Task::new_with_id(1);
+        task.version = 7;
+        oswald.add_task(Box::new(task.clone()));
+        assert_eq!(oswald.get_task(1).unwrap().version, 3);
+        oswald.restore_task(Box::new(task));
+        assert_eq!(oswald.get_task(1).unwrap().version, 7);
+    }
+
+    #[test]
+    fn test_subtask_versions() {
+        let new_tree = |desc: &str, version: u32, is_nested: bool| {
+            let mut task = Box::new(Task::new_with_id(1));
+            let mut subtask = Box::new(Task::new_with_id(2));
+            subtask.desc = desc.to_owned();
+            subtask.version = version;
+            let other_subtask = Box::new(Task::new_with_id(3));
+            match is_nested {
+                true => subtask.add_subtask(other_subtask),
+                false => task.add_subtask(other_subtask)
+            }
+            task.add_subtask(subtask);
+            task
+        };
+        let mut oswald = Oswald::new(MockDataStore);
+        oswald.add_task(new_tree("Draft", 5, false));
+        assert_eq!(oswald.get_task(2).unwrap().version, 1);
+
+        // Only the changed subtasks get bumped, whatever version they came with
+        oswald.add_task(new_tree("Renamed", 0, false));
+        assert_eq!(oswald.get_task(2).unwrap().version, 2);
+        assert_eq!(oswald.get_task(3).unwrap().version, 1);
+
+        // As do the ones moved around within the tree
+        oswald.add_task(new_tree("Renamed", 9, true));
+        assert_eq!(oswald.get_task(2).unwrap().version, 2);
+        assert_eq!(oswald.get_task(3).unwrap().version, 2);
+    }
+
+    #[test]
+    fn test_record_and_apply_events() {
+        let mut oswald = Oswald::new(MockDataStore);
+        oswald.add_task(Box::new(Task::new_with_id(1)));
+        oswald.record_events();
+        oswald.add_task(Box::new(Task::new_with_id(2)));
+        oswald.add_subtask(2, Box::new(Task::new_with_id(3))).unwrap();
+        oswald.move_task(3, Some(1)).unwrap();
+        oswald.place_task(3, Some(Placement::Backlog)).unwrap();
+        oswald.delete_task(2);
+        oswald.delete_task(42);
+
+        let events = oswald.take_events();
+        assert_eq!(events.last(), Some(&TaskEvent::Deleted { id: 2 }));
+        assert!(oswald.take_events().is_empty());
+
+        // A copy of the forest from before catches up by applying the events
+        let mut mirror = Oswald::new(MockDataStore);
+        mirror.add_task(Box::new(Task::new_with_id(1)));
+        for event in events {
+            mirror.apply_event(event);
+        }
+        assert!(mirror.get_task(2).is_none());
+        assert_eq!(mirror.get_task_path(3), Some(vec![1]));
+        assert_eq!(mirror.get_task(3).unwrap().placement, Some(Placement::Backlog));
+
+        // Resetting drops the tasks the mirror had on its own
+        mirror.add_task(Box::new(Task::new_with_id(7)));
+        mirror.apply_event(TaskEvent::Reset { tasks: oswald.get_tasks().into_iter().cloned().collect() });
+        assert!(mirror.get_task(7).is_none());
+        assert_eq!(mirror.get_task_path(3), Some(vec![1]));
+    }
+
+    #[test]
+    fn test_apply_batch() {
+        let mut oswald = Oswald::new(MockDataStore);
+        for id in 1..=3 {
+            oswald.add_task(Box::new(Task::new_with_id(id)));
+        }
+        let operations = vec![
+            BatchOperation::Upsert { task: Box::new(Task::new_with_id(4)) },
+            BatchOperation::SetStatus { ids: vec![1, 2], status: TaskStatus::Done },
+            BatchOperation::AddTag { ids: vec![1, 3], tag: "work".to_owned() },
+            BatchOperation::RemoveTag { ids: vec![3], tag: "work".to_owned() },
+            BatchOperation::Move { ids: vec![2, 3], parent: Some(4) },
+            BatchOperation::Nudge { ids: vec![3], importance: 5.0, urgency: -5.0 },
+            BatchOperation::Delete { ids: vec![1] },
+        ];
+
+        oswald.apply_batch(&operations).unwrap();
+        assert!(oswald.get_task(1).is_none());
+        assert_eq!(oswald.get_task(2).expect("Expected Task #2").status, TaskStatus::Done);
+        assert_eq!(oswald.get_task_path(3), Some(vec![4]));
+        let task = oswald.get_task(3).expect("Expected Task #3");
+        assert!(task.tags.is_empty());
+        assert_eq!((task.importance, task.urgency), (5.0, -5.0));
+    }
+
+    #[test]
+    fn test_apply_batch_is_atomic() {
+        let mut oswald = Oswald::new(MockDataStore);
+        oswald.add_task(Box::new(Task::new_with_id(1)));
+        let operations = vec![
+            BatchOperation::SetStatus { ids: vec![1], status: TaskStatus::Archived },
+            BatchOperation::Move { ids: vec![1], parent: Some(1) },
+        ];
+
+        let err = oswald.apply_batch(&operations).expect_err("Expected the cycle to fail the batch");
+        assert!(err.to_string().starts_with("Operation #2"));
+        assert_eq!(oswald.get_task(1).expect("Expected Task #1").status, TaskStatus::Open);
+    }
+
+    #[test]
+    fn test_apply_batch_checks_versions() {
+        let mut oswald = Oswald::new(MockDataStore);
+        let mut task = Task::new_with_id(1);
+        task.add_subtask(Box::new(Task::new_with_id(2)));
+        oswald.add_task(Box::new(task));
+        let mut update = Task::new_with_id(1);
+        update.desc = "Renamed".to_owned();
+
+        let operations = vec![
+            BatchOperation::CheckVersion { id: 1, version: 1 },
+            BatchOperation::Update { task: Box::new(update.clone()) },
+        ];
+        oswald.apply_batch(&operations).unwrap();
+        let task = oswald.get_task(1).expect("Expected Task #1");
+        assert_eq!((task.desc.as_str(), task.version), ("Renamed", 2));
+        assert_eq!(oswald.get_task_path(2), Some(vec![1]));
+
+        // The task moved on since version 1
+        assert!(oswald.apply_batch(&operations).is_err());
+        assert!(oswald.apply_batch(&[BatchOperation::Update { task: Box::new(Task::new_with_id(3)) }]).is_err());
+    }
+
+    #[test]
+    fn test_parse_batch_operation() {
+        let raw = r#"[{ "op": "set_status", "ids": [1, 2], "status": "Done" }, { "op": "nudge", "ids": [3], "urgency": 1.5 }]"#;
+        let operations: Vec<BatchOperation> = serde_json::from_str(raw).unwrap();
+        assert_eq!(operations, vec![
+            BatchOperation::SetStatus { ids: vec![1, 2], status: TaskStatus::Done },
+            BatchOperation::Nudge { ids: vec![3], importance: 0.0, urgency: 1.5 },
+        ]);
+    }
+
+    #[test]
+    fn test_instantiate_template() {
+        let mut oswald = Oswald::new(MockDataStore);
+        oswald.add_task(Box::new(Task::new_with_id(7)));
+        let mut task = Task::new_with_id(1);
+        task.desc = "Onboard {{name}}".to_owned();
+        task.add_subtask(Box::new(Task::new_with_id(2)));
+        oswald.add_template(Template::new("Onboarding", task)).unwrap();
+        let values = HashMap::from([("name".to_owned(), "Ada".to_owned())]);
+
+        let id = oswald.instantiate_template("Onboarding", &values, Some(7)).unwrap();
+        assert_eq!(id, 8);
+        assert_eq!(oswald.get_task_path(9), Some(vec![7, 8]));
+        assert_eq!(oswald.get_task(8).expect("Expected Task #8").desc, "Onboard Ada");
+        assert_eq!(oswald.search("ada").len(), 1);
+
+        // Instantiating again doesn't clash with the first copy
+        assert_eq!(oswald.instantiate_template("Onboarding", &values, None).unwrap(), 10);
+        assert!(oswald.instantiate_template("Onboarding", &HashMap::new(), None).is_err());
+        assert!(oswald.instantiate_template("Onboarding", &values, Some(99)).is_err());
+        assert!(oswald.instantiate_template("Offboarding", &values, None).is_err());
+        assert_eq!(oswald.get_all_tasks().len(), 5);
+    }
+
+    #[test]
+    fn test_manage_templates() {
+        let mut oswald = Oswald::new(MockDataStore);
+
+        assert!(oswald.add_template(Template::new("  ", Task::new_with_id(1))).is_err());
+        oswald.add_template(Template::new("Release", Task::new_with_id(1))).unwrap();
+        oswald.add_template(Template::new("Release", Task::new_with_id(2))).unwrap();
+        assert_eq!(oswald.get_templates().len(), 1);
+        assert_eq!(oswald.get_template("Release").expect("Expected the template").task.id, 2);
+
+        oswald.delete_template("Release").unwrap();
+        assert!(oswald.delete_template("Release").is_err());
+    }
+
+    #[test]
+    fn test_open_day_pins_the_plan() {
+        let mut oswald = Oswald::new(MockDataStore);
+        for id in 1..=4 {
+            let mut task = Box::new(Task::new_with_id(id));
+            task.importance = (10 - id) as f32;
+            oswald.add_task(task);
+        }
+        let today = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        let pick_two = |tasks: &[&Task]| tasks.iter().take(2).map(|task| task.id).collect();
+
+        assert!(oswald.open_day(today, pick_two));
+        assert_eq!(oswald.get_daily_plan().expect("Expected a plan").task_ids, vec![1, 2]);
+
+        // Reordering the tasks doesn't change the plan until re-planning
+        let mut task = Box::new(Task::new_with_id(4));
+        task.importance = 100.0;
+        oswald.add_task(task);
+        assert!(!oswald.open_day(today, pick_two));
+        assert_eq!(oswald.get_daily_plan().expect("Expected a plan").task_ids, vec![1, 2]);
+
+        oswald.replan_day(today, pick_two);
+        assert_eq!(oswald.get_daily_plan().expect("Expected a plan").task_ids, vec![4, 1]);
+    }
+
+    #[test]
+    fn test_open_day_carries_over_unfinished_tasks() {
+        let mut oswald = Oswald::new(MockDataStore);
+        for id in 1..=4 {
+            let mut task = Box::new(Task::new_with_id(id));
+            task.importance = (10 - id) as f32;
+            oswald.add_task(task);
+        }
+        let today = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        let tomorrow = today.succ_opt().unwrap();
+        let pick_two = |tasks: &[&Task]| tasks.iter().take(2).map(|task| task.id).collect();
+        oswald.set_daily_plan(DailyPlan::new(today, vec![3, 2]));
+
+        let mut done_task = Box::new(Task::new_with_id(2));
+        done_task.status = TaskStatus::Done;
+        oswald.add_task(done_task);
+
+        assert!(oswald.open_day(tomorrow, pick_two));
+        let plan = oswald.get_daily_plan().expect("Expected a plan");
+        assert_eq!(plan.date, tomorrow);
+        assert_eq!(plan.task_ids, vec![3, 1]);
+    }
+
+    #[test]
+    fn test_get_overview_hides_snoozed_tasks() {
+        let mut oswald = Oswald::new(MockDataStore);
+        for id in 1..=3 {
+            let mut task = Box::new(Task::new_with_id(id));
+            task.importance = (10 - id) as f32;
+            oswald.add_task(task);
+        }
+        let today = NaiveDate::from_ymd_opt(2024, 8, 5).unwrap();
+        oswald.snooze_task(1, today.succ_opt().unwrap(), today).unwrap();
+        let settings = PlanningSettings { daily_capacity: 1, ..PlanningSettings::default() };
+
+        assert!(oswald.open_day_with(&settings, today));
+        assert_eq!(oswald.get_daily_plan().map(|plan| plan.task_ids.clone()), Some(vec![2]));
+
+        let overview = oswald.get_overview(&settings, &HashSet::new(), today);
+        let column_ids: Vec<Vec<u32>> = overview.columns.iter()
+            .map(|column| column.tasks.iter().map(|task| task.id).collect())
+            .collect();
+        assert_eq!(column_ids, vec![vec![2], vec![3]]);
+        assert!(overview.backlog.is_empty());
+    }
+
+    #[test]
+    fn test_add_subtask_by_parent_id() {
+        /*
+         *      (1)
+         *       |
+         *      (2)
+         *       |
+         *      (3)
+         */
+        let mut oswald = Oswald::new(MockDataStore);
+        oswald.add_task(Box::new(Task::new_with_id(1)));
+        oswald.add_subtask(1, Box::new(Task::new_with_id(2))).unwrap();
+        oswald.add_subtask(2, Box::new(Task::new_with_id(3))).unwrap();
+
+        assert_eq!(oswald.get_task_path(3), Some(vec![1, 2]));
+        assert_eq!(oswald.get_task_path(1), Some(vec![]));
+        assert_eq!(oswald.get_task_path(4), None);
+        assert!(oswald.add_subtask(4, Box::new(Task::new_with_id(5))).is_err());
+        assert_eq!(oswald.get_all_tasks().len(), 3);
+    }
+
+    #[test]
+    fn test_add_quick_task() {
+        let mut oswald = Oswald::new(MockDataStore);
+        oswald.add_task(Box::new(Task::new_with_id(42)));
+        let today = NaiveDate::from_ymd_opt(2024, 8, 5).unwrap();
+
+        let id = oswald.add_quick_task(QuickAdd::parse("Write report #work under:42", today).unwrap()).unwrap();
+        assert_eq!(id, 43);
+        assert_eq!(oswald.get_task_path(43), Some(vec![42]));
+        assert!(oswald.get_task(43).unwrap().tags.contains("work"));
+
+        // Unknown parents leave the tasks as they were
+        assert!(oswald.add_quick_task(QuickAdd::parse("Orphan under:7", today).unwrap()).is_err());
+        assert_eq!(oswald.get_all_tasks().len(), 2);
+    }
+
+    #[test]
+    fn test_next_ids_run_out() {
+        let mut oswald = Oswald::new(MockDataStore);
+        assert_eq!(oswald.get_next_id().unwrap(), 1);
+        assert_eq!(oswald.get_next_person_id().unwrap(), 1);
+
+        oswald.add_task(Box::new(Task::new_with_id(u32::MAX)));
+        oswald.add_person(Person::new(u32::MAX, "Ada")).unwrap();
+        assert!(oswald.get_next_id().is_err());
+        assert!(oswald.get_next_person_id().is_err());
+        let today = NaiveDate::from_ymd_opt(2024, 8, 5).unwrap();
+        assert!(oswald.add_quick_task(QuickAdd::parse("One too many", today).unwrap()).is_err());
+        assert_eq!(oswald.get_all_tasks().len(), 1);
+    }
+
+    #[test]
+    fn test_delegate_tasks() {
+        /*
+         *      (1)        (3) done
+         *       |
+         *      (2)
+         */
+        let mut oswald = Oswald::new(MockDataStore);
+        let mut task = Task::new_with_id(1);
+        task.add_subtask(Box::new(Task::new_with_id(2)));
+        oswald.add_task(Box::new(task));
+        let mut task = Task::new_with_id(3);
+        task.status = TaskStatus::Done;
+        oswald.add_task(Box::new(task));
+        oswald.add_person(Person::new(1, "Ada")).unwrap();
+        oswald.add_person(Person::new(2, "Grace")).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2024, 8, 5).unwrap();
+
+        oswald.assign_task(1, Some(1), Some(monday + Duration::days(2))).unwrap();
+        oswald.assign_task(2, Some(1), Some(monday)).unwrap();
+        oswald.assign_task(3, Some(2), None).unwrap();
+        assert!(oswald.assign_task(1, Some(7), None).is_err());
+        assert!(oswald.assign_task(7, Some(1), None).is_err());
+
+        // Done tasks aren't owed anymore
+        let ids: Vec<u32> = oswald.get_waiting_for(1).into_iter().map(|task| task.id).collect();
+        assert_eq!(ids, vec![2, 1]);
+        let waiting_for = oswald.get_all_waiting_for();
+        assert_eq!(waiting_for.len(), 1);
+        assert_eq!(waiting_for[0].person.name, "Ada");
+
+        let ids: Vec<u32> = oswald.get_follow_ups(monday).into_iter().map(|task| task.id).collect();
+        assert_eq!(ids, vec![2]);
+
+        // Taking a task back drops its follow-up, and so does deleting its assignee
+        oswald.assign_task(2, None, Some(monday)).unwrap();
+        assert_eq!(oswald.get_task(2).unwrap().follow_up, None);
+        oswald.delete_person(1).unwrap();
+        assert_eq!(oswald.get_task(1).unwrap().assignee, None);
+        assert!(oswald.get_follow_ups(monday + Duration::days(7)).is_empty());
+        assert!(oswald.delete_person(1).is_err());
+        assert_eq!(oswald.get_next_person_id().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_shared_project_roles() {
+        /*
+         *   (1) Launch, owned by #10     (3) Hiring, owned by #20
+         *    |
+         *   (2) Landing page
+         */
+        let mut oswald = Oswald::new(MockDataStore);
+        let project_id = oswald.add_project(10, "Launch").unwrap();
+        oswald.add_project(20, "Hiring").unwrap();
+        oswald.share_project(10, project_id, 11, Some(Role::Editor)).unwrap();
+        oswald.share_project(10, project_id, 12, Some(Role::Viewer)).unwrap();
+
+        let mut task = Task::new_with_id(0);
+        task.desc = "Landing page".to_owned();
+        assert!(oswald.add_task_as(12, project_id, task.clone()).is_err());
+        let task_id = oswald.add_task_as(11, project_id, task).unwrap();
+        assert_eq!((project_id, task_id), (1, 3));
+        assert_eq!(oswald.get_project_id(task_id), Some(project_id));
+
+        // Viewers only get to look, and other projects stay out of sight
+        assert_eq!(oswald.get_role(12, task_id), Some(Role::Viewer));
+        assert!(oswald.check_role(12, task_id, Role::Editor).is_err());
+        let projects: Vec<(u32, Role)> = oswald.get_projects(11).into_iter().map(|(task, role)| (task.id, role)).collect();
+        assert_eq!(projects, vec![(1, Role::Editor)]);
+        assert_eq!(oswald.get_role(11, 2), None);
+
+        // Updates keep the subtree
+        let mut project = Task::new_with_id(project_id);
+        project.desc = "Launch v2".to_owned();
+        oswald.update_task_as(11, project).unwrap();
+        assert_eq!(oswald.get_task(project_id).unwrap().desc, "Launch v2");
+        assert_eq!(oswald.get_task_path(task_id), Some(vec![project_id]));
+
+        // Only owners manage the members, and the last one can't leave
+        assert!(oswald.share_project(11, project_id, 12, Some(Role::Editor)).is_err());
+        assert!(oswald.share_project(10, project_id, 10, None).is_err());
+        oswald.share_project(10, project_id, 11, Some(Role::Owner)).unwrap();
+        oswald.share_project(10, project_id, 10, None).unwrap();
+        assert_eq!(oswald.get_members(project_id).len(), 2);
+
+        assert!(oswald.delete_task_as(12, task_id).is_err());
+        oswald.delete_task_as(11, project_id).unwrap();
+        assert!(oswald.get_members(project_id).is_empty());
+        assert_eq!(oswald.get_all_tasks().len(), 1);
+    }
+
+    #[test]
+    fn test_quadrant_counts_and_suggestions() {
+        /*
+         *   (1) Eliminate       (4) Do        (6) Do, done
+         *    |                   |
+         *   (2) Do              (5) Schedule, due
+         *
+         *   (3) Eliminate
+         */
+        let mut oswald = Oswald::new(MockDataStore);
+        let quadrant_task = |id: u32, importance: f32, urgency: f32| {
+            let mut task = Task::new_with_id(id);
+            task.importance = importance;
+            task.urgency = urgency;
+            task
+        };
+        let mut task = quadrant_task(1, -10.0, -10.0);
+        task.add_subtask(Box::new(quadrant_task(2, 10.0, 10.0)));
+        oswald.add_task(Box::new(task));
+        oswald.add_task(Box::new(quadrant_task(3, -10.0, -10.0)));
+        let mut task = quadrant_task(4, 50.0, 50.0);
+        let mut subtask = quadrant_task(5, 50.0, -10.0);
+        subtask.due_date = NaiveDate::from_ymd_opt(2024, 8, 5);
+        task.add_subtask(Box::new(subtask));
+        oswald.add_task(Box::new(task));
+        let mut task = quadrant_task(6, 10.0, 10.0);
+        task.status = TaskStatus::Done;
+        oswald.add_task(Box::new(task));
+
+        let counts: Vec<usize> = oswald.get_quadrant_counts().into_values().collect();
+        assert_eq!(counts, vec![2, 1, 0, 2]);
+
+        // #1 still has pending work, and #5 is already due
+        let suggestions: Vec<(u32, SuggestedAction)> = oswald.get_suggestions().into_iter()
+            .map(|suggestion| (suggestion.task_id, suggestion.action))
+            .collect();
+        assert_eq!(suggestions.len(), 3);
+        assert!(suggestions.contains(&(3, SuggestedAction::Archive)));
+        assert!(!suggestions.iter().any(|(id, _)| *id == 1 || *id == 5));
+
+        // Raising the bar moves #2 out of the Do quadrant
+        oswald.set_quadrant_thresholds(QuadrantThresholds { importance: 20.0, urgency: 20.0 });
+        let ids: Vec<u32> = oswald.get_quadrant_tasks(Quadrant::Do).into_iter().map(|task| task.id).collect();
+        assert_eq!(ids, vec![4]);
+        assert_eq!(oswald.query(&"quadrant:eliminate status:open".parse().unwrap()).len(), 3);
+    }
+
+    #[test]
+    fn test_search_tasks_with_path() {
+        let mut oswald = Oswald::new(MockDataStore);
+        let mut task = Box::new(Task::new_with_id(1));
+        task.desc = "Release v2".to_owned();
+        let mut subtask = Box::new(Task::new_with_id(2));
+        subtask.desc = "Write release notes".to_owned();
+        task.add_subtask(subtask);
+        oswald.add_task(task);
+
+        let results = oswald.search("notes");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, 2);
+        assert_eq!(results[0].path, vec![1]);
+
+        let mut ids: Vec<u32> = oswald.search("release").into_iter().map(|result| result.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_search_index_follows_updates_and_deletions() {
+        let mut oswald = Oswald::new(MockDataStore);
+        let mut task = Box::new(Task::new_with_id(1));
+        task.desc = "Plan trip".to_owned();
+        let mut subtask = Box::new(Task::new_with_id(2));
+        subtask.desc = "Book flights".to_owned();
+        task.add_subtask(subtask);
+        oswald.add_task(task);
+
+        // Replacing the task without its subtask drops the subtask from the tree
+        let mut updated_task = Box::new(Task::new_with_id(1));
+        updated_task.desc = "Plan holidays".to_owned();
+        oswald.add_task(updated_task);
+
+        assert!(oswald.search("trip").is_empty());
+        assert!(oswald.search("flights").is_empty());
+        assert_eq!(oswald.search("holidays")[0].id, 1);
+
+        oswald.delete_task(1);
+        assert!(oswald.search("holidays").is_empty());
+    }
+
     #[tokio::test]
     async fn test_get_loaded_tasks() {
         let mut oswald = Oswald::new(MockDataStore);
@@ -0,0 +1,177 @@
+use std::collections::{BTreeSet, HashMap};
+use serde::{Serialize, Deserialize};
+use crate::core::tasks::{Task, TaskStatus};
+
+/* TEMPLATE ================================================================ */
+// Named subtree to repeat breakdowns with. Descriptions, notes and checklist items can hold
+// `{{placeholders}}`, filled in when the template is instantiated.
+const PLACEHOLDER_START: &str = "{{";
+const PLACEHOLDER_END: &str = "}}";
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Template {
+    pub name: String,
+    pub task: Task,
+}
+impl Template {
+    pub fn new(name: &str, task: Task) -> Self {
+        Template { name: name.trim().to_owned(), task }
+    }
+
+    fn get_texts(task: &Task) -> Vec<&str> {
+        let mut texts = vec![task.desc.as_str(), task.notes.as_str()];
+        texts.extend(task.checklist.iter().map(|item| item.text.as_str()));
+        for subtask in task.get_subtasks() {
+            texts.extend(Template::get_texts(subtask));
+        }
+        texts
+    }
+
+    pub fn get_placeholders(&self) -> BTreeSet<String> {
+        let mut placeholders = BTreeSet::new();
+        for mut text in Template::get_texts(&self.task) {
+            while let Some(start) = text.find(PLACEHOLDER_START) {
+                let rest = &text[start + PLACEHOLDER_START.len()..];
+                let Some(end) = rest.find(PLACEHOLDER_END) else {
+                    break;
+                };
+                let placeholder = rest[..end].trim();
+                if !placeholder.is_empty() {
+                    placeholders.insert(placeholder.to_owned());
+                }
+                text = &rest[end + PLACEHOLDER_END.len()..];
+            }
+        }
+        placeholders
+    }
+
+    fn fill(text: &str, values: &HashMap<String, String>) -> String {
+        let mut filled = String::new();
+        let mut text = text;
+        while let Some(start) = text.find(PLACEHOLDER_START) {
+            let rest = &text[start + PLACEHOLDER_START.len()..];
+            let Some(end) = rest.find(PLACEHOLDER_END) else {
+                break;
+            };
+            filled.push_str(&text[..start]);
+            match values.get(rest[..end].trim()) {
+                Some(value) => filled.push_str(value),
+                None => filled.push_str(&text[start..start + PLACEHOLDER_START.len() + end + PLACEHOLDER_END.len()])
+            }
+            text = &rest[end + PLACEHOLDER_END.len()..];
+        }
+        filled.push_str(text);
+        filled
+    }
+
+    // Fresh open tasks are handed out consecutive ids, parents first
+    fn instantiate_task(task: &Task, values: &HashMap<String, String>, next_id: &mut u32) -> Task {
+        let mut instance = task.clone();
+        for subtask in task.get_subtasks() {
+            instance.delete_subtask(subtask.id);
+        }
+        instance.id = *next_id;
+        *next_id += 1;
+        instance.status = TaskStatus::Open;
+        instance.due_date = None;
+        instance.deferred_until = None;
+        instance.placement = None;
+        instance.desc = Template::fill(&task.desc, values);
+        instance.notes = Template::fill(&task.notes, values);
+        instance.checklist.iter_mut().for_each(|item| {
+            item.text = Template::fill(&item.text, values);
+            item.done = false;
+        });
+        for subtask in task.get_subtasks() {
+            instance.add_subtask(Box::new(Template::instantiate_task(subtask, values, next_id)));
+        }
+        instance
+    }
+
+    // Copy of the subtree with every placeholder filled in, numbered from `first_id` on
+    pub fn instantiate(&self, values: &HashMap<String, String>, first_id: u32) -> anyhow::Result<Task> {
+        let missing: Vec<String> = self.get_placeholders()
+            .into_iter()
+            .filter(|placeholder| !values.contains_key(placeholder))
+            .collect();
+        if !missing.is_empty() {
+            anyhow::bail!("Missing values for {}", missing.join(", "));
+        }
+        let mut next_id = first_id;
+        Ok(Template::instantiate_task(&self.task, values, &mut next_id))
+    }
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod templates_tests {
+    use super::*;
+
+    fn release_template() -> Template {
+        /*
+         *        (1) Release {{version}}
+         *       /                       \
+         *  (2) Tag {{ version }}    (3) Announce
+         */
+        let mut task = Task::new_with_id(1);
+        task.desc = "Release {{version}}".to_owned();
+        task.status = TaskStatus::Done;
+        let mut tag_task = Task::new_with_id(2);
+        tag_task.desc = "Tag {{ version }}".to_owned();
+        let mut announce_task = Task::new_with_id(3);
+        announce_task.desc = "Announce".to_owned();
+        announce_task.add_checklist_item("Post in {{channel}}");
+        task.add_subtask(Box::new(tag_task));
+        task.add_subtask(Box::new(announce_task));
+        Template::new("Release", task)
+    }
+
+    #[test]
+    fn test_get_placeholders() {
+        let placeholders: Vec<String> = release_template().get_placeholders().into_iter().collect();
+        assert_eq!(placeholders, vec!["channel", "version"]);
+    }
+
+    #[test]
+    fn test_instantiate_template() {
+        let values = HashMap::from([
+            ("version".to_owned(), "v2.0".to_owned()),
+            ("channel".to_owned(), "#general".to_owned()),
+        ]);
+
+        let task = release_template().instantiate(&values, 10).unwrap();
+        assert_eq!(task.id, 10);
+        assert_eq!(task.desc, "Release v2.0");
+        assert_eq!(task.status, TaskStatus::Open);
+
+        let mut subtasks: Vec<(u32, String)> = task.get_all_subtasks().into_iter()
+            .map(|subtask| (subtask.id, subtask.desc.clone()))
+            .collect();
+        subtasks.sort();
+        assert_eq!(subtasks.len(), 2);
+        assert!(subtasks.iter().all(|(id, _)| (11..=12).contains(id)));
+        assert!(subtasks.iter().any(|(_, desc)| desc == "Tag v2.0"));
+
+        let checklist: Vec<&str> = task.get_all_subtasks().into_iter()
+            .flat_map(|subtask| subtask.checklist.iter().map(|item| item.text.as_str()))
+            .collect();
+        assert_eq!(checklist, vec!["Post in #general"]);
+    }
+
+    #[test]
+    fn test_instantiate_template_with_missing_values() {
+        let values = HashMap::from([("version".to_owned(), "v2.0".to_owned())]);
+
+        let err = release_template().instantiate(&values, 10).expect_err("Expected the channel to be missing");
+        assert_eq!(err.to_string(), "Missing values for channel");
+    }
+
+    #[test]
+    fn test_fill_leaves_unknown_text_alone() {
+        let values = HashMap::from([("name".to_owned(), "Ada".to_owned())]);
+
+        assert_eq!(Template::fill("Hi {{name}}, {{other}} and {{ unclosed", &values), "Hi Ada, {{other}} and {{ unclosed");
+    }
+}
+
+/* ========================================================================= */
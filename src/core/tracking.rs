@@ -0,0 +1,62 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Serialize, Deserialize};
+
+/* TIME ENTRY ============================================================== */
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TimeEntry {
+    pub task_id: u32,
+    pub start: DateTime<Utc>,
+    // `None` while the timer is running
+    pub stop: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub note: String,
+}
+impl TimeEntry {
+    pub fn new(task_id: u32, start: DateTime<Utc>, note: &str) -> Self {
+        TimeEntry {
+            task_id,
+            start,
+            stop: None,
+            note: note.to_owned()
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.stop.is_none()
+    }
+
+    pub fn get_duration(&self, now: DateTime<Utc>) -> Duration {
+        let stop = self.stop.unwrap_or(now);
+        (stop - self.start).max(Duration::zero())
+    }
+}
+
+/* TESTS =================================================================== */
+#[cfg(test)]
+mod tracking_tests {
+    use super::*;
+
+    #[test]
+    fn test_running_entry_duration_uses_now() {
+        let start = DateTime::from_timestamp(1_000, 0).unwrap();
+        let now = DateTime::from_timestamp(1_600, 0).unwrap();
+        let mut entry = TimeEntry::new(1, start, "");
+
+        assert!(entry.is_running());
+        assert_eq!(entry.get_duration(now), Duration::minutes(10));
+
+        entry.stop = Some(DateTime::from_timestamp(1_300, 0).unwrap());
+        assert!(!entry.is_running());
+        assert_eq!(entry.get_duration(now), Duration::minutes(5));
+    }
+
+    #[test]
+    fn test_duration_is_never_negative() {
+        let start = DateTime::from_timestamp(1_000, 0).unwrap();
+        let entry = TimeEntry::new(1, start, "");
+
+        assert_eq!(entry.get_duration(DateTime::from_timestamp(0, 0).unwrap()), Duration::zero());
+    }
+}
+
+/* ========================================================================= */
@@ -0,0 +1,4 @@
+pub mod core;
+pub mod adapters;
+pub mod clients;
+pub mod ports;
@@ -1,12 +1,19 @@
-use crate::core::tasks::Oswald;
-
-mod core;
-mod adapters;
-mod clients;
-mod ports;
+use tako::core::tasks::Oswald;
+use tako::{clients, ports};
 
 #[tokio::main]
 async fn main() {
+    #[cfg(feature = "cli")]
+    {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        if !args.is_empty() {
+            if let Err(err) = clients::cli::run(args, &mut std::io::stdout()).await {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+            return;
+        }
+    }
     let oswald = Oswald::new(ports::DummyStore);
     clients::wasm_app::start(oswald).await.unwrap();
 }
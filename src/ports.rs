@@ -1,14 +1,87 @@
 use async_trait::async_trait;
 use crate::core::tasks::{
+    Oswald,
     Task,
     BoxTaskVec
 };
+use crate::core::accounts::User;
+use crate::core::delegation::Person;
+use crate::core::planning::{DailyPlan, PlanningSettings};
+use crate::core::sharing::Membership;
+use crate::core::sync::SyncedTask;
+use crate::core::templates::Template;
+use crate::core::tracking::TimeEntry;
 use std::fmt::Debug;
 
 #[async_trait]
 pub trait DataStore: Debug {
     async fn write(&self, _tasks: Vec<&Task>) -> anyhow::Result<()>;
     async fn read(&self) -> anyhow::Result<BoxTaskVec>;
+
+    // Stores that don't keep track of time just drop the entries
+    async fn write_time_entries(&self, _entries: &[TimeEntry]) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn read_time_entries(&self) -> anyhow::Result<Vec<TimeEntry>> {
+        Ok(vec![])
+    }
+
+    // Only the latest plan is read back
+    async fn write_daily_plan(&self, _plan: &DailyPlan) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn read_daily_plan(&self) -> anyhow::Result<Option<DailyPlan>> {
+        Ok(None)
+    }
+
+    async fn write_templates(&self, _templates: &[&Template]) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn read_templates(&self) -> anyhow::Result<Vec<Template>> {
+        Ok(vec![])
+    }
+
+    async fn write_people(&self, _people: &[&Person]) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn read_people(&self) -> anyhow::Result<Vec<Person>> {
+        Ok(vec![])
+    }
+
+    // Only shared forests have members
+    async fn write_memberships(&self, _memberships: &[Membership]) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn read_memberships(&self) -> anyhow::Result<Vec<Membership>> {
+        Ok(vec![])
+    }
+
+    // What the server had at the last sync, for stores that sync with one
+    async fn write_sync_base(&self, _tasks: &[&SyncedTask]) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn read_sync_base(&self) -> anyhow::Result<Vec<SyncedTask>> {
+        Ok(vec![])
+    }
+
+    // `None` leaves the defaults in place
+    async fn write_planning_settings(&self, _settings: &PlanningSettings) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn read_planning_settings(&self) -> anyhow::Result<Option<PlanningSettings>> {
+        Ok(None)
+    }
+}
+
+// Server-wide storage for shared deployments, handing each user a forest of their own
+#[async_trait]
+pub trait AccountStore: Debug {
+    async fn write_users(&self, users: &[&User]) -> anyhow::Result<()>;
+    async fn read_users(&self) -> anyhow::Result<Vec<User>>;
+    // Not loaded yet
+    fn get_oswald(&self, owner_id: u32) -> Oswald;
+    // Holds the projects shared between users, not loaded yet either
+    fn get_shared_oswald(&self) -> Oswald;
 }
 
 #[derive(Debug, Default)]
@@ -56,3 +129,20 @@ impl DataStore for DummyStore {
         Ok(vec![])
     }
 }
+
+// Nothing outlives the server, which suits tests and throwaway deployments
+#[async_trait]
+impl AccountStore for DummyStore {
+    async fn write_users(&self, _users: &[&User]) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn read_users(&self) -> anyhow::Result<Vec<User>> {
+        Ok(vec![])
+    }
+    fn get_oswald(&self, _owner_id: u32) -> Oswald {
+        Oswald::new(DummyStore)
+    }
+    fn get_shared_oswald(&self) -> Oswald {
+        Oswald::new(DummyStore)
+    }
+}